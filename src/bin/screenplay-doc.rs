@@ -0,0 +1,403 @@
+//! `screenplay-doc` parses a screenplay PDF and prints reports about it.
+//!
+//! Run `screenplay-doc --help` for usage.
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use screenplay_doc_parser_rs::error::ParseError;
+use screenplay_doc_parser_rs::mupdf_basic_parser;
+use screenplay_doc_parser_rs::pdf_document::ElementIndentationsInches;
+use screenplay_doc_parser_rs::reports;
+use screenplay_doc_parser_rs::screenplay_document::{
+    Character, EnvironmentStrings, SPType, ScreenplayDocument, TimeOfDay, TimeOfDayCollection,
+    TransitionStrings,
+};
+
+const USAGE: &str = "\
+Usage: screenplay-doc [OPTIONS] <COMMAND> <PDF>
+
+Commands:
+  parse                         Print the parsed document as JSON
+  scenes                        List every scene, in order, with its length in eighths
+  characters                    List every speaking character
+  locations                     List every full location path
+  dialogue <CHARACTER>          List every line of dialogue for a character
+  pages-for scene <NUMBER>      List the pages a scene is on
+  pages-for location <PATH>     List the pages a location is on, i.e. \"HOUSE - KITCHEN\"
+  pages-for character <NAME>    List the pages a character speaks on
+
+Options:
+  --indentations <KEY=INCHES,...>  Override the US-Letter indentations (i.e. action=1.5,character=3.7)
+                                   Keys: pagewidth, pageheight, left, right, top, bottom,
+                                   action, character, dialogue, parenthetical
+                                   The indentations are deduced from the document if not given
+  --revision-marker <STRING>       The revision marker in the right margin (default: *)
+  --time-of-day <KEY=STRING>       Override a time of day string (i.e. day=JOUR); keys other than
+                                   day, night, morning, evening and afternoon are added as extras
+  --int <STRING>                   An interior environment string (replaces INT.)
+  --ext <STRING>                   An exterior environment string (replaces EXT.)
+  --int-ext <STRING>               A combined environment string (replaces INT./EXT. etc.)
+  --transition <STRING>            An extra right-aligned transition string
+  -h, --help                       Print this help
+
+Options which take a <STRING> can be given more than once.";
+
+enum Command {
+    Parse,
+    Scenes,
+    Characters,
+    Locations,
+    Dialogue(String),
+    PagesForScene(String),
+    PagesForLocation(String),
+    PagesForCharacter(String),
+}
+
+#[derive(Default)]
+struct ParseOptions {
+    element_indentations: Option<ElementIndentationsInches>,
+    revision_marker: Option<String>,
+    time_of_day_strs: Option<TimeOfDayCollection>,
+    environment_strs: Option<EnvironmentStrings>,
+    transition_strs: Option<TransitionStrings>,
+}
+
+fn get_indentations(arg: &str) -> Result<ElementIndentationsInches, String> {
+    let mut indentations = ElementIndentationsInches::us_letter_default();
+    for pair in arg.split(',') {
+        let Some((key, value)) = pair.split_once('=') else {
+            return Err(format!("expected KEY=INCHES, got '{}'", pair));
+        };
+        let Ok(inches) = value.trim().parse::<f64>() else {
+            return Err(format!("'{}' is not a number", value));
+        };
+        let field = match key.trim() {
+            "pagewidth" => &mut indentations.pagewidth,
+            "pageheight" => &mut indentations.pageheight,
+            "left" => &mut indentations.left,
+            "right" => &mut indentations.right,
+            "top" => &mut indentations.top,
+            "bottom" => &mut indentations.bottom,
+            "action" => &mut indentations.action,
+            "character" => &mut indentations.character,
+            "dialogue" => &mut indentations.dialogue,
+            "parenthetical" => &mut indentations.parenthetical,
+            other => return Err(format!("unknown indentation '{}'", other)),
+        };
+        *field = inches;
+    }
+    Ok(indentations)
+}
+
+fn add_time_of_day(time_of_day_strs: &mut TimeOfDayCollection, arg: &str) -> Result<(), String> {
+    let Some((key, string)) = arg.split_once('=') else {
+        return Err(format!("expected KEY=STRING, got '{}'", arg));
+    };
+    let string = string.to_string();
+    match key {
+        "day" => time_of_day_strs.day = TimeOfDay::Day(string),
+        "night" => time_of_day_strs.night = TimeOfDay::Night(string),
+        "morning" => time_of_day_strs.morning = TimeOfDay::Morning(string),
+        "evening" => time_of_day_strs.evening = TimeOfDay::Evening(string),
+        "afternoon" => time_of_day_strs.afternoon = TimeOfDay::Afternoon(string),
+        _ => {
+            time_of_day_strs
+                .extras
+                .get_or_insert_with(HashMap::new)
+                .insert(key.to_string(), string);
+        }
+    }
+    Ok(())
+}
+
+/// Adds an environment string, replacing the defaults for that environment the first time.
+fn add_environment(
+    environment_strs: &mut Option<EnvironmentStrings>,
+    replaced: &mut Vec<&'static str>,
+    environment: &'static str,
+    string: String,
+) {
+    let environment_strs = environment_strs.get_or_insert_with(EnvironmentStrings::default);
+    let strings = match environment {
+        "int" => &mut environment_strs.int,
+        "ext" => &mut environment_strs.ext,
+        _ => &mut environment_strs.combo,
+    };
+    if !replaced.contains(&environment) {
+        strings.clear();
+        replaced.push(environment);
+    }
+    strings.push(string);
+}
+
+fn get_args(args: Vec<String>) -> Result<(Command, String, ParseOptions), String> {
+    let mut options = ParseOptions::default();
+    let mut replaced_environments: Vec<&'static str> = Vec::new();
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            positional.push(arg);
+            continue;
+        }
+        if arg == "-h" || arg == "--help" {
+            return Err(String::new());
+        }
+        let Some(value) = args.next() else {
+            return Err(format!("{} needs a value", arg));
+        };
+        match arg.as_str() {
+            "--indentations" => options.element_indentations = Some(get_indentations(&value)?),
+            "--revision-marker" => options.revision_marker = Some(value),
+            "--time-of-day" => add_time_of_day(
+                options
+                    .time_of_day_strs
+                    .get_or_insert_with(TimeOfDayCollection::default),
+                &value,
+            )?,
+            "--int" => add_environment(
+                &mut options.environment_strs,
+                &mut replaced_environments,
+                "int",
+                value,
+            ),
+            "--ext" => add_environment(
+                &mut options.environment_strs,
+                &mut replaced_environments,
+                "ext",
+                value,
+            ),
+            "--int-ext" => add_environment(
+                &mut options.environment_strs,
+                &mut replaced_environments,
+                "combo",
+                value,
+            ),
+            "--transition" => options
+                .transition_strs
+                .get_or_insert_with(TransitionStrings::default)
+                .right_aligned
+                .push(value),
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    let Some(path) = positional.pop() else {
+        return Err("missing the PDF path".into());
+    };
+    let mut positional = positional.into_iter();
+    let command = match (
+        positional.next().as_deref(),
+        positional.next(),
+        positional.next(),
+    ) {
+        (Some("parse"), None, None) => Command::Parse,
+        (Some("scenes"), None, None) => Command::Scenes,
+        (Some("characters"), None, None) => Command::Characters,
+        (Some("locations"), None, None) => Command::Locations,
+        (Some("dialogue"), Some(name), None) => Command::Dialogue(name),
+        (Some("pages-for"), Some(kind), Some(target)) => match kind.as_str() {
+            "scene" => Command::PagesForScene(target),
+            "location" => Command::PagesForLocation(target),
+            "character" => Command::PagesForCharacter(target),
+            other => return Err(format!("can't get pages for '{}'", other)),
+        },
+        (Some(command), _, _) => {
+            return Err(format!("unknown or incomplete command '{}'", command));
+        }
+        (None, _, _) => return Err("missing a command".into()),
+    };
+    Ok((command, path, options))
+}
+
+fn get_screenplay_doc(
+    path: String,
+    options: ParseOptions,
+) -> Result<ScreenplayDocument, ParseError> {
+    mupdf_basic_parser::get_screenplay_doc_from_filepath(
+        path,
+        options.element_indentations,
+        options.revision_marker,
+        options.time_of_day_strs,
+        options.environment_strs,
+        options.transition_strs,
+    )
+}
+
+/// The printed page number if there is one, otherwise the page's position in the document.
+fn get_page_label(screenplay_doc: &ScreenplayDocument, page_idx: usize) -> String {
+    match screenplay_doc
+        .pages
+        .get(page_idx)
+        .and_then(|p| p.page_number.as_ref())
+    {
+        Some(page_number) => page_number.trim_end_matches('.').to_string(),
+        None => (page_idx + 1).to_string(),
+    }
+}
+
+fn get_line_text(line: &screenplay_doc_parser_rs::screenplay_document::Line) -> String {
+    line.text_elements
+        .iter()
+        .filter(|e| {
+            !matches!(
+                e.element_type,
+                Some(SPType::SP_SCENENUM) | Some(SPType::SP_LINE_REVISION_MARKER)
+            )
+        })
+        .map(|e| e.text.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn get_character<'a>(
+    screenplay_doc: &'a ScreenplayDocument,
+    name: &str,
+) -> Result<&'a Character, String> {
+    screenplay_doc
+        .characters
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("no character named '{}'", name))
+}
+
+fn print_pages(screenplay_doc: &ScreenplayDocument, pages: Option<Vec<usize>>) {
+    let Some(mut pages) = pages else {
+        return;
+    };
+    pages.sort();
+    pages.dedup();
+    let labels: Vec<String> = pages
+        .into_iter()
+        .map(|idx| get_page_label(screenplay_doc, idx))
+        .collect();
+    println!("{}", labels.join(", "));
+}
+
+fn run(command: Command, screenplay_doc: &ScreenplayDocument) -> Result<(), String> {
+    match command {
+        Command::Parse => {
+            let json = serde_json::to_string_pretty(screenplay_doc).map_err(|e| e.to_string())?;
+            println!("{}", json);
+        }
+        Command::Scenes => {
+            for (_, scene) in reports::get_all_scenes_ordered(screenplay_doc).unwrap_or_default() {
+                let heading = screenplay_doc
+                    .pages
+                    .get(scene.start.page)
+                    .and_then(|p| p.lines.get(scene.start.line))
+                    .map(get_line_text)
+                    .unwrap_or_default();
+                let number = scene.number.as_ref().map(|n| n.0.as_str()).unwrap_or("-");
+                let length = scene
+                    .length_eighths
+                    .map(reports::format_eighths)
+                    .unwrap_or_default();
+                println!(
+                    "{}\t{}\tp. {}\t{}",
+                    number,
+                    heading,
+                    get_page_label(screenplay_doc, scene.start.page),
+                    length
+                );
+            }
+        }
+        Command::Characters => {
+            let mut names: Vec<&String> =
+                screenplay_doc.characters.iter().map(|c| &c.name).collect();
+            names.sort();
+            for name in names {
+                println!("{}", name);
+            }
+        }
+        Command::Locations => {
+            let mut paths: Vec<String> = screenplay_doc
+                .locations
+                .iter()
+                .filter(|(_, location)| location.sublocations.is_empty())
+                .filter_map(|(id, _)| {
+                    reports::get_full_string_for_location_path(screenplay_doc, id)
+                })
+                .collect();
+            paths.sort();
+            for path in paths {
+                println!("{}", path);
+            }
+        }
+        Command::Dialogue(name) => {
+            let character = get_character(screenplay_doc, &name)?;
+            let lines = reports::get_all_lines_of_dialogue_for_character(screenplay_doc, character)
+                .unwrap_or_default();
+            for (coordinate, line) in lines {
+                println!(
+                    "p. {}\t{}",
+                    get_page_label(screenplay_doc, coordinate.page),
+                    get_line_text(line)
+                );
+            }
+        }
+        Command::PagesForScene(number) => {
+            let Some((scene_id, _)) = screenplay_doc
+                .scenes
+                .iter()
+                .find(|(_, s)| s.number.as_ref().is_some_and(|n| n.0 == number))
+            else {
+                return Err(format!("no scene numbered '{}'", number));
+            };
+            let pages = reports::get_all_pages_for_scene(screenplay_doc, scene_id)
+                .map(|pages| pages.into_iter().map(|(idx, _)| idx).collect());
+            print_pages(screenplay_doc, pages);
+        }
+        Command::PagesForLocation(path) => {
+            let path: Vec<String> = path.split(" - ").map(|s| s.trim().to_string()).collect();
+            let Some((location_id, remaining)) =
+                reports::location_path_exists(screenplay_doc, &path)
+            else {
+                return Err(format!("no location '{}'", path.join(" - ")));
+            };
+            if !remaining.is_empty() {
+                return Err(format!("no location '{}'", path.join(" - ")));
+            }
+            let pages = reports::get_all_pages_for_location(screenplay_doc, location_id)
+                .map(|pages| pages.into_iter().map(|(idx, _)| idx).collect());
+            print_pages(screenplay_doc, pages);
+        }
+        Command::PagesForCharacter(name) => {
+            let character = get_character(screenplay_doc, &name)?;
+            let pages = reports::get_all_pages_for_character_speaking(screenplay_doc, character)
+                .map(|pages| pages.into_iter().map(|(idx, _)| idx).collect());
+            print_pages(screenplay_doc, pages);
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let (command, path, options) = match get_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(e) if e.is_empty() => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let screenplay_doc = match get_screenplay_doc(path, options) {
+        Ok(doc) => doc,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = run(command, &screenplay_doc) {
+        eprintln!("error: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,204 @@
+//! Breakdown sheets, which list everything a scene needs for production:
+//! its heading, length, cast, and the props and extras it introduces.
+//!
+//! Props and extras are found the way a first-pass breakdown finds them by hand:
+//! screenplays introduce them in CAPS within action lines, the first time they appear.
+
+use serde::{Deserialize, Serialize};
+
+use crate::reports;
+use crate::screenplay_document::{
+    Environment, Line, SPType, SceneID, ScreenplayDocument, TimeOfDay,
+};
+use crate::writer_common::{
+    get_content_text, get_csv_record, get_environment_text, get_time_of_day_text,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownSheet {
+    pub scene_id: SceneID,
+    pub number: Option<String>,
+    pub heading: String,
+    pub environment: Environment,
+    /// The full location path, i.e. `INT. HOUSE - KITCHEN`, or every side of a split heading
+    pub location: Option<String>,
+    /// The scene's effective time of day (see `reports::get_effective_time_of_day`)
+    pub time_of_day: Option<TimeOfDay>,
+    pub length_eighths: Option<u64>,
+    /// The names of every character who speaks in the scene, sorted
+    pub cast: Vec<String>,
+    /// Anything in CAPS in an action line which hasn't been mentioned in an earlier scene
+    pub props_and_extras: Vec<String>,
+}
+
+/// Trims punctuation from the ends of a word, i.e. `GUN.` or `"RADIO"`.
+fn trim_punctuation(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+/// A word is in caps if it has at least two letters, and none of them are lowercase.
+///
+/// Single letters are skipped, so `A` and `I` don't count.
+fn is_caps_word(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).count() >= 2
+        && !word.chars().any(|c| c.is_lowercase())
+}
+
+/// Gets every run of words in caps within an action paragraph, i.e. `a SAWED-OFF SHOTGUN`
+/// has `SAWED-OFF SHOTGUN`. Runs can wrap onto the next line.
+fn get_caps_phrases(paragraph: &[&Line]) -> Vec<String> {
+    let mut phrases: Vec<String> = Vec::new();
+    let mut current_phrase: Vec<&str> = Vec::new();
+    for element in paragraph.iter().flat_map(|l| &l.text_elements) {
+        if element.element_type != Some(SPType::SP_ACTION) {
+            continue;
+        }
+        for word in element.text.split_whitespace() {
+            let trimmed = trim_punctuation(word);
+            if is_caps_word(trimmed) {
+                current_phrase.push(trimmed);
+                // Punctuation at the end of a word ends the phrase
+                if trimmed.len()
+                    == word
+                        .trim_start_matches(|c: char| !c.is_alphanumeric())
+                        .len()
+                {
+                    continue;
+                }
+            }
+            if !current_phrase.is_empty() {
+                phrases.push(current_phrase.join(" "));
+                current_phrase.clear();
+            }
+        }
+    }
+    if !current_phrase.is_empty() {
+        phrases.push(current_phrase.join(" "));
+    }
+    phrases
+}
+
+/// Gets the breakdown sheet of every scene, in document order. Omitted scenes are skipped.
+pub fn get_all_breakdown_sheets(
+    screenplay_doc: &ScreenplayDocument,
+) -> Option<Vec<BreakdownSheet>> {
+    let character_names: Vec<&String> = screenplay_doc.characters.iter().map(|c| &c.name).collect();
+    let mut mentioned: Vec<String> = Vec::new();
+    let mut sheets: Vec<BreakdownSheet> = Vec::new();
+
+    let all_scenes = reports::get_all_scenes_ordered(screenplay_doc)?;
+    let times_of_day = reports::get_effective_times_of_day(&all_scenes);
+    for ((scene_id, scene), time_of_day) in all_scenes.into_iter().zip(times_of_day) {
+        if scene.omitted {
+            continue;
+        }
+        let scene_lines: Vec<&Line> = reports::get_all_lines_for_scene(screenplay_doc, scene_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+
+        let heading = scene_lines
+            .first()
+            .map(|line| get_content_text(line))
+            .unwrap_or_default();
+
+        // Action lines without blank lines between them are wrapped from the same paragraph
+        let mut paragraphs: Vec<Vec<&Line>> = Vec::new();
+        let mut previous_was_action = false;
+        for line in &scene_lines {
+            let is_action = line.line_type == Some(SPType::SP_ACTION);
+            if is_action && previous_was_action && line.preceding_empty_lines == 0 {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.push(line);
+                }
+            } else if is_action {
+                paragraphs.push(vec![line]);
+            }
+            previous_was_action = is_action;
+        }
+
+        let mut props_and_extras: Vec<String> = Vec::new();
+        for paragraph in &paragraphs {
+            for phrase in get_caps_phrases(paragraph) {
+                if character_names.contains(&&phrase) || mentioned.contains(&phrase) {
+                    continue;
+                }
+                mentioned.push(phrase.clone());
+                props_and_extras.push(phrase);
+            }
+        }
+
+        let mut cast: Vec<String> = reports::get_characters_for_scene(screenplay_doc, scene_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| c.name.clone())
+            .collect();
+        cast.sort();
+
+        sheets.push(BreakdownSheet {
+            scene_id: *scene_id,
+            number: scene.number.as_ref().map(|n| n.0.clone()),
+            heading,
+            environment: scene.environment.clone(),
+            location: reports::get_full_string_for_scene_locations(screenplay_doc, scene),
+            time_of_day: time_of_day.cloned(),
+            length_eighths: scene.length_eighths,
+            cast,
+            props_and_extras,
+        });
+    }
+    Some(sheets)
+}
+
+/// Gets the breakdown sheet for a single scene.
+pub fn get_breakdown_sheet(
+    screenplay_doc: &ScreenplayDocument,
+    scene_id: &SceneID,
+) -> Option<BreakdownSheet> {
+    // Props and extras depend on every scene before this one
+    get_all_breakdown_sheets(screenplay_doc)?
+        .into_iter()
+        .find(|sheet| sheet.scene_id == *scene_id)
+}
+
+/// Exports breakdown sheets as CSV, with a header row and one row per scene.
+///
+/// Cast, props and extras are separated by `; ` within their columns.
+pub fn get_breakdown_csv(sheets: &[BreakdownSheet]) -> String {
+    let mut csv = get_csv_record(&[
+        "Scene".into(),
+        "Heading".into(),
+        "INT/EXT".into(),
+        "Location".into(),
+        "Time of Day".into(),
+        "Pages".into(),
+        "Cast".into(),
+        "Props/Extras".into(),
+    ]);
+    for sheet in sheets {
+        csv.push_str(&get_csv_record(&[
+            sheet.number.clone().unwrap_or_default(),
+            sheet.heading.clone(),
+            get_environment_text(&sheet.environment),
+            sheet.location.clone().unwrap_or_default(),
+            sheet
+                .time_of_day
+                .as_ref()
+                .map(get_time_of_day_text)
+                .unwrap_or_default(),
+            sheet
+                .length_eighths
+                .map(reports::format_eighths)
+                .unwrap_or_default(),
+            sheet.cast.join("; "),
+            sheet.props_and_extras.join("; "),
+        ]));
+    }
+    csv
+}
+
+/// Exports breakdown sheets as a JSON array, with the same fields as `BreakdownSheet`.
+pub fn get_breakdown_json(sheets: &[BreakdownSheet]) -> Result<String, serde_json::Error> {
+    serde_json::to_string(sheets)
+}
@@ -0,0 +1,175 @@
+//! Day Out of Days (DOOD) reports, which show the days each cast member works.
+//!
+//! Each shoot day is a group of scenes. A character works on a day if they speak in
+//! any of its scenes. Every day between their first and last work day is a hold day.
+//!
+//! | Code  | Meaning                                       |
+//! |-------|-----------------------------------------------|
+//! | `SW`  | Start Work: the character's first work day    |
+//! | `W`   | Work                                          |
+//! | `H`   | Hold: not working, but between work days      |
+//! | `WF`  | Work Finish: the character's last work day    |
+//! | `SWF` | Start Work Finish: the character's only day   |
+
+use serde::{Deserialize, Serialize};
+
+use crate::reports;
+use crate::screenplay_document::{Character, SceneID, ScreenplayDocument};
+use crate::writer_common::get_csv_record;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DoodCode {
+    StartWork,
+    Work,
+    Hold,
+    WorkFinish,
+    StartWorkFinish,
+}
+impl DoodCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DoodCode::StartWork => "SW",
+            DoodCode::Work => "W",
+            DoodCode::Hold => "H",
+            DoodCode::WorkFinish => "WF",
+            DoodCode::StartWorkFinish => "SWF",
+        }
+    }
+}
+
+/// One character's row in a Day Out of Days report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DoodRow {
+    pub character: Character,
+    /// One code per shoot day; `None` before the character starts and after they finish
+    pub days: Vec<Option<DoodCode>>,
+    pub work_days: usize,
+    pub hold_days: usize,
+    /// The index of the character's first work day
+    pub start_day: usize,
+    /// The index of the character's last work day
+    pub finish_day: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DayOutOfDays {
+    /// The scenes shot on each day
+    pub shoot_days: Vec<Vec<SceneID>>,
+    /// Sorted by the number of work days, most first, then by name
+    pub rows: Vec<DoodRow>,
+}
+
+/// Gets a Day Out of Days report with one scene per shoot day.
+///
+/// `shoot_order` is the order the scenes are shot in. If it's `None`, scenes are shot in script order.
+pub fn get_day_out_of_days(
+    screenplay_doc: &ScreenplayDocument,
+    shoot_order: Option<Vec<SceneID>>,
+) -> Option<DayOutOfDays> {
+    let shoot_order = match shoot_order {
+        Some(order) => order,
+        None => reports::get_all_scenes_ordered(screenplay_doc)?
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect(),
+    };
+    get_day_out_of_days_for_shoot_days(
+        screenplay_doc,
+        shoot_order.into_iter().map(|id| vec![id]).collect(),
+    )
+}
+
+/// Gets a Day Out of Days report, where each shoot day can have multiple scenes.
+///
+/// Returns `None` if nobody speaks in any of the scenes.
+pub fn get_day_out_of_days_for_shoot_days(
+    screenplay_doc: &ScreenplayDocument,
+    shoot_days: Vec<Vec<SceneID>>,
+) -> Option<DayOutOfDays> {
+    let mut rows: Vec<DoodRow> = Vec::new();
+
+    for character in &screenplay_doc.characters {
+        let Some(scenes) =
+            reports::get_all_scenes_with_character_speaking(screenplay_doc, character)
+        else {
+            continue;
+        };
+        let works: Vec<bool> = shoot_days
+            .iter()
+            .map(|day| scenes.iter().any(|(id, _)| day.contains(id)))
+            .collect();
+        let (Some(start_day), Some(finish_day)) = (
+            works.iter().position(|w| *w),
+            works.iter().rposition(|w| *w),
+        ) else {
+            continue;
+        };
+
+        let days: Vec<Option<DoodCode>> = works
+            .iter()
+            .enumerate()
+            .map(|(day, works)| match works {
+                true if start_day == finish_day => Some(DoodCode::StartWorkFinish),
+                true if day == start_day => Some(DoodCode::StartWork),
+                true if day == finish_day => Some(DoodCode::WorkFinish),
+                true => Some(DoodCode::Work),
+                false if start_day < day && day < finish_day => Some(DoodCode::Hold),
+                false => None,
+            })
+            .collect();
+
+        rows.push(DoodRow {
+            character: character.clone(),
+            work_days: works.iter().filter(|w| **w).count(),
+            hold_days: days.iter().filter(|d| **d == Some(DoodCode::Hold)).count(),
+            days,
+            start_day,
+            finish_day,
+        });
+    }
+    if rows.is_empty() {
+        return None;
+    }
+    rows.sort_by(|a, b| {
+        b.work_days
+            .cmp(&a.work_days)
+            .then_with(|| a.character.name.cmp(&b.character.name))
+    });
+
+    Some(DayOutOfDays { shoot_days, rows })
+}
+
+/// Exports a Day Out of Days report as CSV, with one column per shoot day,
+/// followed by the work, hold and total day counts, and the start and finish days.
+///
+/// Days are numbered from 1.
+pub fn get_day_out_of_days_csv(day_out_of_days: &DayOutOfDays) -> String {
+    let mut header: Vec<String> = vec!["Cast".into()];
+    header.extend((1..=day_out_of_days.shoot_days.len()).map(|day| format!("Day {}", day)));
+    header.extend([
+        "Work".into(),
+        "Hold".into(),
+        "Total".into(),
+        "Start".into(),
+        "Finish".into(),
+    ]);
+
+    let mut csv = get_csv_record(&header);
+    for row in &day_out_of_days.rows {
+        let mut record: Vec<String> = vec![row.character.name.clone()];
+        record.extend(
+            row.days
+                .iter()
+                .map(|code| code.map(|c| c.as_str().to_string()).unwrap_or_default()),
+        );
+        record.extend([
+            row.work_days.to_string(),
+            row.hold_days.to_string(),
+            (row.work_days + row.hold_days).to_string(),
+            (row.start_day + 1).to_string(),
+            (row.finish_day + 1).to_string(),
+        ]);
+        csv.push_str(&get_csv_record(&record));
+    }
+    csv
+}
@@ -0,0 +1,172 @@
+//! Diagnostics point out the parts of a parsed `ScreenplayDocument` which probably need
+//! a human to review them, i.e. words which didn't match any indentation, or scene headings
+//! without a time of day.
+//!
+//! Each diagnostic has a confidence score between `0.0` and `1.0`, for how likely it is that
+//! the element at its coordinate was typed correctly anyway.
+
+use serde::{Deserialize, Serialize};
+
+use crate::screenplay_document::{
+    Line, SPType, SceneHeadingElement, ScreenplayCoordinate, ScreenplayDocument,
+};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub coordinate: ScreenplayCoordinate,
+    pub severity: Severity,
+    pub message: String,
+    /// How likely it is that the element is typed correctly anyway, from `0.0` to `1.0`.
+    pub confidence: f64,
+}
+
+fn get_scene_number_text(text: &str) -> &str {
+    text.trim_matches('*').trim_matches('.')
+}
+
+/// Scene numbers in the left and right margins should match.
+fn get_scene_number_diagnostic(
+    line: &Line,
+    coordinate: &ScreenplayCoordinate,
+) -> Option<Diagnostic> {
+    let mut scene_numbers = line
+        .text_elements
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.element_type == Some(SPType::SP_SCENENUM))
+        .map(|(idx, e)| (idx, get_scene_number_text(&e.text)))
+        .filter(|(_, text)| !text.is_empty());
+    let (_, first_number) = scene_numbers.next()?;
+    let (mismatched_idx, mismatched_number) =
+        scene_numbers.find(|(_, text)| text != &first_number)?;
+    Some(Diagnostic {
+        coordinate: ScreenplayCoordinate {
+            element: Some(mismatched_idx as u64),
+            ..coordinate.clone()
+        },
+        severity: Severity::Warning,
+        message: format!(
+            "scene numbers in the margins don't match: '{}' and '{}'",
+            first_number, mismatched_number
+        ),
+        confidence: 0.5,
+    })
+}
+
+fn get_scene_heading_diagnostics(
+    line: &Line,
+    coordinate: &ScreenplayCoordinate,
+) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    // `CONTINUOUS`, `LATER`... take their time of day from the scene before
+    let has_time_of_day = line.text_elements.iter().any(|e| {
+        matches!(
+            e.element_type,
+            Some(SPType::SP_SCENE_HEADING(
+                SceneHeadingElement::TimeOfDay | SceneHeadingElement::Continuity
+            ))
+        )
+    });
+    if !has_time_of_day {
+        diagnostics.push(Diagnostic {
+            coordinate: coordinate.clone(),
+            severity: Severity::Warning,
+            message: "scene heading has no time of day".into(),
+            confidence: 0.8,
+        });
+    }
+
+    diagnostics.extend(get_scene_number_diagnostic(line, coordinate));
+
+    // SlugOther is expected after a time of day (i.e. `NIGHT - CONTINUOUS`),
+    // anywhere else it's just a guess
+    let mut previous_type: Option<SPType> = None;
+    for (e_index, element) in line.text_elements.iter().enumerate() {
+        let is_separator =
+            element.element_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator));
+        if element.element_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::SlugOther))
+            && !matches!(
+                previous_type,
+                Some(SPType::SP_SCENE_HEADING(
+                    SceneHeadingElement::TimeOfDay | SceneHeadingElement::SlugOther
+                ))
+            )
+        {
+            diagnostics.push(Diagnostic {
+                coordinate: ScreenplayCoordinate {
+                    element: Some(e_index as u64),
+                    ..coordinate.clone()
+                },
+                severity: Severity::Warning,
+                message: format!(
+                    "couldn't tell what '{}' is in this scene heading",
+                    element.text
+                ),
+                confidence: 0.3,
+            });
+        }
+        if !is_separator {
+            previous_type = element.element_type;
+        }
+    }
+    diagnostics
+}
+
+/// Checks a parsed document for anything which should be reviewed.
+///
+/// These checks only look at the typed document, so they work on a document from any parser.
+/// The PDF parser can also report diagnostics about the layout of the PDF itself;
+/// see `pdf_parser::get_screenplay_doc_and_diagnostics_from_pdf_obj`.
+pub fn get_diagnostics(screenplay_doc: &ScreenplayDocument) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for (p_index, page) in screenplay_doc.pages.iter().enumerate() {
+        for (l_index, line) in page.lines.iter().enumerate() {
+            let coordinate = ScreenplayCoordinate {
+                page: p_index,
+                line: l_index,
+                element: None,
+            };
+
+            match line.line_type {
+                Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line)) => {
+                    diagnostics.extend(get_scene_heading_diagnostics(line, &coordinate));
+                }
+                None | Some(SPType::NONE) if !line.text_elements.is_empty() => {
+                    diagnostics.push(Diagnostic {
+                        coordinate: coordinate.clone(),
+                        severity: Severity::Warning,
+                        message: "couldn't tell what type of line this is".into(),
+                        confidence: 0.0,
+                    });
+                    continue;
+                }
+                _ => {}
+            }
+
+            for (e_index, element) in line.text_elements.iter().enumerate() {
+                if element.element_type.is_some() {
+                    continue;
+                }
+                diagnostics.push(Diagnostic {
+                    coordinate: ScreenplayCoordinate {
+                        element: Some(e_index as u64),
+                        ..coordinate.clone()
+                    },
+                    severity: Severity::Info,
+                    message: format!("'{}' has no type", element.text),
+                    confidence: 0.0,
+                });
+            }
+        }
+    }
+    diagnostics
+}
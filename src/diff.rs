@@ -0,0 +1,379 @@
+//! Compares two drafts of a screenplay, to find exactly what changed between them.
+//!
+//! Scenes are matched by scene number first, then by their heading text.
+//! Lines within matched scenes are compared by their content text, so changes to
+//! scene numbers, page numbers and revision markers aren't changes to the line.
+//!
+//! A scene is omitted if it's missing from the new draft, or if the new draft has an
+//! `OMITTED` placeholder in its place (`Scene.omitted`).
+//!
+//! A changed line in the new draft without a revision marker (`Line.revised`)
+//! was probably missed by the writer, so these are reported separately.
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser_common::is_scene_content_line;
+use crate::reports;
+use crate::screenplay_document::{Line, SceneID, ScreenplayCoordinate, ScreenplayDocument};
+use crate::writer_common::{get_content_text, is_title_page};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordChange {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LineChange {
+    /// `None` if the line was added
+    pub old: Option<ScreenplayCoordinate>,
+    /// `None` if the line was removed
+    pub new: Option<ScreenplayCoordinate>,
+    pub words: Vec<WordChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneDiff {
+    /// `None` if the scene was added
+    pub old_scene: Option<SceneID>,
+    /// `None` if the scene was omitted without an `OMITTED` placeholder
+    pub new_scene: Option<SceneID>,
+    /// If the scene was omitted from the new draft, or replaced with an `OMITTED` placeholder
+    pub omitted: bool,
+    /// The scene number in the new draft, or the old draft if it was omitted
+    pub number: Option<String>,
+    /// The heading in the new draft, or the old draft if it was omitted
+    pub heading: String,
+    /// If the scene is out of order, compared to the other scenes in both drafts
+    pub moved: bool,
+    pub line_changes: Vec<LineChange>,
+    pub old_length_eighths: Option<u64>,
+    pub new_length_eighths: Option<u64>,
+}
+impl SceneDiff {
+    pub fn is_added(&self) -> bool {
+        self.old_scene.is_none()
+    }
+    pub fn is_omitted(&self) -> bool {
+        self.omitted
+    }
+    pub fn is_changed(&self) -> bool {
+        !self.line_changes.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScreenplayDiff {
+    /// Every scene from both drafts, in new draft order.
+    /// Omitted scenes come after the scene which was before them in the old draft,
+    /// or in place of their `OMITTED` placeholder.
+    pub scenes: Vec<SceneDiff>,
+    /// Names of characters who are only in the new draft, sorted
+    pub added_characters: Vec<String>,
+    /// Names of characters who are only in the old draft, sorted
+    pub removed_characters: Vec<String>,
+    /// Pages, not counting the title page
+    pub old_page_count: usize,
+    pub new_page_count: usize,
+    /// Added and changed lines in the new draft without a revision marker
+    pub unmarked_changes: Vec<ScreenplayCoordinate>,
+}
+impl ScreenplayDiff {
+    pub fn page_count_delta(&self) -> i64 {
+        self.new_page_count as i64 - self.old_page_count as i64
+    }
+}
+
+/// Gets the index pairs of the longest common subsequence of `a` and `b`.
+fn get_common_subsequence<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Gets the word-level changes between two lines of text.
+pub fn get_word_changes(old_text: &str, new_text: &str) -> Vec<WordChange> {
+    let old_words: Vec<&str> = old_text.split_whitespace().collect();
+    let new_words: Vec<&str> = new_text.split_whitespace().collect();
+    let mut changes: Vec<WordChange> = Vec::new();
+    let (mut old_idx, mut new_idx) = (0, 0);
+    for (old_match, new_match) in get_common_subsequence(&old_words, &new_words)
+        .into_iter()
+        .chain([(old_words.len(), new_words.len())])
+    {
+        changes.extend(
+            old_words[old_idx..old_match]
+                .iter()
+                .map(|w| WordChange::Removed(w.to_string())),
+        );
+        changes.extend(
+            new_words[new_idx..new_match]
+                .iter()
+                .map(|w| WordChange::Added(w.to_string())),
+        );
+        if let Some(word) = new_words.get(new_match) {
+            changes.push(WordChange::Same(word.to_string()));
+        }
+        (old_idx, new_idx) = (old_match + 1, new_match + 1);
+    }
+    changes
+}
+
+/// Gets the content lines of a scene, with their text.
+fn get_scene_text_lines<'a>(
+    screenplay_doc: &'a ScreenplayDocument,
+    scene_id: &SceneID,
+) -> Vec<(ScreenplayCoordinate, &'a Line, String)> {
+    reports::get_all_lines_for_scene(screenplay_doc, scene_id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, line)| is_scene_content_line(line))
+        .map(|(coordinate, line)| (coordinate, line, get_content_text(line)))
+        .filter(|(_, _, text)| !text.is_empty())
+        .collect()
+}
+
+/// Gets the changes between two lists of lines.
+///
+/// Runs of removed and added lines between unchanged lines are paired up as changed lines.
+fn get_line_changes(
+    old_lines: &[(ScreenplayCoordinate, &Line, String)],
+    new_lines: &[(ScreenplayCoordinate, &Line, String)],
+) -> Vec<LineChange> {
+    let old_texts: Vec<&String> = old_lines.iter().map(|(_, _, t)| t).collect();
+    let new_texts: Vec<&String> = new_lines.iter().map(|(_, _, t)| t).collect();
+
+    let mut changes: Vec<LineChange> = Vec::new();
+    let (mut old_idx, mut new_idx) = (0, 0);
+    for (old_match, new_match) in get_common_subsequence(&old_texts, &new_texts)
+        .into_iter()
+        .chain([(old_lines.len(), new_lines.len())])
+    {
+        let removed = &old_lines[old_idx..old_match];
+        let added = &new_lines[new_idx..new_match];
+        for pair_idx in 0..removed.len().max(added.len()) {
+            let old = removed.get(pair_idx);
+            let new = added.get(pair_idx);
+            changes.push(LineChange {
+                old: old.map(|(c, _, _)| c.clone()),
+                new: new.map(|(c, _, _)| c.clone()),
+                words: get_word_changes(
+                    old.map(|(_, _, t)| t.as_str()).unwrap_or_default(),
+                    new.map(|(_, _, t)| t.as_str()).unwrap_or_default(),
+                ),
+            });
+        }
+        (old_idx, new_idx) = (old_match + 1, new_match + 1);
+    }
+    changes
+}
+
+/// Gets the scenes which are out of order: everything not in the longest run of
+/// scenes which are in the same order in both drafts.
+fn get_moved_scenes(old_indices: &[usize]) -> Vec<bool> {
+    // Longest increasing subsequence of the old indices, in new draft order
+    let mut lengths: Vec<usize> = vec![1; old_indices.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; old_indices.len()];
+    for i in 0..old_indices.len() {
+        for j in 0..i {
+            if old_indices[j] < old_indices[i] && lengths[j] + 1 > lengths[i] {
+                lengths[i] = lengths[j] + 1;
+                previous[i] = Some(j);
+            }
+        }
+    }
+    let mut moved = vec![true; old_indices.len()];
+    let mut current = lengths
+        .iter()
+        .enumerate()
+        .max_by_key(|(idx, length)| (**length, std::cmp::Reverse(*idx)))
+        .map(|(idx, _)| idx);
+    while let Some(idx) = current {
+        moved[idx] = false;
+        current = previous[idx];
+    }
+    moved
+}
+
+fn get_heading_text(screenplay_doc: &ScreenplayDocument, scene_id: &SceneID) -> String {
+    let Some(scene) = screenplay_doc.scenes.get(scene_id) else {
+        return String::new();
+    };
+    screenplay_doc
+        .pages
+        .get(scene.start.page)
+        .and_then(|page| page.lines.get(scene.start.line))
+        .map(get_content_text)
+        .unwrap_or_default()
+}
+
+fn get_character_names(screenplay_doc: &ScreenplayDocument) -> Vec<&String> {
+    let mut names: Vec<&String> = screenplay_doc.characters.iter().map(|c| &c.name).collect();
+    names.sort();
+    names
+}
+
+/// Compares an old draft of a screenplay to a new one.
+pub fn get_screenplay_diff(
+    old_doc: &ScreenplayDocument,
+    new_doc: &ScreenplayDocument,
+) -> ScreenplayDiff {
+    let old_scenes = reports::get_all_scenes_ordered(old_doc).unwrap_or_default();
+    let new_scenes = reports::get_all_scenes_ordered(new_doc).unwrap_or_default();
+    let old_headings: Vec<String> = old_scenes
+        .iter()
+        .map(|(id, _)| get_heading_text(old_doc, id).to_uppercase())
+        .collect();
+
+    // Match by number first, so renamed scenes are still found
+    let mut matches: Vec<Option<usize>> = vec![None; new_scenes.len()];
+    let mut old_matched: Vec<bool> = vec![false; old_scenes.len()];
+    for (new_idx, (_, new_scene)) in new_scenes.iter().enumerate() {
+        let Some(number) = &new_scene.number else {
+            continue;
+        };
+        if let Some(old_idx) = old_scenes
+            .iter()
+            .enumerate()
+            .position(|(idx, (_, s))| !old_matched[idx] && s.number.as_ref() == Some(number))
+        {
+            matches[new_idx] = Some(old_idx);
+            old_matched[old_idx] = true;
+        }
+    }
+    for (new_idx, (new_id, _)) in new_scenes.iter().enumerate() {
+        if matches[new_idx].is_some() {
+            continue;
+        }
+        let heading = get_heading_text(new_doc, new_id).to_uppercase();
+        if let Some(old_idx) = old_headings
+            .iter()
+            .enumerate()
+            .position(|(idx, h)| !old_matched[idx] && *h == heading)
+        {
+            matches[new_idx] = Some(old_idx);
+            old_matched[old_idx] = true;
+        }
+    }
+
+    let matched_old_indices: Vec<usize> = matches.iter().flatten().copied().collect();
+    let mut moved = get_moved_scenes(&matched_old_indices).into_iter();
+
+    let mut unmarked_changes: Vec<ScreenplayCoordinate> = Vec::new();
+    let omitted_diff = |old_idx: usize| {
+        let (old_id, old_scene) = old_scenes[old_idx];
+        SceneDiff {
+            old_scene: Some(*old_id),
+            new_scene: None,
+            omitted: true,
+            number: old_scene.number.as_ref().map(|n| n.0.clone()),
+            heading: get_heading_text(old_doc, old_id),
+            moved: false,
+            line_changes: get_line_changes(&get_scene_text_lines(old_doc, old_id), &[]),
+            old_length_eighths: old_scene.length_eighths,
+            new_length_eighths: None,
+        }
+    };
+    // Omitted scenes go after the scene which was before them
+    let get_omitted_after = |old_idx: Option<usize>| {
+        let first = old_idx.map(|idx| idx + 1).unwrap_or_default();
+        (first..old_scenes.len())
+            .take_while(|idx| !old_matched[*idx])
+            .map(omitted_diff)
+            .collect::<Vec<SceneDiff>>()
+    };
+
+    let mut scenes: Vec<SceneDiff> = get_omitted_after(None);
+    for ((new_id, new_scene), old_idx) in new_scenes.iter().zip(&matches) {
+        let is_moved = old_idx.is_some() && moved.next().unwrap_or_default();
+        let old_scene = old_idx.map(|idx| old_scenes[idx]);
+        // A placeholder for a scene which isn't in the old draft either
+        if new_scene.omitted && old_scene.is_none() {
+            continue;
+        }
+        let omitted = new_scene.omitted && old_scene.is_some_and(|(_, s)| !s.omitted);
+
+        // Like a missing scene, every line of an omitted scene is removed
+        let new_lines = if omitted {
+            Vec::new()
+        } else {
+            get_scene_text_lines(new_doc, new_id)
+        };
+        let old_lines = old_scene
+            .map(|(old_id, _)| get_scene_text_lines(old_doc, old_id))
+            .unwrap_or_default();
+        let line_changes = get_line_changes(&old_lines, &new_lines);
+
+        for change in &line_changes {
+            let Some(coordinate) = &change.new else {
+                continue;
+            };
+            if new_lines
+                .iter()
+                .any(|(c, line, _)| c == coordinate && !line.revised)
+            {
+                unmarked_changes.push(coordinate.clone());
+            }
+        }
+
+        scenes.push(SceneDiff {
+            old_scene: old_scene.map(|(old_id, _)| *old_id),
+            new_scene: Some(**new_id),
+            omitted,
+            number: new_scene.number.as_ref().map(|n| n.0.clone()),
+            heading: match old_scene {
+                Some((old_id, _)) if omitted => get_heading_text(old_doc, old_id),
+                _ => get_heading_text(new_doc, new_id),
+            },
+            moved: is_moved && !omitted,
+            line_changes,
+            old_length_eighths: old_scene.and_then(|(_, s)| s.length_eighths),
+            new_length_eighths: new_scene.length_eighths,
+        });
+        if old_idx.is_some() {
+            scenes.extend(get_omitted_after(*old_idx));
+        }
+    }
+
+    let old_names = get_character_names(old_doc);
+    let new_names = get_character_names(new_doc);
+
+    ScreenplayDiff {
+        scenes,
+        added_characters: new_names
+            .iter()
+            .filter(|name| !old_names.contains(name))
+            .map(|name| name.to_string())
+            .collect(),
+        removed_characters: old_names
+            .iter()
+            .filter(|name| !new_names.contains(name))
+            .map(|name| name.to_string())
+            .collect(),
+        old_page_count: old_doc.pages.iter().filter(|p| !is_title_page(p)).count(),
+        new_page_count: new_doc.pages.iter().filter(|p| !is_title_page(p)).count(),
+        unmarked_changes,
+    }
+}
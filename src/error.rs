@@ -0,0 +1,59 @@
+//! The error type returned by all of the parsers.
+
+use std::fmt;
+
+use crate::screenplay_document::ScreenplayCoordinate;
+
+/// Why a document couldn't be parsed into a `ScreenplayDocument`.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The document has no pages (or, for plain-text formats, no text at all).
+    EmptyDocument,
+    /// The document has pages, but none of them have any text on them.
+    NoContentPages,
+    /// A scene heading starts with an environment (`INT.`, `EXT.`...)
+    /// which doesn't match any of the `EnvironmentStrings`.
+    UnknownEnvironment {
+        coordinate: ScreenplayCoordinate,
+        text: String,
+    },
+    /// The document isn't in the expected format, i.e. an FDX file which isn't valid XML.
+    InvalidDocument(String),
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The PDF backend couldn't read the file.
+    BackendError(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyDocument => write!(f, "the document is empty"),
+            ParseError::NoContentPages => write!(f, "the document has no pages with any content"),
+            ParseError::UnknownEnvironment { coordinate, text } => write!(
+                f,
+                "unknown scene environment '{}' at page {}, line {}",
+                text, coordinate.page, coordinate.line
+            ),
+            ParseError::InvalidDocument(reason) => write!(f, "invalid document: {}", reason),
+            ParseError::Io(e) => write!(f, "could not read the document: {}", e),
+            ParseError::BackendError(e) => write!(f, "the PDF backend failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::BackendError(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
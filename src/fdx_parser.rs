@@ -0,0 +1,470 @@
+//! This module is responsible for interpreting a Final Draft (`.fdx`) document
+//! into the same semantically-typed ScreenplayDocument structure that the PDF parser produces.
+//!
+//! FDX already states the type of every paragraph, so nothing needs to be deduced from indentation.
+//!
+//! FDX doesn't store where its pages break, so pages are estimated from the document's own
+//! `PageLayout` margins and `ElementSettings` indentations. Each scene heading is then
+//! moved onto the page Final Draft says it starts on (`SceneProperties Page`),
+//! so that `ScreenplayCoordinate` page indices match the printed script.
+
+use std::collections::HashMap;
+
+use roxmltree::Node;
+
+use crate::error::ParseError;
+use crate::parser_common::{
+    DialogueSide, DocumentBuilder, get_character_line, get_scene_heading_line, get_text_elements,
+    get_title_page_line_type, get_wrapped_lines,
+};
+use crate::pdf_document;
+use crate::screenplay_document::{
+    self, EnvironmentStrings, Line, Page, PageFormat, RevisionColor, RevisionColorSequence, SPType,
+    SceneID, TimeOfDayCollection,
+};
+use crate::story_days::StoryDayMarkers;
+
+// 12-point Courier
+const LINES_PER_INCH: f64 = 6.0;
+const CHARACTERS_PER_INCH: f64 = 10.0;
+const POINTS_PER_LINE: f64 = 12.0;
+
+/// How far (in RGB) a Final Draft revision `Color` can be from a revision color, and still be it.
+const MAX_REVISION_COLOR_DISTANCE: f64 = 64.0;
+
+/// The width and spacing of a paragraph type, from the FDX `ElementSettings`.
+#[derive(Clone, Copy, Debug)]
+struct ElementLayout {
+    width: usize,
+    space_before: u64,
+    starts_new_page: bool,
+}
+
+/// Final Draft's default element settings, for a US-Letter screenplay.
+fn get_default_layout(paragraph_type: &str) -> ElementLayout {
+    let (left, right, space_before) = match paragraph_type {
+        "Scene Heading" | "Shot" => (1.5, 7.5, 2),
+        "Action" => (1.5, 7.5, 1),
+        "Character" => (3.5, 7.25, 1),
+        "Parenthetical" => (3.0, 5.5, 0),
+        "Dialogue" => (2.5, 6.0, 0),
+        "Transition" => (5.5, 7.1, 1),
+        _ => (1.5, 7.5, 0),
+    };
+    ElementLayout {
+        width: ((right - left) * CHARACTERS_PER_INCH) as usize,
+        space_before,
+        starts_new_page: false,
+    }
+}
+
+fn get_f64_attribute(node: &Node, name: &str) -> Option<f64> {
+    node.attribute(name)?.trim().parse::<f64>().ok()
+}
+
+fn get_element_layouts(root: &Node) -> HashMap<String, ElementLayout> {
+    let mut layouts: HashMap<String, ElementLayout> = HashMap::new();
+
+    for element_settings in root
+        .children()
+        .filter(|n| n.has_tag_name("ElementSettings"))
+    {
+        let Some(paragraph_type) = element_settings.attribute("Type") else {
+            continue;
+        };
+        let mut layout = get_default_layout(paragraph_type);
+        let Some(paragraph_spec) = element_settings
+            .children()
+            .find(|n| n.has_tag_name("ParagraphSpec"))
+        else {
+            layouts.insert(paragraph_type.to_string(), layout);
+            continue;
+        };
+
+        if let Some(left) = get_f64_attribute(&paragraph_spec, "LeftIndent")
+            && let Some(right) = get_f64_attribute(&paragraph_spec, "RightIndent")
+            && right > left
+        {
+            layout.width = ((right - left) * CHARACTERS_PER_INCH) as usize;
+        }
+        if let Some(space_before) = get_f64_attribute(&paragraph_spec, "SpaceBefore") {
+            layout.space_before = (space_before / POINTS_PER_LINE).round() as u64;
+        }
+        layout.starts_new_page = paragraph_spec.attribute("StartsNewPage") == Some("Yes");
+        layouts.insert(paragraph_type.to_string(), layout);
+    }
+    layouts
+}
+
+/// Gets the number of lines that fit between the top and bottom margins of a page.
+fn get_lines_per_page(root: &Node) -> u64 {
+    let page_layout = root.children().find(|n| n.has_tag_name("PageLayout"));
+
+    let page_height_inches = page_layout
+        .and_then(|layout| layout.children().find(|n| n.has_tag_name("PageSize")))
+        .and_then(|page_size| get_f64_attribute(&page_size, "Height"))
+        .unwrap_or(11.0);
+    let top_margin_pts = page_layout
+        .and_then(|layout| get_f64_attribute(&layout, "TopMargin"))
+        .unwrap_or(72.0);
+    let bottom_margin_pts = page_layout
+        .and_then(|layout| get_f64_attribute(&layout, "BottomMargin"))
+        .unwrap_or(72.0);
+
+    let content_height_inches = page_height_inches - (top_margin_pts + bottom_margin_pts) / 72.0;
+    (content_height_inches * LINES_PER_INCH).floor().max(1.0) as u64
+}
+
+/// Gets the page format from the `PageSize` of the `PageLayout`, if there is one.
+fn get_page_format(root: &Node) -> Option<PageFormat> {
+    let page_size = root
+        .children()
+        .find(|n| n.has_tag_name("PageLayout"))?
+        .children()
+        .find(|n| n.has_tag_name("PageSize"))?;
+    pdf_document::PageSize {
+        width: get_f64_attribute(&page_size, "Width")? * 72.0,
+        height: get_f64_attribute(&page_size, "Height")? * 72.0,
+    }
+    .get_page_format()
+}
+
+/// Gets the revision color nearest to a Final Draft revision `Color`, i.e. `#9999CCCCFFFF`.
+///
+/// Final Draft colors have 16 bits per channel, so only the high byte of each is used.
+/// They're compared to both the paper color of each revision, and the text color Final Draft
+/// usually shows it in (i.e. black for White, blue for Blue).
+/// Returns `None` if the color isn't close to any of them.
+fn get_revision_color_for_hex(hex: &str) -> Option<RevisionColor> {
+    let hex = hex.trim().trim_start_matches('#');
+    let channel_len = match hex.len() {
+        12 => 4,
+        6 => 2,
+        _ => return None,
+    };
+    let mut rgb = [0.0; 3];
+    for (idx, channel) in rgb.iter_mut().enumerate() {
+        let start = idx * channel_len;
+        *channel = f64::from(u8::from_str_radix(hex.get(start..start + 2)?, 16).ok()?);
+    }
+
+    let colors: [(RevisionColor, [u8; 3]); 18] = [
+        (RevisionColor::White, [255, 255, 255]),
+        (RevisionColor::White, [0, 0, 0]),
+        (RevisionColor::Blue, [153, 204, 255]),
+        (RevisionColor::Blue, [0, 0, 255]),
+        (RevisionColor::Pink, [255, 204, 229]),
+        (RevisionColor::Pink, [255, 0, 255]),
+        (RevisionColor::Yellow, [255, 255, 153]),
+        (RevisionColor::Yellow, [204, 153, 0]),
+        (RevisionColor::Green, [204, 255, 204]),
+        (RevisionColor::Green, [0, 153, 0]),
+        (RevisionColor::Goldenrod, [255, 214, 112]),
+        (RevisionColor::Goldenrod, [218, 165, 32]),
+        (RevisionColor::Buff, [240, 220, 180]),
+        (RevisionColor::Buff, [204, 153, 102]),
+        (RevisionColor::Salmon, [255, 170, 150]),
+        (RevisionColor::Salmon, [250, 128, 114]),
+        (RevisionColor::Cherry, [255, 153, 170]),
+        (RevisionColor::Cherry, [222, 49, 99]),
+    ];
+    colors
+        .into_iter()
+        .map(|(color, reference)| {
+            let distance = rgb
+                .iter()
+                .zip(reference)
+                .map(|(channel, reference)| (channel - f64::from(reference)).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            (color, distance)
+        })
+        .filter(|(_, distance)| *distance <= MAX_REVISION_COLOR_DISTANCE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(color, _)| color)
+}
+
+/// Gets the names of all revision sets, by their `ID`.
+///
+/// A set with no name is named after its revision color, or skipped if its `Color`
+/// isn't close to any revision color.
+fn get_revision_sets(root: &Node) -> HashMap<u32, String> {
+    let mut revision_sets: HashMap<u32, String> = HashMap::new();
+    let Some(revisions) = root.children().find(|n| n.has_tag_name("Revisions")) else {
+        return revision_sets;
+    };
+    for revision in revisions.children().filter(|n| n.has_tag_name("Revision")) {
+        let Some(id) = revision
+            .attribute("ID")
+            .and_then(|id| id.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let name = match revision.attribute("Name") {
+            Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+            _ => match revision
+                .attribute("Color")
+                .and_then(get_revision_color_for_hex)
+            {
+                Some(color) => color.name().to_string(),
+                None => continue,
+            },
+        };
+        revision_sets.insert(id, name);
+    }
+    revision_sets
+}
+
+/// Gets the full text of a paragraph, and the highest revision set ID of any of its text.
+fn get_paragraph_text(paragraph: &Node) -> (String, Option<u32>) {
+    let mut text = String::new();
+    let mut revision_id: Option<u32> = None;
+    for text_node in paragraph.children().filter(|n| n.has_tag_name("Text")) {
+        text.push_str(text_node.text().unwrap_or_default());
+        let text_revision = text_node
+            .attribute("RevisionID")
+            .or(text_node.attribute("Revision"))
+            .and_then(|id| id.parse::<u32>().ok())
+            .filter(|id| *id > 0);
+        revision_id = revision_id.max(text_revision);
+    }
+    (
+        text.replace(['\t', '\n'], " ").trim().to_string(),
+        revision_id,
+    )
+}
+
+/// Gets the `SPType` for an FDX `Paragraph Type`.
+fn get_type_for_paragraph(paragraph_type: &str, side: DialogueSide) -> SPType {
+    match paragraph_type {
+        "Action" => SPType::SP_ACTION,
+        "Character" => side.character_type(),
+        "Dialogue" => side.dialogue_type(),
+        "Parenthetical" => side.parenthetical_type(),
+        "Transition" => SPType::SP_TRANSITION,
+        "Shot" => SPType::SP_SHOT_ANGLE,
+        _ => SPType::SP_OTHER,
+    }
+}
+
+/// FDX title pages are free-form, so the type of each paragraph is guessed from its
+/// alignment, its text, and the paragraphs before it.
+fn parse_title_page(root: &Node) -> Option<Page> {
+    let content = root
+        .children()
+        .find(|n| n.has_tag_name("TitlePage"))?
+        .children()
+        .find(|n| n.has_tag_name("Content"))?;
+
+    let mut title_page = Page::default();
+    let mut previous_type: Option<SPType> = None;
+    let mut empty_lines: u64 = 0;
+
+    for paragraph in content.children().filter(|n| n.has_tag_name("Paragraph")) {
+        let (text, _) = get_paragraph_text(&paragraph);
+        if text.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        let centered = paragraph.attribute("Alignment") == Some("Center");
+        let has_title = title_page
+            .lines
+            .iter()
+            .any(|l| l.line_type == Some(SPType::TP_TITLE));
+
+        let element_type = get_title_page_line_type(&text, centered, previous_type, has_title);
+        title_page.lines.push(Line {
+            text_elements: get_text_elements(&text, Some(element_type)),
+            line_type: Some(element_type),
+            preceding_empty_lines: if title_page.lines.is_empty() {
+                0
+            } else {
+                empty_lines
+            },
+            ..Default::default()
+        });
+        previous_type = Some(element_type);
+        empty_lines = 0;
+    }
+
+    if title_page.lines.is_empty() {
+        return None;
+    }
+    Some(title_page)
+}
+
+/// Parses an FDX document from a string.
+///
+/// Returns `ParseError::InvalidDocument` if the XML is malformed or isn't a Final Draft document,
+/// and `ParseError::EmptyDocument` if the document has no content.
+pub fn get_screenplay_doc_from_fdx_str(
+    fdx_text: &str,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    let time_of_day_strs = time_of_day_strs_opt.unwrap_or_default();
+    let environment_strs = env_strs_opt.unwrap_or_default();
+
+    let xml_doc = roxmltree::Document::parse(fdx_text)
+        .map_err(|e| ParseError::InvalidDocument(e.to_string()))?;
+    let root = xml_doc.root_element();
+    if !root.has_tag_name("FinalDraft") {
+        return Err(ParseError::InvalidDocument(
+            "the root element isn't <FinalDraft>".into(),
+        ));
+    }
+    let content = root
+        .children()
+        .find(|n| n.has_tag_name("Content"))
+        .ok_or(ParseError::EmptyDocument)?;
+
+    let element_layouts = get_element_layouts(&root);
+    let get_layout = |paragraph_type: &str| -> ElementLayout {
+        element_layouts
+            .get(paragraph_type)
+            .copied()
+            .unwrap_or(get_default_layout(paragraph_type))
+    };
+    let revision_sets = get_revision_sets(&root);
+
+    let mut builder = DocumentBuilder::new(&time_of_day_strs, get_lines_per_page(&root));
+
+    if let Some(title_page) = parse_title_page(&root) {
+        builder.screenplay_doc.pages.push(title_page);
+    }
+
+    // The highest revision set on each page, by page index
+    let mut page_revisions: HashMap<usize, u32> = HashMap::new();
+    let mut current_scene: Option<SceneID> = None;
+
+    // Dual dialogue paragraphs are nested inside of a regular paragraph,
+    // so flatten them out first, with the column they belong to
+    let mut paragraphs: Vec<(Node, DialogueSide)> = Vec::new();
+    for paragraph in content.children().filter(|n| n.has_tag_name("Paragraph")) {
+        let Some(dual_dialogue) = paragraph
+            .children()
+            .find(|n| n.has_tag_name("DualDialogue"))
+        else {
+            paragraphs.push((paragraph, DialogueSide::Single));
+            continue;
+        };
+        let mut side = DialogueSide::Left;
+        let mut seen_character = false;
+        for dual_paragraph in dual_dialogue
+            .children()
+            .filter(|n| n.has_tag_name("Paragraph"))
+        {
+            if dual_paragraph.attribute("Type") == Some("Character") {
+                if seen_character {
+                    side = DialogueSide::Right;
+                }
+                seen_character = true;
+            }
+            paragraphs.push((dual_paragraph, side));
+        }
+    }
+
+    for (paragraph, side) in paragraphs {
+        let paragraph_type = paragraph.attribute("Type").unwrap_or("General");
+        let (text, revision_id) = get_paragraph_text(&paragraph);
+        let layout = get_layout(paragraph_type);
+
+        if paragraph.attribute("StartsNewPage") == Some("Yes") || layout.starts_new_page {
+            builder.page_break();
+        }
+        if text.is_empty() {
+            builder.add_blank_lines(1);
+            continue;
+        }
+        builder.add_blank_lines(layout.space_before);
+
+        let mut environment = None;
+        let mut new_lines: Vec<Line> = match paragraph_type {
+            "Scene Heading" => {
+                if let Some(page_number) = paragraph
+                    .children()
+                    .find(|n| n.has_tag_name("SceneProperties"))
+                    .and_then(|properties| properties.attribute("Page"))
+                    .and_then(|page| page.trim().parse::<usize>().ok())
+                {
+                    builder.sync_to_page_number(page_number);
+                }
+                let scene_number = paragraph
+                    .attribute("Number")
+                    .map(|num| num.trim().to_string())
+                    .filter(|num| !num.is_empty());
+                let (line, env) = get_scene_heading_line(
+                    &text,
+                    scene_number,
+                    &time_of_day_strs,
+                    &environment_strs,
+                );
+                environment = Some(env);
+                vec![line]
+            }
+            "Character" => vec![get_character_line(&text, side)],
+            _ => {
+                let element_type = get_type_for_paragraph(paragraph_type, side);
+                let line_type = match paragraph_type {
+                    "Dialogue" | "Parenthetical" => side.dialogue_line_type(element_type),
+                    _ => element_type,
+                };
+                get_wrapped_lines(&text, element_type, line_type, layout.width.max(1))
+            }
+        };
+        let centered = paragraph.attribute("Alignment") == Some("Center");
+        for line in &mut new_lines {
+            line.revised = revision_id.is_some();
+            line.centered = centered;
+        }
+
+        let first_line_idx = builder.add_element(new_lines, environment);
+        if paragraph_type == "Scene Heading" {
+            current_scene = builder.current_page.lines[first_line_idx].scene_id;
+        }
+
+        let Some(revision_id) = revision_id else {
+            continue;
+        };
+        let page_idx = builder.screenplay_doc.pages.len();
+        let page_revision = page_revisions.entry(page_idx).or_insert(revision_id);
+        *page_revision = (*page_revision).max(revision_id);
+        if let Some(scene) = current_scene.and_then(|id| builder.screenplay_doc.scenes.get_mut(&id))
+        {
+            scene.revised = true;
+        }
+    }
+
+    let mut screenplay_doc = builder.finish();
+    if screenplay_doc.pages.is_empty() {
+        return Err(ParseError::EmptyDocument);
+    }
+
+    let page_format = get_page_format(&root);
+    for page in screenplay_doc.pages.iter_mut() {
+        page.page_format = page_format;
+    }
+
+    for (page_idx, revision_id) in &page_revisions {
+        if let Some(page) = screenplay_doc.pages.get_mut(*page_idx) {
+            page.revised = true;
+            page.revision_label = revision_sets.get(revision_id).cloned();
+        }
+    }
+
+    screenplay_doc.set_revisions(&RevisionColorSequence::default());
+
+    screenplay_doc.set_story_days(&StoryDayMarkers::default(), &HashMap::new());
+
+    Ok(screenplay_doc)
+}
+
+/// Reads and parses an FDX document from a file.
+pub fn get_screenplay_doc_from_fdx_filepath(
+    path: String,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    let fdx_text = std::fs::read_to_string(path)?;
+    get_screenplay_doc_from_fdx_str(&fdx_text, time_of_day_strs_opt, env_strs_opt)
+}
@@ -0,0 +1,472 @@
+//! This module writes a ScreenplayDocument out as a Final Draft (`.fdx`) document.
+//!
+//! Lines which were wrapped onto multiple lines in the source document are joined back
+//! into a single paragraph. Revised lines are marked with the revision set
+//! named by their page's `revision_label`.
+//!
+//! Final Draft repaginates the script itself, but each scene heading keeps
+//! the page it started on (`SceneProperties Page`), so `fdx_parser` can restore the pagination.
+
+use std::collections::BTreeSet;
+
+use crate::parser_common::get_environment;
+use crate::reports;
+use crate::screenplay_document::{
+    EnvironmentStrings, Line, Page, SPType, ScreenplayDocument, TimeOfDayCollection,
+};
+use crate::writer_common::{
+    get_content_text, get_scene_number, get_text_for_elements, is_title_page,
+};
+
+/// The revision set name used for revised pages which don't have a revision label.
+const DEFAULT_REVISION_NAME: &str = "Revised Pages";
+
+#[derive(Clone, Debug, PartialEq)]
+struct FdxParagraph {
+    paragraph_type: &'static str,
+    text: String,
+    revision_id: Option<usize>,
+    number: Option<String>,
+    page: Option<String>,
+    centered: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FdxElement {
+    Paragraph(FdxParagraph),
+    DualDialogue(Vec<FdxParagraph>),
+}
+
+/// Escapes text for use in XML content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Gets the FDX `Paragraph Type` for a `Line`'s type.
+///
+/// Returns `None` for lines which don't belong in the script, like page headers.
+fn get_paragraph_type(line_type: &Option<SPType>) -> Option<&'static str> {
+    match line_type {
+        Some(SPType::SP_SCENE_HEADING(_)) | Some(SPType::SP_OMITTED) => Some("Scene Heading"),
+        Some(SPType::SP_ACTION) => Some("Action"),
+        Some(SPType::SP_CHARACTER) => Some("Character"),
+        Some(SPType::SP_PARENTHETICAL) => Some("Parenthetical"),
+        Some(SPType::SP_DIALOGUE) => Some("Dialogue"),
+        Some(SPType::SP_TRANSITION) => Some("Transition"),
+        Some(SPType::SP_SHOT_ANGLE) => Some("Shot"),
+        Some(SPType::SP_OTHER) => Some("General"),
+        _ => None,
+    }
+}
+
+/// Adds a paragraph, or joins it onto the previous paragraph if this line was wrapped from it.
+fn push_paragraph(
+    paragraphs: &mut Vec<FdxParagraph>,
+    new_paragraph: FdxParagraph,
+    continues_previous: bool,
+) {
+    if continues_previous
+        && let Some(previous) = paragraphs.last_mut()
+        && previous.paragraph_type == new_paragraph.paragraph_type
+        && previous.centered == new_paragraph.centered
+        && !matches!(previous.paragraph_type, "Scene Heading" | "Character")
+    {
+        previous.text.push(' ');
+        previous.text.push_str(&new_paragraph.text);
+        previous.revision_id = previous.revision_id.max(new_paragraph.revision_id);
+        return;
+    }
+    paragraphs.push(new_paragraph);
+}
+
+/// Splits a dual dialogue line into the paragraphs for its left and right columns.
+///
+/// A single line may contain both columns, if it was parsed from a PDF.
+fn push_dual_dialogue_line(
+    line: &Line,
+    revision_id: Option<usize>,
+    left: &mut Vec<FdxParagraph>,
+    right: &mut Vec<FdxParagraph>,
+) {
+    let columns = [
+        (
+            left,
+            [
+                (
+                    "Character",
+                    SPType::SP_DD_L_CHARACTER,
+                    SPType::SP_DD_L_CHARACTER_EXTENSION,
+                ),
+                (
+                    "Parenthetical",
+                    SPType::SP_DD_L_PARENTHETICAL,
+                    SPType::SP_DD_L_PARENTHETICAL,
+                ),
+                (
+                    "Dialogue",
+                    SPType::SP_DD_L_DIALOGUE,
+                    SPType::SP_DD_L_DIALOGUE,
+                ),
+            ],
+        ),
+        (
+            right,
+            [
+                (
+                    "Character",
+                    SPType::SP_DD_R_CHARACTER,
+                    SPType::SP_DD_R_CHARACTER_EXTENSION,
+                ),
+                (
+                    "Parenthetical",
+                    SPType::SP_DD_R_PARENTHETICAL,
+                    SPType::SP_DD_R_PARENTHETICAL,
+                ),
+                (
+                    "Dialogue",
+                    SPType::SP_DD_R_DIALOGUE,
+                    SPType::SP_DD_R_DIALOGUE,
+                ),
+            ],
+        ),
+    ];
+    for (column, paragraph_types) in columns {
+        for (paragraph_type, element_type, extension_type) in paragraph_types {
+            let text = get_text_for_elements(&line.text_elements, |t| {
+                *t == Some(element_type) || *t == Some(extension_type)
+            });
+            if text.is_empty() {
+                continue;
+            }
+            let new_paragraph = FdxParagraph {
+                paragraph_type,
+                text,
+                revision_id,
+                number: None,
+                page: None,
+                centered: false,
+            };
+            push_paragraph(column, new_paragraph, true);
+        }
+    }
+}
+
+fn flush_dual_dialogue(
+    elements: &mut Vec<FdxElement>,
+    left: &mut Vec<FdxParagraph>,
+    right: &mut Vec<FdxParagraph>,
+) {
+    if left.is_empty() && right.is_empty() {
+        return;
+    }
+    let mut dual_paragraphs: Vec<FdxParagraph> = std::mem::take(left);
+    dual_paragraphs.append(right);
+    elements.push(FdxElement::DualDialogue(dual_paragraphs));
+}
+
+fn write_paragraph(fdx: &mut String, paragraph: &FdxParagraph, indent: &str) {
+    fdx.push_str(indent);
+    fdx.push_str("<Paragraph");
+    if let Some(number) = &paragraph.number {
+        fdx.push_str(&format!(" Number=\"{}\"", escape_xml(number)));
+    }
+    if paragraph.centered {
+        fdx.push_str(" Alignment=\"Center\"");
+    }
+    fdx.push_str(&format!(" Type=\"{}\">\n", paragraph.paragraph_type));
+    if let Some(page) = &paragraph.page {
+        fdx.push_str(&format!(
+            "{}  <SceneProperties Page=\"{}\"/>\n",
+            indent,
+            escape_xml(page)
+        ));
+    }
+    fdx.push_str(indent);
+    match paragraph.revision_id {
+        Some(id) => fdx.push_str(&format!("  <Text RevisionID=\"{}\">", id)),
+        None => fdx.push_str("  <Text>"),
+    }
+    fdx.push_str(&escape_xml(&paragraph.text));
+    fdx.push_str("</Text>\n");
+    fdx.push_str(indent);
+    fdx.push_str("</Paragraph>\n");
+}
+
+fn write_title_page(fdx: &mut String, title_page_lines: &[&Line]) {
+    if title_page_lines.is_empty() {
+        return;
+    }
+    fdx.push_str("  <TitlePage>\n    <Content>\n");
+    let mut previous_line: Option<&Line> = None;
+    for line in title_page_lines {
+        let blank_lines = match previous_line {
+            Some(previous) if previous.line_type != line.line_type => {
+                line.preceding_empty_lines.max(1)
+            }
+            _ => line.preceding_empty_lines,
+        };
+        for _ in 0..blank_lines {
+            fdx.push_str(
+                "      <Paragraph Alignment=\"Center\" Type=\"Action\">\n        <Text></Text>\n      </Paragraph>\n",
+            );
+        }
+        let alignment = match line.line_type {
+            Some(SPType::TP_CONTACT) => "Left",
+            Some(SPType::TP_DRAFT_DATE) => "Right",
+            _ => "Center",
+        };
+        fdx.push_str(&format!(
+            "      <Paragraph Alignment=\"{}\" Type=\"Action\">\n        <Text>{}</Text>\n      </Paragraph>\n",
+            alignment,
+            escape_xml(&get_content_text(line))
+        ));
+        previous_line = Some(line);
+    }
+    fdx.push_str("    </Content>\n  </TitlePage>\n");
+}
+
+fn write_smart_type(
+    fdx: &mut String,
+    screenplay_doc: &ScreenplayDocument,
+    time_of_day_strs: &TimeOfDayCollection,
+    environment_strs: &EnvironmentStrings,
+) {
+    fdx.push_str("  <SmartType>\n");
+
+    let characters: BTreeSet<&String> = screenplay_doc.characters.iter().map(|c| &c.name).collect();
+    fdx.push_str("    <Characters>\n");
+    for character in characters {
+        fdx.push_str(&format!(
+            "      <Character>{}</Character>\n",
+            escape_xml(character)
+        ));
+    }
+    fdx.push_str("    </Characters>\n");
+
+    fdx.push_str("    <SceneIntros>\n");
+    for environment_str in environment_strs
+        .int
+        .iter()
+        .chain(&environment_strs.ext)
+        .chain(&environment_strs.combo)
+    {
+        fdx.push_str(&format!(
+            "      <SceneIntro>{} </SceneIntro>\n",
+            escape_xml(environment_str)
+        ));
+    }
+    fdx.push_str("    </SceneIntros>\n");
+
+    // Every location path in the tree, without the environment in front of its root
+    let mut locations: BTreeSet<String> = BTreeSet::new();
+    for location_id in screenplay_doc.locations.keys() {
+        let Some(path_string) =
+            reports::get_full_string_for_location_path(screenplay_doc, location_id)
+        else {
+            continue;
+        };
+        let location = match path_string.split_once(' ') {
+            Some((first_word, rest)) if get_environment(first_word, environment_strs).is_some() => {
+                rest.to_string()
+            }
+            _ => path_string,
+        };
+        if !location.is_empty() {
+            locations.insert(location);
+        }
+    }
+    fdx.push_str("    <Locations>\n");
+    for location in locations {
+        fdx.push_str(&format!(
+            "      <Location>{}</Location>\n",
+            escape_xml(&location)
+        ));
+    }
+    fdx.push_str("    </Locations>\n");
+
+    fdx.push_str("    <TimesOfDay Separator=\" - \">\n");
+    for time_of_day in time_of_day_strs.get_all_strs() {
+        fdx.push_str(&format!(
+            "      <TimeOfDay>{}</TimeOfDay>\n",
+            escape_xml(time_of_day)
+        ));
+    }
+    fdx.push_str("    </TimesOfDay>\n");
+
+    fdx.push_str("  </SmartType>\n");
+}
+
+/// Converts a ScreenplayDocument into an FDX document.
+///
+/// The `TimeOfDayCollection` and `EnvironmentStrings` are written to the FDX `SmartType` lists,
+/// and should be the same ones the document was parsed with.
+pub fn get_fdx_str_from_screenplay_doc(
+    screenplay_doc: &ScreenplayDocument,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> String {
+    let time_of_day_strs = time_of_day_strs_opt.unwrap_or_default();
+    let environment_strs = env_strs_opt.unwrap_or_default();
+
+    let mut title_page_lines: Vec<&Line> = Vec::new();
+    let mut elements: Vec<FdxElement> = Vec::new();
+    let mut paragraphs: Vec<FdxParagraph> = Vec::new();
+    let mut dual_left: Vec<FdxParagraph> = Vec::new();
+    let mut dual_right: Vec<FdxParagraph> = Vec::new();
+
+    // Revision sets, by name, in the order they first appear
+    let mut revision_sets: Vec<String> = Vec::new();
+    let mut get_revision_id = |page: &Page| -> usize {
+        let name = page.revision_label.clone().unwrap_or_else(|| {
+            screenplay_doc
+                .revisions
+                .as_ref()
+                .and_then(|revisions| revisions.last().map(|r| r.label.clone()))
+                .unwrap_or(DEFAULT_REVISION_NAME.to_string())
+        });
+        match revision_sets.iter().position(|set| *set == name) {
+            Some(idx) => idx + 1,
+            None => {
+                revision_sets.push(name);
+                revision_sets.len()
+            }
+        }
+    };
+
+    for page in &screenplay_doc.pages {
+        if is_title_page(page) {
+            title_page_lines.extend(&page.lines);
+            continue;
+        }
+
+        let mut previous_line_type: Option<SPType> = None;
+        for line in &page.lines {
+            let continues_previous =
+                line.preceding_empty_lines == 0 && previous_line_type == line.line_type;
+            let revision_id = if line.revised {
+                Some(get_revision_id(page))
+            } else {
+                None
+            };
+
+            if matches!(
+                line.line_type,
+                Some(SPType::SP_DUAL_CHARACTERS) | Some(SPType::SP_DUAL_DIALOGUES)
+            ) {
+                // A new left character means a new dual dialogue block
+                let starts_new_block = line
+                    .text_elements
+                    .iter()
+                    .any(|e| e.element_type == Some(SPType::SP_DD_L_CHARACTER));
+                if starts_new_block && !dual_right.is_empty() {
+                    flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+                }
+                if dual_left.is_empty() && dual_right.is_empty() {
+                    elements.extend(paragraphs.drain(..).map(FdxElement::Paragraph));
+                }
+                push_dual_dialogue_line(line, revision_id, &mut dual_left, &mut dual_right);
+                previous_line_type = line.line_type;
+                continue;
+            }
+            flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+
+            let Some(paragraph_type) = get_paragraph_type(&line.line_type) else {
+                continue;
+            };
+            let text = get_content_text(line);
+            if text.is_empty() {
+                continue;
+            }
+            let is_scene_heading = paragraph_type == "Scene Heading";
+            let new_paragraph = FdxParagraph {
+                paragraph_type,
+                text,
+                revision_id,
+                number: if is_scene_heading {
+                    get_scene_number(line, screenplay_doc)
+                } else {
+                    None
+                },
+                page: if is_scene_heading {
+                    page.page_number.as_ref().map(|number| number.0.clone())
+                } else {
+                    None
+                },
+                centered: line.centered,
+            };
+            push_paragraph(&mut paragraphs, new_paragraph, continues_previous);
+            previous_line_type = line.line_type;
+        }
+    }
+    flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+    elements.extend(paragraphs.drain(..).map(FdxElement::Paragraph));
+
+    let mut fdx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\" ?>\n<FinalDraft DocumentType=\"Script\" Template=\"No\" Version=\"5\">\n  <Content>\n",
+    );
+    for element in &elements {
+        match element {
+            FdxElement::Paragraph(paragraph) => write_paragraph(&mut fdx, paragraph, "    "),
+            FdxElement::DualDialogue(dual_paragraphs) => {
+                fdx.push_str("    <Paragraph>\n      <DualDialogue>\n");
+                for paragraph in dual_paragraphs {
+                    write_paragraph(&mut fdx, paragraph, "        ");
+                }
+                fdx.push_str("      </DualDialogue>\n    </Paragraph>\n");
+            }
+        }
+    }
+    fdx.push_str("  </Content>\n");
+
+    write_title_page(&mut fdx, &title_page_lines);
+
+    if !revision_sets.is_empty() {
+        fdx.push_str(&format!(
+            "  <Revisions ActiveSet=\"{}\" RevisionMode=\"No\" RevisionsShown=\"Active\">\n",
+            revision_sets.len()
+        ));
+        for (idx, name) in revision_sets.iter().enumerate() {
+            fdx.push_str(&format!(
+                "    <Revision FullRevision=\"No\" ID=\"{}\" Mark=\"*\" Name=\"{}\" Style=\"\"/>\n",
+                idx + 1,
+                escape_xml(name)
+            ));
+        }
+        fdx.push_str("  </Revisions>\n");
+    }
+
+    write_smart_type(
+        &mut fdx,
+        screenplay_doc,
+        &time_of_day_strs,
+        &environment_strs,
+    );
+
+    fdx.push_str("</FinalDraft>\n");
+    fdx
+}
+
+/// Writes a ScreenplayDocument out to an FDX file.
+pub fn write_fdx_to_filepath(
+    screenplay_doc: &ScreenplayDocument,
+    path: String,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(
+        path,
+        get_fdx_str_from_screenplay_doc(screenplay_doc, time_of_day_strs_opt, env_strs_opt),
+    )?;
+    Ok(())
+}
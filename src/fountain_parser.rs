@@ -0,0 +1,486 @@
+//! This module is responsible for interpreting a Fountain (`.fountain`) plain-text
+//! screenplay into the same semantically-typed ScreenplayDocument structure
+//! that the PDF parser produces.
+//!
+//! See <https://fountain.io/syntax> for the Fountain syntax.
+//!
+//! Fountain has no pagination of its own, so pages are estimated by wrapping each element
+//! at its standard US-Letter width, and breaking every `LINES_PER_PAGE` lines.
+//! Forced page breaks (`===`) are honored.
+//!
+//! Boneyard (`/* */`), notes (`[[ ]]`), sections (`#`) and synopses (`=`) are not part
+//! of the printed screenplay, so they are dropped.
+
+use std::collections::HashMap;
+
+use crate::error::ParseError;
+use crate::parser_common::{
+    DialogueSide, DocumentBuilder, get_character_line, get_environment, get_scene_heading_line,
+    get_text_elements, get_wrapped_lines,
+};
+use crate::screenplay_document::{
+    self, EnvironmentStrings, Line, Page, RevisionColorSequence, SPType, TimeOfDayCollection,
+};
+use crate::story_days::StoryDayMarkers;
+
+/// Number of printed lines on a US-Letter page, in 12-point Courier.
+const LINES_PER_PAGE: u64 = 55;
+
+// Element widths in characters, for US-Letter, 12-point Courier
+const ACTION_WIDTH: usize = 61;
+const DIALOGUE_WIDTH: usize = 35;
+const PARENTHETICAL_WIDTH: usize = 25;
+
+/// Removes boneyard (`/* */`) and note (`[[ ]]`) blocks, which may span multiple lines.
+///
+/// Lines which only contained boneyard or notes are removed entirely,
+/// so they don't break up the surrounding elements.
+fn strip_boneyard_and_notes(fountain_text: &str) -> Vec<String> {
+    let mut stripped_lines: Vec<String> = Vec::new();
+    let mut in_boneyard = false;
+    let mut in_note = false;
+
+    for raw_line in fountain_text.lines() {
+        let mut new_line = String::new();
+        let mut removed_anything = in_boneyard || in_note;
+        let mut chars = raw_line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if in_boneyard {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_boneyard = false;
+                }
+                continue;
+            }
+            if in_note {
+                if c == ']' && chars.peek() == Some(&']') {
+                    chars.next();
+                    in_note = false;
+                }
+                continue;
+            }
+            if c == '/' && chars.peek() == Some(&'*') {
+                chars.next();
+                in_boneyard = true;
+                removed_anything = true;
+                continue;
+            }
+            if c == '[' && chars.peek() == Some(&'[') {
+                chars.next();
+                in_note = true;
+                removed_anything = true;
+                continue;
+            }
+            new_line.push(c);
+        }
+
+        if removed_anything && new_line.trim().is_empty() {
+            continue;
+        }
+        stripped_lines.push(new_line.trim_end_matches(['\r', '\n']).to_string());
+    }
+    stripped_lines
+}
+
+/// A run of emphasis markers (`*`, `**`, `***` or `_`), or a literal character.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EmphasisToken {
+    Marker(char, usize),
+    Literal(char),
+}
+
+/// Removes emphasis markers (`*italics*`, `**bold**`, `***bold italics***` and `_underline_`).
+///
+/// Markers only count if they are paired on the same line, with the opening marker
+/// followed by, and the closing marker preceded by, a non-whitespace character.
+/// Unpaired markers, and markers escaped with a backslash (`\*`), are kept as text.
+fn strip_emphasis(text: &str) -> String {
+    use EmphasisToken::*;
+
+    let mut tokens: Vec<EmphasisToken> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some('*') | Some('_')) => {
+                tokens.push(Literal(chars.next().unwrap_or(c)));
+            }
+            '*' => {
+                let mut count = 1;
+                while count < 3 && chars.peek() == Some(&'*') {
+                    chars.next();
+                    count += 1;
+                }
+                tokens.push(Marker('*', count));
+            }
+            '_' => tokens.push(Marker('_', 1)),
+            _ => tokens.push(Literal(c)),
+        }
+    }
+
+    let is_text_at = |idx: Option<usize>| {
+        idx.and_then(|i| tokens.get(i))
+            .is_some_and(|token| !matches!(token, Literal(c) if c.is_whitespace()))
+    };
+    let mut removed = vec![false; tokens.len()];
+    for open in 0..tokens.len() {
+        let Marker(..) = tokens[open] else {
+            continue;
+        };
+        if removed[open] || !is_text_at(Some(open + 1)) {
+            continue;
+        }
+        let close = (open + 2..tokens.len()).find(|&close| {
+            !removed[close] && tokens[close] == tokens[open] && is_text_at(close.checked_sub(1))
+        });
+        if let Some(close) = close {
+            removed[open] = true;
+            removed[close] = true;
+        }
+    }
+
+    tokens
+        .iter()
+        .zip(removed)
+        .filter(|(_, removed)| !removed)
+        .map(|(token, _)| match token {
+            Marker(marker, count) => marker.to_string().repeat(*count),
+            Literal(c) => c.to_string(),
+        })
+        .collect()
+}
+
+fn is_uppercase_text(text: &str) -> bool {
+    text.chars().any(|c| c.is_alphabetic()) && !text.chars().any(|c| c.is_lowercase())
+}
+
+fn is_scene_heading(text: &str, environment_strs: &EnvironmentStrings) -> bool {
+    let Some(first_word) = text.split_whitespace().next() else {
+        return false;
+    };
+    get_environment(first_word, environment_strs).is_some()
+}
+
+fn is_transition(text: &str) -> bool {
+    is_uppercase_text(text) && text.trim_end().ends_with("TO:")
+}
+
+/// Splits a trailing scene number (`#12A#`) off of a scene heading.
+fn split_scene_number(text: &str) -> (String, Option<String>) {
+    let trimmed = text.trim_end();
+    if let Some(without_suffix) = trimmed.strip_suffix('#')
+        && let Some(start) = without_suffix.rfind('#')
+    {
+        let number = without_suffix[start + 1..].trim().to_string();
+        if !number.is_empty() {
+            return (without_suffix[..start].trim_end().to_string(), Some(number));
+        }
+    }
+    (trimmed.to_string(), None)
+}
+
+/// Re-types a single-column dialogue block as the left side of a dual dialogue block.
+fn convert_to_left_dual_dialogue(lines: &mut [Line]) {
+    let left = DialogueSide::Left;
+    for line in lines {
+        line.line_type = match line.line_type {
+            Some(SPType::SP_CHARACTER) => Some(left.character_line_type()),
+            Some(SPType::SP_PARENTHETICAL) | Some(SPType::SP_DIALOGUE) => {
+                Some(SPType::SP_DUAL_DIALOGUES)
+            }
+            other => other,
+        };
+        for element in &mut line.text_elements {
+            element.element_type = match element.element_type {
+                Some(SPType::SP_CHARACTER) => Some(left.character_type()),
+                Some(SPType::SP_CHARACTER_EXTENSION) => Some(left.extension_type()),
+                Some(SPType::SP_PARENTHETICAL) => Some(left.parenthetical_type()),
+                Some(SPType::SP_DIALOGUE) => Some(left.dialogue_type()),
+                other => other,
+            };
+        }
+    }
+}
+
+/// Parses the Fountain title page (`Key: value` pairs at the very start of the document).
+///
+/// Returns the title page, and the number of source lines it used.
+fn parse_title_page(lines: &[String]) -> Option<(Page, usize)> {
+    let first_line = lines.first()?;
+    let (first_key, _) = first_line.split_once(':')?;
+    if first_key.is_empty()
+        || first_key.starts_with([' ', '\t'])
+        || !first_key.chars().all(|c| c.is_alphanumeric() || c == ' ')
+    {
+        return None;
+    }
+
+    let mut title_page = Page::default();
+    let mut current_type = SPType::SP_OTHER;
+    let mut consumed: usize = 0;
+
+    for line in lines {
+        if line.trim().is_empty() {
+            break;
+        }
+        consumed += 1;
+
+        let is_continuation = line.starts_with('\t') || line.starts_with("   ");
+        let value = if !is_continuation && let Some((key, value)) = line.split_once(':') {
+            current_type = match key.trim().to_lowercase().as_str() {
+                "title" => SPType::TP_TITLE,
+                "credit" => SPType::TP_BYLINE,
+                "author" | "authors" => SPType::TP_AUTHOR,
+                "source" => SPType::TP_SOURCE,
+                "draft date" | "date" => SPType::TP_DRAFT_DATE,
+                "contact" => SPType::TP_CONTACT,
+                _ => SPType::SP_OTHER,
+            };
+            value.trim()
+        } else {
+            line.trim()
+        };
+        let value = strip_emphasis(value);
+        if value.is_empty() {
+            continue;
+        }
+        title_page.lines.push(Line {
+            text_elements: get_text_elements(&value, Some(current_type)),
+            line_type: Some(current_type),
+            ..Default::default()
+        });
+    }
+
+    Some((title_page, consumed))
+}
+
+/// Parses a Fountain document from a string.
+///
+/// Returns `ParseError::EmptyDocument` if the document has no content.
+pub fn get_screenplay_doc_from_fountain_str(
+    fountain_text: &str,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    let time_of_day_strs = time_of_day_strs_opt.unwrap_or_default();
+    let environment_strs = env_strs_opt.unwrap_or_default();
+
+    let source_lines = strip_boneyard_and_notes(fountain_text);
+    if source_lines.iter().all(|l| l.trim().is_empty()) {
+        return Err(ParseError::EmptyDocument);
+    }
+
+    let mut builder = DocumentBuilder::new(&time_of_day_strs, LINES_PER_PAGE);
+
+    let mut body_start: usize = 0;
+    if let Some((title_page, consumed)) = parse_title_page(&source_lines) {
+        builder.screenplay_doc.pages.push(title_page);
+        body_start = consumed;
+    }
+    let body = &source_lines[body_start..];
+
+    let is_blank = |idx: Option<usize>| -> bool {
+        match idx.and_then(|i| body.get(i)) {
+            None => true,
+            Some(l) => l.trim().is_empty(),
+        }
+    };
+
+    // The dialogue block we're currently in, and where the last single-column
+    // dialogue block started on the current page, in case it turns out to be dual dialogue
+    let mut dialogue_side: Option<DialogueSide> = None;
+    let mut last_dialogue_block: Option<(usize, usize)> = None;
+
+    for (idx, raw_line) in body.iter().enumerate() {
+        let stripped_text = strip_emphasis(raw_line.trim());
+        let text = stripped_text.as_str();
+
+        if text.is_empty() {
+            // Two spaces keep a dialogue block going
+            if dialogue_side.is_some() && raw_line.len() >= 2 {
+                continue;
+            }
+            dialogue_side = None;
+            builder.add_blank_lines(1);
+            continue;
+        }
+
+        if text.len() >= 3 && text.chars().all(|c| c == '=') {
+            builder.page_break();
+            dialogue_side = None;
+            last_dialogue_block = None;
+            continue;
+        }
+        // Sections and synopses aren't printed
+        if text.starts_with('#') || text.starts_with('=') {
+            continue;
+        }
+
+        // ------ Dialogue blocks
+        if let Some(side) = dialogue_side {
+            let (element_type, width) = if text.starts_with('(') && text.ends_with(')') {
+                (side.parenthetical_type(), PARENTHETICAL_WIDTH)
+            } else {
+                (side.dialogue_type(), DIALOGUE_WIDTH)
+            };
+            let line_type = side.dialogue_line_type(element_type);
+            builder.add_element(
+                get_wrapped_lines(text, element_type, line_type, width),
+                None,
+            );
+            continue;
+        }
+
+        let preceded_by_blank = idx == 0 || is_blank(idx.checked_sub(1));
+        let followed_by_blank = is_blank(Some(idx + 1));
+
+        // ------ Forced elements
+        if let Some(forced_heading) = text.strip_prefix('.')
+            && !forced_heading.starts_with('.')
+        {
+            let (heading_text, scene_number) = split_scene_number(forced_heading);
+            let (line, environment) = get_scene_heading_line(
+                &heading_text,
+                scene_number,
+                &time_of_day_strs,
+                &environment_strs,
+            );
+            builder.add_element(vec![line], Some(environment));
+            last_dialogue_block = None;
+            continue;
+        }
+        if let Some(forced_action) = text.strip_prefix('!') {
+            builder.add_element(
+                get_wrapped_lines(
+                    forced_action,
+                    SPType::SP_ACTION,
+                    SPType::SP_ACTION,
+                    ACTION_WIDTH,
+                ),
+                None,
+            );
+            continue;
+        }
+        if let Some(forced_transition) = text.strip_prefix('>') {
+            if let Some(centered) = forced_transition.strip_suffix('<') {
+                let mut new_lines = get_wrapped_lines(
+                    centered.trim(),
+                    SPType::SP_ACTION,
+                    SPType::SP_ACTION,
+                    ACTION_WIDTH,
+                );
+                for line in &mut new_lines {
+                    line.centered = true;
+                }
+                builder.add_element(new_lines, None);
+            } else {
+                builder.add_element(
+                    get_wrapped_lines(
+                        forced_transition,
+                        SPType::SP_TRANSITION,
+                        SPType::SP_TRANSITION,
+                        ACTION_WIDTH,
+                    ),
+                    None,
+                );
+            }
+            continue;
+        }
+        if let Some(lyric) = text.strip_prefix('~') {
+            builder.add_element(
+                get_wrapped_lines(lyric, SPType::SP_ACTION, SPType::SP_ACTION, ACTION_WIDTH),
+                None,
+            );
+            continue;
+        }
+
+        // ------ Scene Headings
+        if preceded_by_blank && is_scene_heading(text, &environment_strs) {
+            let (heading_text, scene_number) = split_scene_number(text);
+            let (line, environment) = get_scene_heading_line(
+                &heading_text,
+                scene_number,
+                &time_of_day_strs,
+                &environment_strs,
+            );
+            builder.add_element(vec![line], Some(environment));
+            last_dialogue_block = None;
+            continue;
+        }
+
+        // ------ Transitions
+        if preceded_by_blank && followed_by_blank && is_transition(text) {
+            builder.add_element(
+                get_wrapped_lines(
+                    text,
+                    SPType::SP_TRANSITION,
+                    SPType::SP_TRANSITION,
+                    ACTION_WIDTH,
+                ),
+                None,
+            );
+            continue;
+        }
+
+        // ------ Character cues
+        let (forced_character, character_text) = match text.strip_prefix('@') {
+            Some(stripped) => (true, stripped),
+            None => (false, text),
+        };
+        let (is_dual, character_text) = match character_text.strip_suffix('^') {
+            Some(stripped) => (true, stripped.trim_end()),
+            None => (false, character_text),
+        };
+        let name_part = character_text.split('(').next().unwrap_or_default();
+        if preceded_by_blank
+            && !followed_by_blank
+            && (forced_character || is_uppercase_text(name_part))
+        {
+            let side = if is_dual {
+                DialogueSide::Right
+            } else {
+                DialogueSide::Single
+            };
+            if is_dual {
+                if let Some((page, start)) = last_dialogue_block
+                    && page == builder.screenplay_doc.pages.len()
+                {
+                    convert_to_left_dual_dialogue(&mut builder.current_page.lines[start..]);
+                }
+                last_dialogue_block = None;
+            }
+            let first_line_idx =
+                builder.add_element(vec![get_character_line(character_text, side)], None);
+            if !is_dual {
+                last_dialogue_block = Some((builder.screenplay_doc.pages.len(), first_line_idx));
+            }
+            dialogue_side = Some(side);
+            continue;
+        }
+
+        // ------ Action
+        builder.add_element(
+            get_wrapped_lines(text, SPType::SP_ACTION, SPType::SP_ACTION, ACTION_WIDTH),
+            None,
+        );
+        last_dialogue_block = None;
+    }
+
+    let mut screenplay_doc = builder.finish();
+    if screenplay_doc.pages.is_empty() {
+        return Err(ParseError::NoContentPages);
+    }
+    screenplay_doc.set_revisions(&RevisionColorSequence::default());
+    screenplay_doc.set_story_days(&StoryDayMarkers::default(), &HashMap::new());
+    Ok(screenplay_doc)
+}
+
+/// Reads and parses a Fountain document from a file.
+pub fn get_screenplay_doc_from_fountain_filepath(
+    path: String,
+    time_of_day_strs_opt: Option<TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    let fountain_text = std::fs::read_to_string(path)?;
+    get_screenplay_doc_from_fountain_str(&fountain_text, time_of_day_strs_opt, env_strs_opt)
+}
@@ -0,0 +1,354 @@
+//! This module writes a ScreenplayDocument back out as Fountain (`.fountain`) plain text.
+//!
+//! This is mostly useful for recovering an editable script from a parsed PDF.
+//!
+//! Lines which were wrapped onto multiple lines in the source document are joined back
+//! into a single paragraph. Page headers, `(MORE)` / `(CONT'D)` markers and anything
+//! outside of the page margins are dropped.
+//!
+//! See <https://fountain.io/syntax> for the Fountain syntax.
+
+use crate::screenplay_document::{
+    Line, Page, SPType, SceneHeadingElement, ScreenplayDocument, TextElement,
+};
+use crate::writer_common::{
+    get_content_text, get_scene_number, get_text_for_elements, is_content_element_type,
+    is_title_page,
+};
+
+/// Options for writing a Fountain document.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct FountainWriterOptions {
+    /// Write a forced page break (`===`) between pages, so the Fountain document
+    /// keeps the pagination of the source document.
+    pub page_breaks: bool,
+}
+impl FountainWriterOptions {
+    pub fn page_breaks(mut self, keep_page_breaks: bool) -> Self {
+        self.page_breaks = keep_page_breaks;
+        self
+    }
+}
+
+/// A single Fountain paragraph, before it is written out.
+#[derive(Clone, Debug, PartialEq)]
+enum FountainElement {
+    SceneHeading(String),
+    Action(String),
+    Character(String),
+    Parenthetical(String),
+    Dialogue(String),
+    Transition(String),
+    Centered(String),
+    PageBreak,
+}
+
+fn is_uppercase_text(text: &str) -> bool {
+    text.chars().any(|c| c.is_alphabetic()) && !text.chars().any(|c| c.is_lowercase())
+}
+
+/// Escapes characters which Fountain would read as emphasis markers.
+fn escape_emphasis(text: &str) -> String {
+    text.replace('*', "\\*").replace('_', "\\_")
+}
+
+fn get_scene_heading(line: &Line, screenplay_doc: &ScreenplayDocument) -> String {
+    let mut heading = get_content_text(line);
+
+    // Fountain only recognizes headings that start with INT, EXT, etc. on their own.
+    // PDF headings start with their scene number, so skip past any non-content text.
+    let starts_with_environment = line
+        .text_elements
+        .iter()
+        .find(|e| is_content_element_type(&e.element_type))
+        .map(|e| e.element_type)
+        == Some(Some(SPType::SP_SCENE_HEADING(
+            SceneHeadingElement::Environment,
+        )));
+    if !starts_with_environment {
+        heading.insert(0, '.');
+    }
+
+    if let Some(number) = get_scene_number(line, screenplay_doc) {
+        heading.push_str(&format!(" #{}#", number));
+    }
+    heading
+}
+
+fn get_character_cue(
+    text_elements: &[TextElement],
+    character_type: SPType,
+    extension_type: SPType,
+) -> String {
+    let cue = get_text_for_elements(text_elements, |t| {
+        *t == Some(character_type) || *t == Some(extension_type)
+    });
+    let name = get_text_for_elements(text_elements, |t| *t == Some(character_type));
+    // Character names with lowercase letters have to be forced
+    if is_uppercase_text(&name) {
+        cue
+    } else {
+        format!("@{}", cue)
+    }
+}
+
+/// Adds a paragraph, or joins it onto the previous paragraph if this line was wrapped from it.
+fn push_element(
+    elements: &mut Vec<FountainElement>,
+    new_element: FountainElement,
+    continues_previous: bool,
+) {
+    use FountainElement::*;
+
+    if continues_previous {
+        match (elements.last_mut(), &new_element) {
+            (Some(Action(previous)), Action(text))
+            | (Some(Dialogue(previous)), Dialogue(text))
+            | (Some(Centered(previous)), Centered(text)) => {
+                previous.push(' ');
+                previous.push_str(text);
+                return;
+            }
+            (Some(Parenthetical(previous)), Parenthetical(text)) if !previous.ends_with(')') => {
+                previous.push(' ');
+                previous.push_str(text);
+                return;
+            }
+            _ => {}
+        }
+    }
+    elements.push(new_element);
+}
+
+/// Splits a dual dialogue line into the elements for its left and right columns.
+///
+/// A single line may contain both columns, if it was parsed from a PDF.
+fn push_dual_dialogue_line(
+    line: &Line,
+    left: &mut Vec<FountainElement>,
+    right: &mut Vec<FountainElement>,
+) {
+    let columns = [
+        (
+            left,
+            SPType::SP_DD_L_CHARACTER,
+            SPType::SP_DD_L_CHARACTER_EXTENSION,
+            SPType::SP_DD_L_PARENTHETICAL,
+            SPType::SP_DD_L_DIALOGUE,
+        ),
+        (
+            right,
+            SPType::SP_DD_R_CHARACTER,
+            SPType::SP_DD_R_CHARACTER_EXTENSION,
+            SPType::SP_DD_R_PARENTHETICAL,
+            SPType::SP_DD_R_DIALOGUE,
+        ),
+    ];
+    for (column, character_type, extension_type, parenthetical_type, dialogue_type) in columns {
+        let character = get_character_cue(&line.text_elements, character_type, extension_type);
+        if !character.trim_start_matches('@').is_empty() {
+            column.push(FountainElement::Character(character));
+        }
+        let parenthetical =
+            get_text_for_elements(&line.text_elements, |t| *t == Some(parenthetical_type));
+        if !parenthetical.is_empty() {
+            push_element(column, FountainElement::Parenthetical(parenthetical), true);
+        }
+        let dialogue = get_text_for_elements(&line.text_elements, |t| *t == Some(dialogue_type));
+        if !dialogue.is_empty() {
+            push_element(column, FountainElement::Dialogue(dialogue), true);
+        }
+    }
+}
+
+/// Writes out the left column of a dual dialogue block as a regular dialogue block,
+/// followed by the right column with its character marked with a caret (`^`).
+fn flush_dual_dialogue(
+    elements: &mut Vec<FountainElement>,
+    left: &mut Vec<FountainElement>,
+    right: &mut Vec<FountainElement>,
+) {
+    elements.append(left);
+    for (idx, element) in right.drain(..).enumerate() {
+        match element {
+            FountainElement::Character(cue) if idx == 0 => {
+                elements.push(FountainElement::Character(format!("{} ^", cue)))
+            }
+            other => elements.push(other),
+        }
+    }
+}
+
+fn get_title_page(page: &Page) -> String {
+    let mut title_page = String::new();
+    let mut previous_key = "";
+    for line in &page.lines {
+        let key = match line.line_type {
+            Some(SPType::TP_TITLE) => "Title",
+            Some(SPType::TP_BYLINE) => "Credit",
+            Some(SPType::TP_AUTHOR) => "Author",
+            Some(SPType::TP_SOURCE) => "Source",
+            Some(SPType::TP_DRAFT_DATE) => "Draft date",
+            Some(SPType::TP_CONTACT) => "Contact",
+            _ => "Notes",
+        };
+        let text = escape_emphasis(&get_content_text(line));
+        if text.is_empty() {
+            continue;
+        }
+        if key == previous_key {
+            title_page.push_str(&format!("    {}\n", text));
+            continue;
+        }
+        // Keys with multiple lines put every line on its own, indented
+        title_page.push_str(&format!("{}: {}\n", key, text));
+        previous_key = key;
+    }
+    title_page
+}
+
+/// Converts a ScreenplayDocument into a Fountain document.
+pub fn get_fountain_str_from_screenplay_doc(
+    screenplay_doc: &ScreenplayDocument,
+    options: &FountainWriterOptions,
+) -> String {
+    let mut title_page = String::new();
+    let mut elements: Vec<FountainElement> = Vec::new();
+    let mut dual_left: Vec<FountainElement> = Vec::new();
+    let mut dual_right: Vec<FountainElement> = Vec::new();
+    let mut content_pages_written: usize = 0;
+
+    for page in &screenplay_doc.pages {
+        if is_title_page(page) {
+            title_page.push_str(&get_title_page(page));
+            continue;
+        }
+        if options.page_breaks && content_pages_written > 0 {
+            flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+            elements.push(FountainElement::PageBreak);
+        }
+        content_pages_written += 1;
+
+        let mut previous_line_type: Option<SPType> = None;
+        for line in &page.lines {
+            let continues_previous =
+                line.preceding_empty_lines == 0 && previous_line_type == line.line_type;
+
+            if !matches!(
+                line.line_type,
+                Some(SPType::SP_DUAL_CHARACTERS) | Some(SPType::SP_DUAL_DIALOGUES)
+            ) {
+                flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+            }
+
+            let text = get_content_text(line);
+            if text.is_empty() {
+                continue;
+            }
+            let new_element = match line.line_type {
+                // Omitted scenes are forced headings, so they keep their scene numbers
+                Some(SPType::SP_SCENE_HEADING(_)) | Some(SPType::SP_OMITTED) => {
+                    FountainElement::SceneHeading(get_scene_heading(line, screenplay_doc))
+                }
+                Some(SPType::SP_ACTION) if line.centered => FountainElement::Centered(text),
+                Some(SPType::SP_ACTION) | Some(SPType::SP_SHOT_ANGLE) | Some(SPType::SP_OTHER) => {
+                    FountainElement::Action(text)
+                }
+                Some(SPType::SP_CHARACTER) => FountainElement::Character(get_character_cue(
+                    &line.text_elements,
+                    SPType::SP_CHARACTER,
+                    SPType::SP_CHARACTER_EXTENSION,
+                )),
+                Some(SPType::SP_PARENTHETICAL) => FountainElement::Parenthetical(text),
+                Some(SPType::SP_DIALOGUE) => FountainElement::Dialogue(text),
+                Some(SPType::SP_TRANSITION) => FountainElement::Transition(text),
+                Some(SPType::SP_DUAL_CHARACTERS) | Some(SPType::SP_DUAL_DIALOGUES) => {
+                    // A new left character means a new dual dialogue block
+                    let starts_new_block = line
+                        .text_elements
+                        .iter()
+                        .any(|e| e.element_type == Some(SPType::SP_DD_L_CHARACTER));
+                    if starts_new_block && !dual_right.is_empty() {
+                        flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+                    }
+                    push_dual_dialogue_line(line, &mut dual_left, &mut dual_right);
+                    previous_line_type = line.line_type;
+                    continue;
+                }
+                _ => continue,
+            };
+            push_element(&mut elements, new_element, continues_previous);
+            previous_line_type = line.line_type;
+        }
+    }
+    flush_dual_dialogue(&mut elements, &mut dual_left, &mut dual_right);
+
+    let mut fountain = title_page;
+    let mut previous: Option<&FountainElement> = None;
+    for element in &elements {
+        let in_dialogue_block = matches!(
+            previous,
+            Some(FountainElement::Character(_))
+                | Some(FountainElement::Parenthetical(_))
+                | Some(FountainElement::Dialogue(_))
+        );
+        let text = match element {
+            FountainElement::SceneHeading(text) | FountainElement::Character(text) => text.clone(),
+            FountainElement::Parenthetical(text) | FountainElement::Dialogue(text) => {
+                if !in_dialogue_block {
+                    // Orphaned dialogue, i.e. the character cue is on a previous page
+                    format!("!{}", text)
+                } else {
+                    text.clone()
+                }
+            }
+            FountainElement::Action(text) => {
+                // Escape action which Fountain would otherwise read as a heading or transition
+                let first_word = text.split_whitespace().next().unwrap_or_default();
+                let looks_like_heading = ["INT", "EXT", "EST", "I/E"]
+                    .iter()
+                    .any(|prefix| first_word.to_uppercase().trim_end_matches('.') == *prefix);
+                if looks_like_heading || (is_uppercase_text(text) && text.ends_with("TO:")) {
+                    format!("!{}", text)
+                } else {
+                    text.clone()
+                }
+            }
+            FountainElement::Transition(text) => {
+                if is_uppercase_text(text) && text.ends_with("TO:") {
+                    text.clone()
+                } else {
+                    format!("> {}", text)
+                }
+            }
+            FountainElement::Centered(text) => format!(">{}<", text),
+            FountainElement::PageBreak => "===".to_string(),
+        };
+
+        let continues_dialogue_block = in_dialogue_block
+            && matches!(
+                element,
+                FountainElement::Parenthetical(_) | FountainElement::Dialogue(_)
+            );
+        if !fountain.is_empty() && !continues_dialogue_block {
+            fountain.push('\n');
+        }
+        fountain.push_str(&escape_emphasis(&text));
+        fountain.push('\n');
+        previous = Some(element);
+    }
+    fountain
+}
+
+/// Writes a ScreenplayDocument out to a Fountain file.
+pub fn write_fountain_to_filepath(
+    screenplay_doc: &ScreenplayDocument,
+    path: String,
+    options: &FountainWriterOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(
+        path,
+        get_fountain_str_from_screenplay_doc(screenplay_doc, options),
+    )?;
+    Ok(())
+}
@@ -1,11 +1,23 @@
-use std::fmt::Error;
-
+pub mod breakdown;
+pub mod day_out_of_days;
+pub mod diagnostics;
+pub mod diff;
+pub mod error;
 pub mod pdf_document;
 pub mod reports;
 pub mod screenplay_document;
+pub mod story_days;
+pub mod stripboard;
 
+pub mod fdx_parser;
+pub mod fdx_writer;
+pub mod fountain_parser;
+pub mod fountain_writer;
 pub mod pdf_parser;
 
+mod parser_common;
+mod writer_common;
+
 #[cfg(feature = "mupdf-basic-parsing")]
 pub mod mupdf_basic_parser;
 
@@ -15,7 +27,7 @@ mod tests {
     use crate::{
         pdf_document::{ElementIndentationsPoints, PDFDocument, TextPosition},
         pdf_parser::deduce_indentations,
-        screenplay_document::{EnvironmentStrings, SPType, TimeOfDayCollection},
+        screenplay_document::{RevisionColor, SPType},
     };
 
     use super::*;
@@ -25,13 +37,7 @@ mod tests {
         element_indentation: f64,
         y_height_inches: Option<f64>,
     ) -> pdf_document::Line {
-        let mut new_word = pdf_document::Word::default();
-
-        if let Some(inches) = y_height_inches {
-            new_word = _create_pdfword(text, element_indentation, y_height_inches);
-        } else {
-            new_word = _create_pdfword(text, element_indentation, None);
-        }
+        let new_word = _create_pdfword(text, element_indentation, y_height_inches);
 
         let new_line: pdf_document::Line = pdf_document::Line {
             words: vec![new_word],
@@ -44,16 +50,14 @@ mod tests {
         element_indentation: f64,
         y_height_inches: Option<f64>,
     ) -> pdf_document::Word {
-        let mut y_height_pts = 0.0;
-        if let Some(inches) = y_height_inches {
-            y_height_pts = 72.0 * inches;
-        } else {
-            y_height_pts = 3.0 * 72.0;
-        }
+        let y_height_pts = match y_height_inches {
+            Some(inches) => 72.0 * inches,
+            None => 3.0 * 72.0,
+        };
 
         let new_word: pdf_document::Word = pdf_document::Word {
             text: text.clone(),
-            bbox_width: text.len() as f64 * 7.2 as f64,
+            bbox_width: text.len() as f64 * 7.2_f64,
             bbox_height: 0.0,
             position: TextPosition {
                 x: element_indentation,
@@ -66,6 +70,7 @@ mod tests {
         new_word
     }
 
+    #[cfg(feature = "mupdf-basic-parsing")]
     #[test]
     fn indent_deduction() {
         let doc_result =
@@ -94,6 +99,7 @@ mod tests {
             None,
             None,
             None,
+            None,
         );
         let Ok(screenplay) = screenplay_result else {
             println!("{:#?}", screenplay_result);
@@ -527,7 +533,7 @@ mod tests {
         let indentations = ElementIndentationsPoints::us_letter_default(&None);
 
         println!(" ------ Testing Screenplay Element Types ------ ");
-        println!("");
+        println!();
 
         let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
         let mut new_page = pdf_document::Page::default();
@@ -628,13 +634,14 @@ mod tests {
         mock_pdf.pages.push(new_page);
 
         let parsed_doc =
-            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None).unwrap();
+            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None, None)
+                .unwrap();
 
         println!(
-            "\n-----\n\nPage number: {:>8} | Rev. label/date(?): {:12} | {}\n",
-            format!("{:?}", parsed_doc.pages.first().unwrap().page_number),
-            format!("{:?}", parsed_doc.pages.first().unwrap().revision_label),
-            format!("{:?}", parsed_doc.pages.first().unwrap().revision_date),
+            "\n-----\n\nPage number: {:>8?} | Rev. label/date(?): {:12?} | {:?}\n",
+            parsed_doc.pages.first().unwrap().page_number,
+            parsed_doc.pages.first().unwrap().revision_label,
+            parsed_doc.pages.first().unwrap().revision_date,
         );
 
         let lines = &parsed_doc.pages.first().unwrap().lines;
@@ -665,7 +672,7 @@ mod tests {
             for el in &line.text_elements {
                 println!(
                     "     {:24} | {:.2}, {:.2} | '{}'",
-                    if let Some(l_type) = el.element_type.clone() {
+                    if let Some(l_type) = el.element_type {
                         format!("{:?}", l_type)
                             .strip_prefix("SP_")
                             .unwrap_or(&format!("{:?}", l_type))
@@ -678,7 +685,7 @@ mod tests {
                     el.text,
                 );
             }
-            println!("");
+            println!();
         }
 
         //TODO: Add SEPARATE test function just for Scene Parsing
@@ -686,6 +693,457 @@ mod tests {
         println!("{:#?}", parsed_doc.scenes)
     }
 
+    #[test]
+    fn mock_indent_deduction() {
+        // Shift every indentation by half an inch, like a "centered" script
+        let offset = 36.0;
+        let defaults = ElementIndentationsPoints::us_letter_default(&None);
+
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        for page_idx in 0..3 {
+            let mut new_page = pdf_document::Page::default();
+            let mut line_y_inches = 10.5;
+            let next_line = |line_y_inches: &mut f64, lines: f64| {
+                *line_y_inches -= lines * (12.0 / 72.0);
+                Some(*line_y_inches)
+            };
+
+            new_page.lines.push(_create_pdfline_with_word(
+                format!("{}.", page_idx + 2),
+                7.0 * 72.0,
+                Some(line_y_inches),
+            ));
+            line_y_inches = 10.0;
+
+            let mut heading = get_scene_heading_line(
+                "INT.",
+                "HOUSE - DAY",
+                &format!("{}", page_idx + 1),
+                &ElementIndentationsPoints::from_inches(
+                    &pdf_document::ElementIndentationsInches::us_letter_default()
+                        .action((defaults.action + offset) / 72.0)
+                        .right((defaults.right + offset) / 72.0),
+                    &None,
+                ),
+            );
+            for word in &mut heading.words {
+                word.position.y = line_y_inches * 72.0;
+            }
+            new_page.lines.push(heading);
+
+            for _ in 0..4 {
+                new_page.lines.push(_create_pdfline_with_word(
+                    "Action".to_string(),
+                    defaults.action + offset,
+                    next_line(&mut line_y_inches, 2.0),
+                ));
+                new_page.lines.push(_create_pdfline_with_word(
+                    "CHARACTER".to_string(),
+                    defaults.character + offset,
+                    next_line(&mut line_y_inches, 2.0),
+                ));
+                new_page.lines.push(_create_pdfline_with_word(
+                    "(wryly)".to_string(),
+                    defaults.parenthetical + offset,
+                    next_line(&mut line_y_inches, 1.0),
+                ));
+                new_page.lines.push(_create_pdfline_with_word(
+                    "Dialogue".to_string(),
+                    defaults.dialogue + offset,
+                    next_line(&mut line_y_inches, 1.0),
+                ));
+            }
+            mock_pdf.pages.push(new_page);
+        }
+
+        let deduced = deduce_indentations(&mock_pdf).unwrap();
+        let within_tolerance = |a: f64, b: f64| (a - b).abs() < 0.01;
+
+        assert!(within_tolerance(
+            deduced.action * 72.0,
+            defaults.action + offset
+        ));
+        assert!(within_tolerance(
+            deduced.character * 72.0,
+            defaults.character + offset
+        ));
+        assert!(within_tolerance(
+            deduced.dialogue * 72.0,
+            defaults.dialogue + offset
+        ));
+        assert!(within_tolerance(
+            deduced.parenthetical * 72.0,
+            defaults.parenthetical + offset
+        ));
+        assert!(deduced.left * 72.0 <= defaults.action + offset);
+        assert!(deduced.right * 72.0 <= defaults.right + offset);
+        assert!(deduced.top < 10.5 && deduced.top > 10.0);
+        assert!(deduced.bottom > 0.0 && deduced.bottom < 6.0);
+
+        // The parser should pick up the deduced indentations when none are passed in
+        let parsed_doc =
+            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None, None)
+                .unwrap();
+        let first_page = parsed_doc.pages.first().unwrap();
+        let line_types: Vec<Option<SPType>> =
+            first_page.lines.iter().map(|l| l.line_type).collect();
+        assert_eq!(line_types[0], Some(SPType::SP_PAGE_HEADER));
+        assert_eq!(
+            line_types[1],
+            Some(SPType::SP_SCENE_HEADING(
+                screenplay_document::SceneHeadingElement::Line
+            ))
+        );
+        assert_eq!(line_types[2], Some(SPType::SP_ACTION));
+        assert_eq!(line_types[3], Some(SPType::SP_CHARACTER));
+        assert_eq!(line_types[4], Some(SPType::SP_PARENTHETICAL));
+        assert_eq!(line_types[5], Some(SPType::SP_DIALOGUE));
+        assert_eq!(parsed_doc.scenes.len(), 3);
+    }
+
+    #[test]
+    fn fountain_parsing() {
+        use crate::reports;
+        use crate::screenplay_document::{Environment, SceneNumber};
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_filepath(
+            "test_data/FountainTest.fountain".into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Title page, then two content pages split by the forced page break
+        assert_eq!(screenplay.pages.len(), 3);
+        let title_page = &screenplay.pages[0];
+        assert!(title_page.page_number.is_none());
+        assert_eq!(title_page.lines[0].line_type, Some(SPType::TP_TITLE));
+        let revisions = screenplay.revisions.as_ref().unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].label, "10/18/2026");
+        assert_eq!(revisions[0].color, RevisionColor::White);
+        assert!(revisions[0].date.is_some());
+        let title_page = screenplay.title_page.as_ref().unwrap();
+        assert_eq!(title_page.title, Some("Fountain Test".to_string()));
+        assert_eq!(title_page.authors, vec!["Jane Doe".to_string()]);
+        assert_eq!(title_page.contact.len(), 2);
+        assert!(title_page.draft_dates[0].date.is_some());
+
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        let scene_numbers: Vec<Option<SceneNumber>> =
+            scenes.iter().map(|(_, s)| s.number.clone()).collect();
+        assert_eq!(
+            scene_numbers,
+            vec![
+                Some(SceneNumber("1".into())),
+                Some(SceneNumber("2".into())),
+                None,
+                Some(SceneNumber("3".into())),
+            ]
+        );
+        assert_eq!(scenes[0].1.environment, Environment::Int);
+        assert_eq!(scenes[1].1.environment, Environment::Ext);
+        assert_eq!(scenes[3].1.start.page, 2);
+        // Same location, so the same LocationNode
+        assert_eq!(scenes[0].1.story_locations, scenes[3].1.story_locations);
+        let kitchen = screenplay
+            .locations
+            .get(scenes[0].1.story_locations.last().unwrap())
+            .unwrap();
+        assert_eq!(kitchen.string, "KITCHEN");
+
+        // Boneyard and notes are dropped
+        let first_page = &screenplay.pages[1];
+        assert!(first_page.lines.iter().all(|l| {
+            l.text_elements
+                .iter()
+                .all(|e| e.text != "Cut" && e.text != "Rewrite")
+        }));
+        assert!(
+            first_page
+                .lines
+                .iter()
+                .any(|l| l.line_type == Some(SPType::SP_TRANSITION))
+        );
+
+        assert_eq!(screenplay.characters.len(), 2);
+        for name in ["BOB", "ALICE"] {
+            let character = screenplay
+                .characters
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap();
+            let dialogue =
+                reports::get_all_lines_of_dialogue_for_character(&screenplay, character).unwrap();
+            assert_eq!(dialogue.len(), 2);
+        }
+
+        // Dual dialogue
+        let dual_character_lines: Vec<&screenplay_document::Line> = first_page
+            .lines
+            .iter()
+            .filter(|l| l.line_type == Some(SPType::SP_DUAL_CHARACTERS))
+            .collect();
+        assert_eq!(dual_character_lines.len(), 2);
+        assert_eq!(
+            dual_character_lines[0].text_elements[0].element_type,
+            Some(SPType::SP_DD_L_CHARACTER)
+        );
+        assert_eq!(
+            dual_character_lines[1].text_elements[0].element_type,
+            Some(SPType::SP_DD_R_CHARACTER)
+        );
+
+        // Emphasis markers are dropped, unless they are escaped or unpaired
+        let formatted = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "Title: _**Brick & Steel**_\n\nINT. HOUSE - DAY\n\nJohn walks *slowly* and **boldly**. \\*Not\\* ***very*** _far_, 2 * 3 steps.\n\n>THE END<\n",
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            formatted.title_page.as_ref().unwrap().title,
+            Some("Brick & Steel".to_string())
+        );
+        let content_lines: Vec<&screenplay_document::Line> = formatted.pages[1]
+            .lines
+            .iter()
+            .filter(|l| !l.text_elements.is_empty())
+            .collect();
+        let action_text: Vec<&str> = content_lines[1]
+            .text_elements
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect();
+        assert_eq!(
+            action_text.join(" "),
+            "John walks slowly and boldly. *Not* very far, 2 * 3 steps."
+        );
+
+        // Centered text is action, marked as centered
+        let the_end = content_lines[2];
+        assert_eq!(the_end.line_type, Some(SPType::SP_ACTION));
+        assert!(the_end.centered);
+        assert!(!content_lines[1].centered);
+        let fountain = fountain_writer::get_fountain_str_from_screenplay_doc(
+            &formatted,
+            &fountain_writer::FountainWriterOptions::default(),
+        );
+        assert!(fountain.contains("\n>THE END<\n"));
+        assert!(fountain.contains("\\*Not\\*"));
+        let fdx = fdx_writer::get_fdx_str_from_screenplay_doc(&formatted, None, None);
+        assert!(fdx.contains("<Paragraph Alignment=\"Center\" Type=\"Action\">"));
+    }
+
+    #[test]
+    fn fountain_writing() {
+        use crate::fountain_writer::FountainWriterOptions;
+        use crate::reports;
+
+        let original = fountain_parser::get_screenplay_doc_from_fountain_filepath(
+            "test_data/FountainTest.fountain".into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let without_breaks = fountain_writer::get_fountain_str_from_screenplay_doc(
+            &original,
+            &FountainWriterOptions::default(),
+        );
+        assert!(!without_breaks.contains("==="));
+
+        let fountain = fountain_writer::get_fountain_str_from_screenplay_doc(
+            &original,
+            &FountainWriterOptions::default().page_breaks(true),
+        );
+        assert!(fountain.starts_with("Title: Fountain Test\n"));
+        assert!(fountain.contains("INT. HOUSE - KITCHEN - NIGHT #1#\n"));
+        assert!(fountain.contains(".FLASHBACK\n"));
+        assert!(fountain.contains("BOB\n(quietly)\nIs anyone home?\n"));
+        assert!(fountain.contains("ALICE (O.S.)\nJust me.\n"));
+        assert!(fountain.contains("ALICE ^\nBall!\n"));
+        assert!(fountain.contains("\nCUT TO:\n"));
+        assert!(fountain.contains("\n===\n"));
+
+        // Writing and re-parsing should give back the same script
+        let reparsed =
+            fountain_parser::get_screenplay_doc_from_fountain_str(&fountain, None, None).unwrap();
+        assert_eq!(reparsed.pages.len(), original.pages.len());
+        assert_eq!(reparsed.revisions, original.revisions);
+        let scene_numbers = |doc| -> Vec<Option<screenplay_document::SceneNumber>> {
+            reports::get_all_scenes_ordered(doc)
+                .unwrap()
+                .iter()
+                .map(|(_, s)| s.number.clone())
+                .collect()
+        };
+        assert_eq!(scene_numbers(&reparsed), scene_numbers(&original));
+        assert_eq!(reparsed.characters.len(), original.characters.len());
+        for (original_page, reparsed_page) in original.pages.iter().zip(&reparsed.pages) {
+            let line_types = |page: &screenplay_document::Page| -> Vec<Option<SPType>> {
+                page.lines.iter().map(|l| l.line_type).collect()
+            };
+            assert_eq!(line_types(reparsed_page), line_types(original_page));
+        }
+
+        // PDF headings start with their scene number, but aren't forced headings
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut heading = get_scene_heading_line("INT.", "HOUSE - DAY", "12", &indentations);
+        heading.words.insert(
+            0,
+            _create_pdfword("12".to_string(), indentations.left - 36.0, None),
+        );
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        mock_pdf.pages.push(pdf_document::Page {
+            lines: vec![heading],
+            ..Default::default()
+        });
+        let pdf_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            pdf_doc.pages[0].lines[0].text_elements[0].element_type,
+            Some(SPType::SP_SCENENUM)
+        );
+        let fountain = fountain_writer::get_fountain_str_from_screenplay_doc(
+            &pdf_doc,
+            &FountainWriterOptions::default(),
+        );
+        assert_eq!(fountain, "INT. HOUSE - DAY #12#\n");
+    }
+
+    #[test]
+    fn fdx_parsing() {
+        use crate::reports;
+        use crate::screenplay_document::{PageNumber, SceneNumber};
+
+        let screenplay = fdx_parser::get_screenplay_doc_from_fdx_filepath(
+            "test_data/FDXTest.fdx".into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let title_page_types: Vec<Option<SPType>> = screenplay.pages[0]
+            .lines
+            .iter()
+            .map(|l| l.line_type)
+            .collect();
+        assert_eq!(
+            title_page_types,
+            vec![
+                Some(SPType::TP_TITLE),
+                Some(SPType::TP_BYLINE),
+                Some(SPType::TP_AUTHOR),
+                Some(SPType::TP_CONTACT),
+                Some(SPType::TP_DRAFT_DATE),
+            ]
+        );
+        let title_page = screenplay.title_page.as_ref().unwrap();
+        assert_eq!(title_page.credit, Some("Written by".to_string()));
+        assert_eq!(title_page.draft_dates[0].text, "Draft: 10/18/2026");
+        assert!(title_page.draft_dates[0].date.is_some());
+
+        // The second scene starts on page 3, according to its SceneProperties
+        assert_eq!(screenplay.pages.len(), 3);
+        assert_eq!(
+            screenplay.pages[2].page_number,
+            Some(PageNumber("3".into()))
+        );
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        assert_eq!(scenes.len(), 2);
+        assert_eq!(scenes[0].1.number, Some(SceneNumber("1".into())));
+        assert_eq!(scenes[1].1.number, Some(SceneNumber("2A".into())));
+        assert_eq!(scenes[1].1.start.page, 2);
+
+        // Revisions
+        let revisions = screenplay.revisions.as_ref().unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].label, "Blue Revision");
+        assert_eq!(revisions[0].color, RevisionColor::Blue);
+        assert!(scenes[0].1.revised);
+        assert!(!scenes[1].1.revised);
+        assert!(screenplay.pages[1].revised);
+        assert_eq!(
+            screenplay.pages[1].revision_label,
+            Some("Blue Revision".to_string())
+        );
+        assert!(!screenplay.pages[2].revised);
+
+        // Unnamed revision sets only have a hex color
+        let unnamed_sets = fdx_parser::get_screenplay_doc_from_fdx_str(
+            r##"<FinalDraft DocumentType="Script" Version="5">
+              <Content>
+                <Paragraph Type="Scene Heading" Number="1">
+                  <Text>INT. HOUSE - DAY</Text>
+                </Paragraph>
+                <Paragraph Type="Action">
+                  <Text RevisionID="1">Light blue paper.</Text>
+                </Paragraph>
+                <Paragraph Type="Scene Heading" Number="2">
+                  <SceneProperties Page="2"/>
+                  <Text>INT. HOUSE - NIGHT</Text>
+                </Paragraph>
+                <Paragraph Type="Action">
+                  <Text RevisionID="2">Gray paper.</Text>
+                </Paragraph>
+              </Content>
+              <Revisions>
+                <Revision Color="#9999CCCCFFFF" ID="1" Name=""/>
+                <Revision Color="#808080808080" ID="2" Name=""/>
+              </Revisions>
+            </FinalDraft>"##,
+            None,
+            None,
+        )
+        .unwrap();
+        let labels: Vec<(bool, Option<String>)> = unnamed_sets
+            .pages
+            .iter()
+            .map(|p| (p.revised, p.revision_label.clone()))
+            .collect();
+        assert_eq!(labels, vec![(true, Some("Blue".to_string())), (true, None)]);
+        assert_eq!(
+            unnamed_sets.revisions.as_ref().unwrap()[0].color,
+            RevisionColor::Blue
+        );
+
+        let line_types: Vec<Option<SPType>> = screenplay.pages[1..]
+            .iter()
+            .flat_map(|p| p.lines.iter().map(|l| l.line_type))
+            .collect();
+        for expected in [
+            SPType::SP_ACTION,
+            SPType::SP_PARENTHETICAL,
+            SPType::SP_TRANSITION,
+            SPType::SP_SHOT_ANGLE,
+            SPType::SP_DUAL_CHARACTERS,
+            SPType::SP_DUAL_DIALOGUES,
+            SPType::SP_OTHER,
+        ] {
+            assert!(line_types.contains(&Some(expected)));
+        }
+
+        assert_eq!(screenplay.characters.len(), 2);
+        for (name, lines_of_dialogue) in [("BOB", 2), ("ALICE", 1)] {
+            let character = screenplay
+                .characters
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap();
+            let dialogue =
+                reports::get_all_lines_of_dialogue_for_character(&screenplay, character).unwrap();
+            assert_eq!(dialogue.len(), lines_of_dialogue);
+        }
+    }
+
     fn get_scene_heading_line(
         env: &str,
         text: &str,
@@ -708,7 +1166,7 @@ mod tests {
 
             last_word = text.clone();
             last_word_pos = new_x_offset;
-            return new_word;
+            new_word
         };
 
         let scene_heading_words = text.split_whitespace();
@@ -725,7 +1183,7 @@ mod tests {
             None,
         ));
 
-        return scene_heading_line;
+        scene_heading_line
     }
 
     // TODO: Implement and Test FORCING Scene Numbers for scenes that aren't assigned numbers
@@ -735,7 +1193,7 @@ mod tests {
         let indentations = ElementIndentationsPoints::us_letter_default(&None);
 
         println!(" ------ Scene Parsing ------ ");
-        println!("");
+        println!();
 
         let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
         let mut new_page = pdf_document::Page::default();
@@ -759,8 +1217,1933 @@ mod tests {
         mock_pdf.pages.push(second_page);
 
         let parsed_doc =
-            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None);
+            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None, None);
 
         println!("{:#?}", parsed_doc);
     }
+
+    #[test]
+    fn dual_dialogue_parsing() {
+        use crate::reports;
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut new_page = pdf_document::Page::default();
+
+        new_page.lines.push(get_scene_heading_line(
+            "EXT.",
+            "BASEBALL FIELD - DAY",
+            "1",
+            &indentations,
+        ));
+        let mut line_with_words = |words: Vec<(&str, f64)>, y_inches: f64| {
+            new_page.lines.push(pdf_document::Line {
+                words: words
+                    .into_iter()
+                    .map(|(text, x_inches)| {
+                        _create_pdfword(text.to_string(), x_inches * 72.0, Some(y_inches))
+                    })
+                    .collect(),
+            });
+        };
+        // Both character cues are on the same line, each column is typed separately below
+        line_with_words(vec![("BOB", 2.5), ("ALICE", 5.25)], 3.0 - (24.0 / 72.0));
+        line_with_words(vec![("(yelling)", 2.0), ("Ball!", 4.5)], 2.5);
+        line_with_words(vec![("Strike!", 1.75)], 2.5 - (12.0 / 72.0));
+        line_with_words(vec![("The", 1.5), ("ball", 1.5 + 0.4)], 2.0);
+
+        mock_pdf.pages.push(new_page);
+        let parsed_doc =
+            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None, None)
+                .unwrap();
+
+        let line_types: Vec<Option<SPType>> = parsed_doc.pages[0]
+            .lines
+            .iter()
+            .map(|l| l.line_type)
+            .collect();
+        assert_eq!(
+            line_types[1..],
+            [
+                Some(SPType::SP_DUAL_CHARACTERS),
+                Some(SPType::SP_DUAL_DIALOGUES),
+                Some(SPType::SP_DUAL_DIALOGUES),
+                Some(SPType::SP_ACTION),
+            ]
+        );
+        let element_types: Vec<Option<SPType>> = parsed_doc.pages[0].lines[2]
+            .text_elements
+            .iter()
+            .map(|e| e.element_type)
+            .collect();
+        assert_eq!(
+            element_types,
+            [
+                Some(SPType::SP_DD_L_PARENTHETICAL),
+                Some(SPType::SP_DD_R_DIALOGUE)
+            ]
+        );
+
+        for (name, expected_text) in [("BOB", "Strike!"), ("ALICE", "Ball!")] {
+            let character = parsed_doc
+                .characters
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap();
+            let lines =
+                reports::get_all_lines_of_dialogue_for_character(&parsed_doc, character).unwrap();
+            assert_eq!(lines.len(), 1);
+            let (coordinate, line) = &lines[0];
+            assert_eq!(
+                line.text_elements[coordinate.element.unwrap() as usize].text,
+                expected_text
+            );
+        }
+    }
+
+    #[test]
+    fn transition_parsing() {
+        use crate::pdf_document::ElementIndentationsInches;
+        use crate::reports;
+        use crate::screenplay_document::TransitionStrings;
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let create_line = |words: Vec<(&str, f64)>, y_inches: f64| pdf_document::Line {
+            words: words
+                .into_iter()
+                .map(|(text, x_pts)| _create_pdfword(text.to_string(), x_pts, Some(y_inches)))
+                .collect(),
+        };
+        // Right-aligned, ending just before the right margin
+        let create_transition_line = |text: &str, y_inches: f64| {
+            let mut x_pts = indentations.right - (text.len() as f64 * 7.2) - 7.2;
+            let words: Vec<(&str, f64)> = text
+                .split_whitespace()
+                .map(|word| {
+                    let word_x = x_pts;
+                    x_pts += (word.len() as f64 + 1.0) * 7.2;
+                    (word, word_x)
+                })
+                .collect();
+            create_line(words, y_inches)
+        };
+
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut first_page = pdf_document::Page::default();
+        first_page.lines.push(create_line(
+            vec![
+                ("FADE", indentations.action),
+                ("IN:", indentations.action + 36.0),
+            ],
+            9.5,
+        ));
+        first_page.lines.push(get_scene_heading_line(
+            "INT.",
+            "HOUSE - DAY",
+            "1",
+            &indentations,
+        ));
+        first_page
+            .lines
+            .push(create_line(vec![("Bob", indentations.action)], 2.5));
+        first_page
+            .lines
+            .push(create_transition_line("CUT TO:", 2.0));
+        mock_pdf.pages.push(first_page);
+
+        let mut second_page = pdf_document::Page::default();
+        second_page.lines.push(get_scene_heading_line(
+            "EXT.",
+            "HOUSE - NIGHT",
+            "2",
+            &indentations,
+        ));
+        second_page
+            .lines
+            .push(create_transition_line("DISSOLVE TO:", 2.5));
+        second_page
+            .lines
+            .push(create_line(vec![("Alice", indentations.action)], 2.0));
+        mock_pdf.pages.push(second_page);
+
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed_doc.pages[0].lines[0].line_type,
+            Some(SPType::SP_TRANSITION)
+        );
+        assert_eq!(
+            parsed_doc.pages[0].lines[3].line_type,
+            Some(SPType::SP_TRANSITION)
+        );
+        assert_eq!(reports::get_all_transitions(&parsed_doc).unwrap().len(), 3);
+
+        let scenes = reports::get_all_scenes_ordered(&parsed_doc).unwrap();
+        let (first_scene_id, _) = scenes[0];
+        let (second_scene_id, _) = scenes[1];
+        assert_eq!(
+            reports::get_transitions_for_scene(&parsed_doc, first_scene_id)
+                .unwrap()
+                .len(),
+            1
+        );
+        let (coord, _) =
+            reports::get_ending_transition_for_scene(&parsed_doc, first_scene_id).unwrap();
+        assert_eq!((coord.page, coord.line), (0, 3));
+
+        // The second scene has a transition, but doesn't end on it
+        assert!(reports::get_transitions_for_scene(&parsed_doc, second_scene_id).is_some());
+        assert!(reports::get_ending_transition_for_scene(&parsed_doc, second_scene_id).is_none());
+        assert_eq!(
+            reports::get_all_ending_transitions(&parsed_doc)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let transition_strings = TransitionStrings::default();
+        assert!(transition_strings.is_right_aligned_transition("Cut to:"));
+        assert!(transition_strings.is_right_aligned_transition("CUT TO"));
+        assert!(transition_strings.is_left_aligned_transition("Fade in:"));
+        assert!(!transition_strings.is_right_aligned_transition("CUT"));
+    }
+
+    #[test]
+    fn title_page_parsing() {
+        use chrono::Datelike;
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let create_line = |text: &str, x_pts: Option<f64>, y_inches: f64| {
+            // Centered, unless an x-position is given
+            let mut x_pts =
+                x_pts.unwrap_or((indentations.pagewidth - text.len() as f64 * 7.2) / 2.0);
+            pdf_document::Line {
+                words: text
+                    .split_whitespace()
+                    .map(|word| {
+                        let new_word = _create_pdfword(word.to_string(), x_pts, Some(y_inches));
+                        x_pts += (word.len() as f64 + 1.0) * 7.2;
+                        new_word
+                    })
+                    .collect(),
+            }
+        };
+
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let title_page = pdf_document::Page {
+            lines: vec![
+                create_line("THE BIG", None, 7.0),
+                create_line("GAME", None, 7.0 - (12.0 / 72.0)),
+                create_line("Written by", None, 6.0),
+                create_line("Jane Doe", None, 5.5),
+                create_line("Based on the novel by John Roe", None, 5.0),
+                create_line("jane@example.com", Some(indentations.action), 1.5),
+                create_line("October 18, 2026", Some(5.5 * 72.0), 1.5),
+            ],
+            ..Default::default()
+        };
+        mock_pdf.pages.push(title_page);
+
+        let mut first_page = pdf_document::Page::default();
+        first_page.lines.push(get_scene_heading_line(
+            "INT.",
+            "HOUSE - DAY",
+            "1",
+            &indentations,
+        ));
+        mock_pdf.pages.push(first_page);
+
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let title_page_types: Vec<Option<SPType>> = parsed_doc.pages[0]
+            .lines
+            .iter()
+            .map(|l| l.line_type)
+            .collect();
+        assert_eq!(
+            title_page_types,
+            vec![
+                Some(SPType::TP_TITLE),
+                Some(SPType::TP_TITLE),
+                Some(SPType::TP_BYLINE),
+                Some(SPType::TP_AUTHOR),
+                Some(SPType::TP_SOURCE),
+                Some(SPType::TP_CONTACT),
+                Some(SPType::TP_DRAFT_DATE),
+            ]
+        );
+        assert!(parsed_doc.pages[0].page_number.is_none());
+
+        let title_page = parsed_doc.title_page.as_ref().unwrap();
+        assert_eq!(title_page.title, Some("THE BIG GAME".to_string()));
+        assert_eq!(title_page.credit, Some("Written by".to_string()));
+        assert_eq!(title_page.authors, vec!["Jane Doe".to_string()]);
+        assert_eq!(
+            title_page.source,
+            Some("Based on the novel by John Roe".to_string())
+        );
+        assert_eq!(title_page.contact, vec!["jane@example.com".to_string()]);
+        assert_eq!(title_page.draft_dates[0].date.unwrap().year(), 2026);
+        let revisions = parsed_doc.revisions.as_ref().unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].label, "October 18, 2026");
+        assert_eq!(revisions[0].color, RevisionColor::White);
+
+        // The scene still starts on the first content page
+        let scenes = reports::get_all_scenes_ordered(&parsed_doc).unwrap();
+        assert_eq!(scenes[0].1.start.page, 1);
+    }
+
+    #[test]
+    fn page_format_detection() {
+        use crate::pdf_document::PageSize;
+        use crate::screenplay_document::{PageFormat, SceneHeadingElement};
+
+        let us_letter = PageSize {
+            width: 612.0,
+            height: 792.0,
+        };
+        let a4 = PageSize {
+            width: 595.28,
+            height: 841.89,
+        };
+        assert_eq!(us_letter.get_page_format(), Some(PageFormat::US));
+        assert_eq!(a4.get_page_format(), Some(PageFormat::A4));
+        assert_eq!(
+            PageSize {
+                width: 841.89,
+                height: 595.28
+            }
+            .get_page_format(),
+            Some(PageFormat::A4)
+        );
+        assert_eq!(PageSize::default().get_page_format(), None);
+
+        // A US-Letter script with an A4 page in it
+        let us_indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut us_page = pdf_document::Page {
+            page_size: us_letter,
+            ..Default::default()
+        };
+        us_page.lines.push(_create_pdfline_with_word(
+            "1.".into(),
+            7.0 * 72.0,
+            Some(10.5),
+        ));
+        us_page.lines.push(get_scene_heading_line(
+            "INT.",
+            "HOUSE - DAY",
+            "1",
+            &us_indentations,
+        ));
+        mock_pdf.pages.push(us_page);
+
+        // Above the US-Letter top margin, but within the A4 one
+        let mut a4_heading = get_scene_heading_line(
+            "EXT.",
+            "HOUSE - NIGHT",
+            "2",
+            &ElementIndentationsPoints::a4_default(&None),
+        );
+        for word in a4_heading.words.iter_mut() {
+            word.position.y = 10.5 * 72.0;
+        }
+        mock_pdf.pages.push(pdf_document::Page {
+            lines: vec![a4_heading],
+            page_size: a4,
+        });
+
+        let parsed_doc =
+            pdf_parser::get_screenplay_doc_from_pdf_obj(mock_pdf, None, None, None, None, None)
+                .unwrap();
+        assert_eq!(parsed_doc.pages[0].page_format, Some(PageFormat::US));
+        assert_eq!(
+            parsed_doc.pages[0].page_number,
+            Some(screenplay_document::PageNumber("1.".into()))
+        );
+        assert_eq!(parsed_doc.pages[1].page_format, Some(PageFormat::A4));
+        assert_eq!(
+            parsed_doc.pages[1].lines[0].line_type,
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line))
+        );
+        assert_eq!(parsed_doc.scenes.len(), 2);
+    }
+
+    #[test]
+    fn revision_labels() {
+        use crate::screenplay_document::RevisionColorSequence;
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        for (idx, label) in ["", "Pink Rev. 03/20/26", "Blue Rev. 03/14/26"]
+            .iter()
+            .enumerate()
+        {
+            let mut header =
+                _create_pdfline_with_word(format!("{}.", idx + 1), 7.0 * 72.0, Some(10.5));
+            for (word_idx, word) in label.split_whitespace().enumerate() {
+                header.words.push(_create_pdfword(
+                    word.into(),
+                    (1.0 + word_idx as f64 * 0.5) * 72.0,
+                    Some(10.5),
+                ));
+            }
+            let mut page = pdf_document::Page::default();
+            page.lines.push(header);
+            page.lines.push(get_scene_heading_line(
+                "INT.",
+                "HOUSE - DAY",
+                &(idx + 1).to_string(),
+                &indentations,
+            ));
+            mock_pdf.pages.push(page);
+        }
+
+        let mut parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(!parsed_doc.pages[0].revised);
+        assert_eq!(
+            parsed_doc.pages[1].revision_label,
+            Some("Pink Rev. 03/20/26".into())
+        );
+        assert_eq!(parsed_doc.pages[1].revision_date, Some("03/20/26".into()));
+        assert!(parsed_doc.pages[1].revised);
+
+        // WGA order, not page order
+        let colors: Vec<RevisionColor> = parsed_doc
+            .revisions
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|r| r.color.clone())
+            .collect();
+        assert_eq!(colors, vec![RevisionColor::Blue, RevisionColor::Pink]);
+        assert_eq!(
+            parsed_doc.revisions.as_ref().unwrap()[0]
+                .date
+                .unwrap()
+                .to_string(),
+            "2026-03-14 00:00:00 UTC"
+        );
+
+        let sequence = RevisionColorSequence {
+            colors: vec![
+                RevisionColor::White,
+                RevisionColor::Pink,
+                RevisionColor::Other("Double Blue".into()),
+                RevisionColor::Blue,
+            ],
+        };
+        assert_eq!(
+            sequence.get_color_for_str("DOUBLE BLUE REVISIONS"),
+            Some(RevisionColor::Other("Double Blue".into()))
+        );
+        assert_eq!(
+            sequence.get_next_color(&RevisionColor::Pink),
+            Some(&RevisionColor::Other("Double Blue".into()))
+        );
+        parsed_doc.set_revisions(&sequence);
+        assert_eq!(
+            parsed_doc.revisions.as_ref().unwrap()[0].color,
+            RevisionColor::Pink
+        );
+
+        // A label on its own header line, without a page number
+        let mut label_line = pdf_document::Line::default();
+        for (word_idx, word) in "Blue Rev. 03/12/24".split_whitespace().enumerate() {
+            label_line.words.push(_create_pdfword(
+                word.into(),
+                (1.0 + word_idx as f64 * 0.5) * 72.0,
+                Some(10.5),
+            ));
+        }
+        let mut page = pdf_document::Page::default();
+        page.lines.push(label_line);
+        page.lines.push(get_scene_heading_line(
+            "INT.",
+            "HOUSE - DAY",
+            "1",
+            &indentations,
+        ));
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        mock_pdf.pages.push(page);
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let page = &parsed_doc.pages[0];
+        assert_eq!(page.revision_label, Some("Blue Rev. 03/12/24".into()));
+        assert_eq!(page.revision_date, Some("03/12/24".into()));
+        assert!(page.revised);
+        assert_eq!(page.lines[0].line_type, Some(SPType::SP_PAGE_HEADER));
+        assert_eq!(
+            parsed_doc.revisions.as_ref().unwrap()[0].color,
+            RevisionColor::Blue
+        );
+
+        // Header text without a revision color or date isn't a revision label
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        for (idx, header_text) in ["MY SCRIPT", "CONTINUED:"].iter().enumerate() {
+            let mut header_line = if idx == 0 {
+                _create_pdfline_with_word(format!("{}.", idx + 1), 7.0 * 72.0, Some(10.5))
+            } else {
+                pdf_document::Line::default()
+            };
+            for (word_idx, word) in header_text.split_whitespace().enumerate() {
+                header_line.words.push(_create_pdfword(
+                    word.into(),
+                    (1.0 + word_idx as f64 * 0.5) * 72.0,
+                    Some(10.5),
+                ));
+            }
+            let mut page = pdf_document::Page::default();
+            page.lines.push(header_line);
+            page.lines.push(get_scene_heading_line(
+                "INT.",
+                "HOUSE - DAY",
+                &(idx + 1).to_string(),
+                &indentations,
+            ));
+            mock_pdf.pages.push(page);
+        }
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        for page in &parsed_doc.pages {
+            assert!(!page.revised);
+            assert_eq!(page.revision_label, None);
+        }
+        assert!(parsed_doc.revisions.as_ref().is_none_or(|r| r.is_empty()));
+        let fdx = fdx_writer::get_fdx_str_from_screenplay_doc(&parsed_doc, None, None);
+        assert!(!fdx.contains("<Revision "));
+    }
+
+    #[test]
+    fn scene_numbering() {
+        use crate::reports::SceneNumberingIssue;
+        use crate::screenplay_document::{SceneNumber, SceneNumberParts};
+
+        assert_eq!(
+            SceneNumber("a12B*".into()).get_parts(),
+            Some(SceneNumberParts {
+                prefix: "A".into(),
+                number: 12,
+                suffix: "B".into()
+            })
+        );
+        assert_eq!(SceneNumber("12-14".into()).get_parts(), None);
+        let mut numbers: Vec<SceneNumber> = ["13", "12B", "12", "B12", "12A", "A12", "AA12"]
+            .iter()
+            .map(|n| SceneNumber(n.to_string()))
+            .collect();
+        numbers.sort();
+        let sorted: Vec<&str> = numbers.iter().map(|n| n.0.as_str()).collect();
+        assert_eq!(sorted, vec!["A12", "B12", "AA12", "12", "12A", "12B", "13"]);
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. HOUSE - DAY #12#\n\
+            \n\
+            Quiet.\n\
+            \n\
+            .OMITTED #13#\n\
+            \n\
+            EXT. HOUSE - DAY #15#\n\
+            \n\
+            Loud.\n\
+            \n\
+            EXT. STREET - DAY #15B#\n\
+            \n\
+            EXT. STREET - NIGHT #15A#\n\
+            \n\
+            EXT. PARK - NIGHT #12#\n\
+            \n\
+            EXT. PARK - DAY\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        assert!(scenes[1].1.omitted);
+        assert_eq!(scenes[1].1.number, Some(SceneNumber("13".into())));
+        assert_eq!(scenes[1].1.length_eighths, Some(0));
+        assert_eq!(
+            stripboard::get_stripboard(&screenplay)
+                .unwrap()
+                .get_strips()
+                .len(),
+            6
+        );
+        // Written back out as a forced heading, so it's still omitted when read again
+        assert!(
+            fountain_writer::get_fountain_str_from_screenplay_doc(
+                &screenplay,
+                &fountain_writer::FountainWriterOptions::default()
+            )
+            .contains(".OMITTED #13#")
+        );
+
+        let issues: Vec<SceneNumberingIssue> = reports::validate_scene_numbers(&screenplay)
+            .unwrap()
+            .into_iter()
+            .map(|(_, issue)| issue)
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                SceneNumberingIssue::Gap {
+                    previous: "13".into()
+                },
+                SceneNumberingIssue::OutOfOrder {
+                    previous: "15B".into()
+                },
+                SceneNumberingIssue::Duplicate,
+                SceneNumberingIssue::Missing,
+            ]
+        );
+
+        // In a PDF, omitted scenes are just the scene numbers and OMITTED
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut page = pdf_document::Page::default();
+        let mut heading = get_scene_heading_line("INT.", "HOUSE - DAY", "7", &indentations);
+        heading.words.insert(
+            0,
+            _create_pdfword("6".into(), indentations.left - 36.0, None),
+        );
+        page.lines.push(heading);
+        let mut omitted = _create_pdfline_with_word("8".into(), indentations.left - 36.0, None);
+        omitted
+            .words
+            .push(_create_pdfword("OMITTED".into(), indentations.action, None));
+        omitted
+            .words
+            .push(_create_pdfword("8".into(), indentations.right, None));
+        page.lines.push(omitted);
+        mock_pdf.pages.push(page);
+
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed_doc.pages[0].lines[1].line_type,
+            Some(SPType::SP_OMITTED)
+        );
+        let scenes = reports::get_all_scenes_ordered(&parsed_doc).unwrap();
+        assert_eq!(scenes.len(), 2);
+        assert!(scenes[1].1.omitted);
+        assert_eq!(scenes[1].1.number, Some(SceneNumber("8".into())));
+        assert_eq!(
+            reports::validate_scene_numbers(&parsed_doc).unwrap()[0].1,
+            SceneNumberingIssue::MarginMismatch {
+                left: "6".into(),
+                right: "7".into()
+            }
+        );
+    }
+
+    #[test]
+    fn split_scene_headings() {
+        use crate::screenplay_document::{Environment, EnvironmentStrings};
+
+        let env_strs = EnvironmentStrings::default();
+        assert_eq!(
+            Environment::from_str(&"EXT./INT.".into(), &env_strs),
+            Some(Environment::Combo(Some(vec![
+                Environment::Ext,
+                Environment::Int
+            ])))
+        );
+        assert_eq!(
+            Environment::from_str(&"I./E.".into(), &env_strs),
+            Some(Environment::Combo(Some(vec![
+                Environment::Int,
+                Environment::Ext
+            ])))
+        );
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. CAR / EXT. ROAD - NIGHT\n\
+            \n\
+            Headlights.\n\
+            \n\
+            EXT./INT. CAR - DAY\n\
+            \n\
+            Sunlight.\n\
+            \n\
+            INT. HOUSE / GARAGE - DAY\n\
+            \n\
+            Tools.\n\
+            \n\
+            EXT. ROAD - DAY\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        assert_eq!(
+            scenes[0].1.environment,
+            Environment::Combo(Some(vec![Environment::Int, Environment::Ext]))
+        );
+        let paths: Vec<String> = scenes[0]
+            .1
+            .story_locations
+            .iter()
+            .map(|id| reports::get_full_string_for_location_path(&screenplay, id).unwrap())
+            .collect();
+        assert_eq!(paths, vec!["INT. CAR", "EXT. ROAD"]);
+        assert_eq!(
+            scenes[1].1.environment,
+            Environment::Combo(Some(vec![Environment::Ext, Environment::Int]))
+        );
+        // A `/` which isn't followed by an environment is part of the location
+        assert_eq!(scenes[2].1.environment, Environment::Int);
+        assert_eq!(
+            reports::get_full_string_for_scene_locations(&screenplay, scenes[2].1),
+            Some("INT. HOUSE / GARAGE".into())
+        );
+
+        // Both sides link to their own location, so either finds the scene
+        let road = &scenes[3].1.story_locations[0];
+        let road_scenes = reports::get_scenes_with_location(&screenplay, road).unwrap();
+        assert_eq!(road_scenes.len(), 2);
+        let interiors =
+            reports::get_all_scenes_with_environment(&screenplay, &Environment::Int).unwrap();
+        assert_eq!(interiors.len(), 3);
+        let exteriors =
+            reports::get_all_scenes_with_environment(&screenplay, &Environment::Ext).unwrap();
+        assert_eq!(exteriors.len(), 3);
+        assert_eq!(
+            stripboard::get_stripboard(&screenplay)
+                .unwrap()
+                .get_strips()[0]
+                .location,
+            Some("INT. CAR / EXT. ROAD".into())
+        );
+
+        // The same split heading in a PDF
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut page = pdf_document::Page::default();
+        page.lines.push(get_scene_heading_line(
+            "INT.",
+            "CAR / EXT. ROAD - NIGHT",
+            "1",
+            &indentations,
+        ));
+        mock_pdf.pages.push(page);
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&parsed_doc).unwrap();
+        assert_eq!(
+            scenes[0].1.environment,
+            Environment::Combo(Some(vec![Environment::Int, Environment::Ext]))
+        );
+        assert_eq!(
+            reports::get_full_string_for_scene_locations(&parsed_doc, scenes[0].1),
+            Some("INT. CAR / EXT. ROAD".into())
+        );
+    }
+
+    #[test]
+    fn continuity_and_time_periods() {
+        use crate::screenplay_document::{Continuity, SceneHeadingElement, TimeOfDay, TimePeriod};
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. HOUSE - NIGHT\n\
+            \n\
+            EXT. YARD - CONTINUOUS\n\
+            \n\
+            INT. HOUSE - KITCHEN - MOMENTS LATER\n\
+            \n\
+            EXT. BEACH - DAY (FLASHBACK)\n\
+            \n\
+            INT. DINER - 1985 - NIGHT\n\
+            \n\
+            INT. DINER - SAME\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+
+        assert_eq!(
+            scenes[1].1.continuity,
+            Some(Continuity::Continuous("CONTINUOUS".into()))
+        );
+        assert_eq!(
+            reports::get_full_string_for_scene_locations(&screenplay, scenes[1].1),
+            Some("EXT. YARD".into())
+        );
+        assert_eq!(
+            scenes[2].1.continuity,
+            Some(Continuity::Later("MOMENTS LATER".into()))
+        );
+        assert_eq!(
+            reports::get_full_string_for_scene_locations(&screenplay, scenes[2].1),
+            Some("INT. HOUSE - KITCHEN".into())
+        );
+        assert_eq!(
+            scenes[3].1.time_period,
+            Some(TimePeriod::Flashback("FLASHBACK".into()))
+        );
+        assert_eq!(
+            scenes[4].1.time_period,
+            Some(TimePeriod::Year("1985".into()))
+        );
+        let heading = &screenplay.pages[0].lines[scenes[4].1.start.line];
+        assert!(heading.text_elements.iter().any(|e| e.text == "1985"
+            && e.element_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::TimePeriod))));
+
+        // CONTINUOUS and SAME take the time of day of the scene before them
+        let night = TimeOfDay::Night("NIGHT".into());
+        assert_eq!(
+            reports::get_effective_time_of_day(&screenplay, scenes[1].0),
+            Some(&night)
+        );
+        assert_eq!(
+            reports::get_effective_time_of_day(&screenplay, scenes[2].0),
+            None
+        );
+        assert_eq!(
+            reports::get_effective_time_of_day(&screenplay, scenes[5].0),
+            Some(&night)
+        );
+        let nights = reports::get_eighths_per_time_of_day(&screenplay)
+            .unwrap()
+            .into_iter()
+            .find(|(t, _)| **t == night)
+            .unwrap();
+        let night_scenes: u64 = [0, 1, 4, 5]
+            .iter()
+            .map(|idx| scenes[*idx].1.length_eighths.unwrap())
+            .sum();
+        assert_eq!(nights.1, night_scenes);
+
+        // Breakdown sheets and strips use the effective time of day too
+        let sheets = breakdown::get_all_breakdown_sheets(&screenplay).unwrap();
+        assert_eq!(sheets[1].time_of_day, Some(night.clone()));
+        assert_eq!(sheets[2].time_of_day, None);
+        let strip_times_of_day = |stripboard: stripboard::Stripboard| -> Vec<Option<TimeOfDay>> {
+            stripboard
+                .entries
+                .into_iter()
+                .filter_map(|entry| match entry {
+                    stripboard::StripboardEntry::Strip(strip) => Some(strip.time_of_day),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(
+            strip_times_of_day(stripboard::get_stripboard(&screenplay).unwrap())[1],
+            Some(night.clone())
+        );
+        // Even without the scene before it on the stripboard
+        let yard = scenes[1].1.story_locations.last().unwrap();
+        assert_eq!(
+            strip_times_of_day(
+                stripboard::get_stripboard_for_locations(&screenplay, vec![yard]).unwrap()
+            ),
+            vec![Some(night.clone())]
+        );
+
+        // Continuity doesn't need a time of day
+        assert!(
+            !diagnostics::get_diagnostics(&screenplay)
+                .iter()
+                .any(|d| d.message == "scene heading has no time of day"
+                    && d.coordinate.line == scenes[1].1.start.line)
+        );
+    }
+
+    #[test]
+    fn story_days() {
+        use crate::screenplay_document::StoryDay;
+        use crate::story_days::StoryDayMarkers;
+        use std::collections::HashMap;
+
+        let day = |number| StoryDay {
+            number,
+            night: false,
+        };
+        let night = |number| StoryDay {
+            number,
+            night: true,
+        };
+
+        let mut screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. HOUSE - DAY\n\
+            \n\
+            Quiet.\n\
+            \n\
+            INT. HOUSE - NIGHT\n\
+            \n\
+            BOB\n\
+            Hello?\n\
+            \n\
+            EXT. YARD - CONTINUOUS\n\
+            \n\
+            EXT. YARD - DAY\n\
+            \n\
+            INT. KITCHEN - DAY\n\
+            \n\
+            THE NEXT MORNING.\n\
+            \n\
+            EXT. BEACH - DAY (FLASHBACK)\n\
+            \n\
+            INT. OFFICE - MORNING\n\
+            \n\
+            SUPER: DAY 7\n\
+            \n\
+            INT. OFFICE - AFTERNOON\n\
+            \n\
+            INT. BAR - NIGHT\n\
+            \n\
+            EXT. FARM - NIGHT - 1985\n\
+            \n\
+            A poster reads: NIGHT 1985.\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let story_days: Vec<Option<StoryDay>> = reports::get_all_scenes_ordered(&screenplay)
+            .unwrap()
+            .iter()
+            .map(|(_, scene)| scene.story_day)
+            .collect();
+        assert_eq!(
+            story_days,
+            vec![
+                Some(day(1)),
+                Some(night(1)),
+                Some(night(1)),
+                Some(day(2)),
+                Some(day(3)),
+                None,
+                Some(day(7)),
+                Some(day(7)),
+                Some(night(7)),
+                Some(night(7)),
+            ]
+        );
+        assert_eq!(night(7).get_label(), "Night 7");
+
+        let report = story_days::get_story_day_report(&screenplay).unwrap();
+        assert_eq!(report.len(), 6);
+        assert_eq!(report[1].story_day, night(1));
+        assert_eq!(report[1].scenes.len(), 2);
+        assert_eq!(report[1].locations.len(), 2);
+        assert_eq!(report[1].characters[0].name, "BOB");
+        let csv = story_days::get_story_day_csv(&screenplay, &report);
+        assert_eq!(
+            csv.lines().next().unwrap(),
+            "Story Day,Scenes,Pages,Locations,Characters"
+        );
+        assert!(csv.contains("Night 1,,"));
+        assert!(csv.contains("\"INT. HOUSE, EXT. YARD\",BOB"));
+
+        // Later scenes continue from an override
+        let afternoon = *reports::get_all_scenes_ordered(&screenplay).unwrap()[7].0;
+        screenplay.set_story_days(
+            &StoryDayMarkers::default(),
+            &HashMap::from([(afternoon, day(10))]),
+        );
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        assert_eq!(scenes[7].1.story_day, Some(day(10)));
+        assert_eq!(scenes[8].1.story_day, Some(night(10)));
+    }
+
+    #[test]
+    fn time_of_day_extras() {
+        use crate::screenplay_document::{Lighting, TimeOfDay, TimeOfDayCollection};
+        use std::collections::HashMap;
+
+        let time_of_day_strs = TimeOfDayCollection {
+            extras: Some(HashMap::from([
+                ("dusk".to_string(), "DUSK".to_string()),
+                ("dawn".to_string(), "DAWN".to_string()),
+                ("gloaming".to_string(), "GLOAMING".to_string()),
+            ])),
+            extras_lighting: Some(HashMap::from([
+                ("dusk".to_string(), Lighting::Transitional),
+                ("dawn".to_string(), Lighting::Transitional),
+                ("gloaming".to_string(), Lighting::Night),
+            ])),
+            ..Default::default()
+        };
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. HOUSE - DAY\n\
+            \n\
+            EXT. FIELD - DUSK\n\
+            \n\
+            EXT. FIELD - DAWN\n\
+            \n\
+            EXT. FIELD - DUSK\n\
+            \n\
+            EXT. ROAD - GLOAMING\n\
+            \n\
+            INT. HOUSE - NIGHT\n",
+            Some(time_of_day_strs),
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        let dusk = TimeOfDay::Extra {
+            key: "dusk".into(),
+            string: "DUSK".into(),
+            lighting: Some(Lighting::Transitional),
+        };
+        assert_eq!(scenes[1].1.story_time_of_day, Some(dusk.clone()));
+        assert_ne!(scenes[1].1.story_time_of_day, scenes[2].1.story_time_of_day);
+
+        let groups = reports::get_all_scenes_grouped_by_time_of_day(&screenplay).unwrap();
+        assert_eq!(groups.len(), 5);
+        assert_eq!(*groups[1].0, dusk);
+        assert_eq!(groups[1].1.len(), 2);
+
+        let eighths = |idxs: &[usize]| -> u64 {
+            idxs.iter()
+                .map(|idx| scenes[*idx].1.length_eighths.unwrap())
+                .sum()
+        };
+        assert_eq!(
+            reports::get_eighths_per_lighting(&screenplay).unwrap(),
+            vec![
+                (Lighting::Day, eighths(&[0])),
+                (Lighting::Transitional, eighths(&[1, 2, 3])),
+                (Lighting::Night, eighths(&[4, 5])),
+            ]
+        );
+        // A night-like extra is a night for story days, too
+        assert!(scenes[4].1.story_day.unwrap().night);
+
+        // When extras overlap, the same one always wins, however the `HashMap` is ordered
+        for _ in 0..16 {
+            let overlapping = TimeOfDayCollection {
+                extras: Some(HashMap::from([
+                    ("sunset".to_string(), "DUSK".to_string()),
+                    ("dusk".to_string(), "DUSK".to_string()),
+                    ("twilight".to_string(), "Dusk.".to_string()),
+                    ("magic_hour".to_string(), "MAGIC HOUR".to_string()),
+                    ("noon".to_string(), "NOON".to_string()),
+                    ("high_noon".to_string(), "NOON".to_string()),
+                ])),
+                ..Default::default()
+            };
+            let key = |text: &str| match overlapping.get_time_of_day(&text.to_string()) {
+                Some(TimeOfDay::Extra { key, .. }) => key,
+                other => panic!("{:?} isn't an extra", other),
+            };
+            // The longest string, then the first key
+            assert_eq!(key("DUSK"), "twilight");
+            assert_eq!(key("magic hour"), "magic_hour");
+            assert_eq!(key("NOON"), "high_noon");
+        }
+    }
+
+    #[test]
+    fn multi_word_matching() {
+        use crate::screenplay_document::{
+            Continuity, Environment, EnvironmentStrings, SceneHeadingElement, TimeOfDay,
+            TimeOfDayCollection,
+        };
+        use std::collections::HashMap;
+
+        let time_of_day_strs = TimeOfDayCollection {
+            extras: Some(HashMap::from([(
+                "high_noon".to_string(),
+                "HIGH NOON".to_string(),
+            )])),
+            ..Default::default()
+        };
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "EXT. MAIN STREET - HIGH NOON\n\
+            \n\
+            Tumbleweeds.\n\
+            \n\
+            int. saloon - night.\n\
+            \n\
+            Piano.\n\
+            \n\
+            INT. / EXT. SALOON - MOMENTS LATER\n\
+            \n\
+            Doors swing.\n",
+            Some(time_of_day_strs),
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        let heading_elements = |scene_id| {
+            let lines = reports::get_all_lines_for_scene(&screenplay, scene_id).unwrap();
+            lines[0]
+                .1
+                .text_elements
+                .iter()
+                .filter(|e| e.element_type.is_some())
+                .map(|e| (e.text.clone(), e.element_type.unwrap()))
+                .collect::<Vec<(String, SPType)>>()
+        };
+
+        // Both words of the extra are one element
+        assert_eq!(
+            scenes[0].1.story_time_of_day,
+            Some(TimeOfDay::Extra {
+                key: "high_noon".into(),
+                string: "HIGH NOON".into(),
+                lighting: None,
+            })
+        );
+        assert!(heading_elements(scenes[0].0).contains(&(
+            "HIGH NOON".into(),
+            SPType::SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay)
+        )));
+
+        // Case and punctuation don't matter
+        assert_eq!(scenes[1].1.environment, Environment::Int);
+        assert!(matches!(
+            scenes[1].1.story_time_of_day,
+            Some(TimeOfDay::Night(_))
+        ));
+
+        assert_eq!(
+            scenes[2].1.environment,
+            Environment::Combo(Some(vec![Environment::Int, Environment::Ext]))
+        );
+        let elements = heading_elements(scenes[2].0);
+        assert_eq!(
+            elements[0],
+            (
+                "INT. / EXT.".into(),
+                SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment)
+            )
+        );
+        assert!(elements.contains(&(
+            "MOMENTS LATER".into(),
+            SPType::SP_SCENE_HEADING(SceneHeadingElement::Continuity)
+        )));
+        assert!(matches!(scenes[2].1.continuity, Some(Continuity::Later(_))));
+
+        // A multi-word environment in a PDF, where its first word alone isn't an environment
+        let env_strs = EnvironmentStrings {
+            int: vec!["EN INTERIOR".into()],
+            ..Default::default()
+        };
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut page = pdf_document::Page::default();
+        page.lines.push(get_scene_heading_line(
+            "EN",
+            "INTERIOR HOUSE - DAY",
+            "1",
+            &indentations,
+        ));
+        mock_pdf.pages.push(page);
+        let parsed_doc = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(pdf_document::ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            Some(env_strs),
+            None,
+        )
+        .unwrap();
+        let scenes = reports::get_all_scenes_ordered(&parsed_doc).unwrap();
+        assert_eq!(scenes[0].1.environment, Environment::Int);
+        assert_eq!(
+            reports::get_full_string_for_scene_locations(&parsed_doc, scenes[0].1),
+            Some("EN INTERIOR HOUSE".into())
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        use crate::error::ParseError;
+
+        let empty_pdf = PDFDocument::default();
+        assert!(matches!(
+            pdf_parser::get_screenplay_doc_from_pdf_obj(empty_pdf, None, None, None, None, None),
+            Err(ParseError::EmptyDocument)
+        ));
+
+        let mut blank_pdf = PDFDocument::default();
+        blank_pdf.pages.push(pdf_document::Page::default());
+        assert!(matches!(
+            pdf_parser::get_screenplay_doc_from_pdf_obj(blank_pdf, None, None, None, None, None),
+            Err(ParseError::NoContentPages)
+        ));
+
+        assert!(matches!(
+            fountain_parser::get_screenplay_doc_from_fountain_str("  \n\n", None, None),
+            Err(ParseError::EmptyDocument)
+        ));
+        assert!(matches!(
+            fountain_parser::get_screenplay_doc_from_fountain_filepath(
+                "test_data/does_not_exist.fountain".into(),
+                None,
+                None
+            ),
+            Err(ParseError::Io(_))
+        ));
+
+        let invalid_fdx = fdx_parser::get_screenplay_doc_from_fdx_str("<FinalDraft>", None, None);
+        assert!(matches!(invalid_fdx, Err(ParseError::InvalidDocument(_))));
+        assert!(matches!(
+            fdx_parser::get_screenplay_doc_from_fdx_str("<Document/>", None, None),
+            Err(ParseError::InvalidDocument(_))
+        ));
+
+        // Errors can be sent across threads, i.e. into `anyhow` or an async task
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<ParseError>();
+    }
+
+    #[test]
+    fn parse_diagnostics() {
+        use crate::diagnostics::Severity;
+        use crate::pdf_document::ElementIndentationsInches;
+        use crate::screenplay_document::ScreenplayCoordinate;
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut page = pdf_document::Page::default();
+        let mut mismatched_heading =
+            get_scene_heading_line("INT.", "HOUSE - DAY", "2", &indentations);
+        mismatched_heading.words.insert(
+            0,
+            _create_pdfword("1".to_string(), indentations.left - 36.0, None),
+        );
+        page.lines.push(mismatched_heading);
+        // Half a character off of the Action indentation
+        page.lines.push(_create_pdfline_with_word(
+            "Bob".to_string(),
+            indentations.action + 3.6,
+            Some(2.5),
+        ));
+        let mut no_time_of_day_heading =
+            get_scene_heading_line("EXT.", "GARDEN", "3", &indentations);
+        for word in &mut no_time_of_day_heading.words {
+            word.position.y = 2.0 * 72.0;
+        }
+        page.lines.push(no_time_of_day_heading);
+        mock_pdf.pages.push(page);
+
+        let (parsed_doc, diagnostics) =
+            pdf_parser::get_screenplay_doc_and_diagnostics_from_pdf_obj(
+                mock_pdf,
+                Some(ElementIndentationsInches::us_letter_default()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(diagnostics.len(), 3);
+
+        let scene_number_mismatch = &diagnostics[1];
+        assert_eq!(
+            scene_number_mismatch.coordinate,
+            ScreenplayCoordinate {
+                page: 0,
+                line: 0,
+                element: Some(5),
+            }
+        );
+        assert!(scene_number_mismatch.message.contains("'1' and '2'"));
+
+        let misplaced_word = &diagnostics[0];
+        assert_eq!(misplaced_word.severity, Severity::Warning);
+        assert_eq!(
+            misplaced_word.coordinate,
+            ScreenplayCoordinate {
+                page: 0,
+                line: 1,
+                element: Some(0),
+            }
+        );
+        assert!(misplaced_word.message.contains("Action"));
+        assert!((misplaced_word.confidence - 0.75).abs() < 0.001);
+
+        let no_time_of_day = &diagnostics[2];
+        assert_eq!(no_time_of_day.coordinate.line, 2);
+        assert_eq!(no_time_of_day.coordinate.element, None);
+
+        // The same checks work on a document from any parser
+        assert_eq!(diagnostics::get_diagnostics(&parsed_doc).len(), 3);
+    }
+
+    #[test]
+    fn scene_eighths() {
+        use crate::pdf_document::ElementIndentationsInches;
+        use crate::reports;
+        use crate::screenplay_document::{ScreenplayCoordinate, TimeOfDay};
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        // The US-Letter content zone is 648 points tall, so an eighth is 81 points
+        let create_heading_line = |text: &str, scn_num: &str, y_pts: f64| {
+            let mut line = get_scene_heading_line("INT.", text, scn_num, &indentations);
+            for word in &mut line.words {
+                word.position.y = y_pts;
+            }
+            line
+        };
+
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut first_page = pdf_document::Page::default();
+        first_page
+            .lines
+            .push(create_heading_line("HOUSE - KITCHEN - DAY", "1", 711.0));
+        first_page.lines.push(_create_pdfline_with_word(
+            "BOB".to_string(),
+            indentations.character,
+            Some(9.0),
+        ));
+        first_page.lines.push(_create_pdfline_with_word(
+            "Morning.".to_string(),
+            indentations.dialogue,
+            Some(9.0 - 12.0 / 72.0),
+        ));
+        // 3 eighths below the first heading
+        first_page
+            .lines
+            .push(create_heading_line("HOUSE - GARAGE - NIGHT", "2", 468.0));
+        first_page.lines.push(_create_pdfline_with_word(
+            "ALICE".to_string(),
+            indentations.character,
+            Some(6.0),
+        ));
+        first_page.lines.push(_create_pdfline_with_word(
+            "Evening.".to_string(),
+            indentations.dialogue,
+            Some(6.0 - 12.0 / 72.0),
+        ));
+        mock_pdf.pages.push(first_page);
+        let mut second_page = pdf_document::Page::default();
+        second_page.lines.push(_create_pdfline_with_word(
+            "BOB".to_string(),
+            indentations.character,
+            Some(9.875),
+        ));
+        // 150 points below the top of the content zone
+        second_page.lines.push(_create_pdfline_with_word(
+            "Goodnight.".to_string(),
+            indentations.dialogue,
+            Some(570.0 / 72.0),
+        ));
+        mock_pdf.pages.push(second_page);
+
+        let screenplay = pdf_parser::get_screenplay_doc_from_pdf_obj(
+            mock_pdf,
+            Some(ElementIndentationsInches::us_letter_default()),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let scenes = reports::get_all_scenes_ordered(&screenplay).unwrap();
+        assert_eq!(scenes[0].1.length_eighths, Some(3));
+        assert_eq!(
+            scenes[0].1.end,
+            Some(ScreenplayCoordinate {
+                page: 0,
+                line: 2,
+                element: None
+            })
+        );
+        // The rest of the first page (396 points), then 150 + 12 points of the second page
+        assert_eq!(scenes[1].1.length_eighths, Some(7));
+        assert_eq!(
+            scenes[1].1.end,
+            Some(ScreenplayCoordinate {
+                page: 1,
+                line: 1,
+                element: None
+            })
+        );
+
+        let per_location: Vec<(String, u64)> = reports::get_eighths_per_location(&screenplay)
+            .unwrap()
+            .into_iter()
+            .map(|(id, eighths)| {
+                (
+                    reports::get_full_string_for_location_path(&screenplay, id).unwrap(),
+                    eighths,
+                )
+            })
+            .collect();
+        assert_eq!(
+            per_location,
+            vec![
+                ("INT. HOUSE".to_string(), 10),
+                ("INT. HOUSE - GARAGE".to_string(), 7),
+                ("INT. HOUSE - KITCHEN".to_string(), 3),
+            ]
+        );
+
+        let per_character: Vec<(&str, u64)> =
+            reports::get_eighths_per_character_speaking(&screenplay)
+                .unwrap()
+                .into_iter()
+                .map(|(c, eighths)| (c.name.as_str(), eighths))
+                .collect();
+        assert_eq!(per_character, vec![("ALICE", 7), ("BOB", 10)]);
+
+        let per_time_of_day = reports::get_eighths_per_time_of_day(&screenplay).unwrap();
+        assert_eq!(
+            per_time_of_day,
+            vec![
+                (&TimeOfDay::Day("DAY".into()), 3),
+                (&TimeOfDay::Night("NIGHT".into()), 7)
+            ]
+        );
+        assert_eq!(reports::format_eighths(10), "1 2/8");
+
+        // Plain-text formats count lines instead
+        let fountain = fountain_parser::get_screenplay_doc_from_fountain_filepath(
+            "test_data/FountainTest.fountain".into(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(
+            fountain
+                .scenes
+                .values()
+                .all(|s| s.end.is_some() && s.length_eighths.is_some_and(|e| e >= 1))
+        );
+    }
+
+    #[test]
+    fn breakdown_sheets() {
+        use crate::screenplay_document::{Environment, TimeOfDay};
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. DINER - NIGHT #1#\n\
+            \n\
+            BOB slides a BRIEFCASE across the table to two WAITERS.\n\
+            \n\
+            ALICE\n\
+            Is that it?\n\
+            \n\
+            EXT. PARKING LOT - NIGHT #2#\n\
+            \n\
+            ALICE opens the BRIEFCASE. A REVOLVER, wrapped in an \"OLD RAG\".\n",
+            None,
+            None,
+        )
+        .unwrap();
+
+        let sheets = breakdown::get_all_breakdown_sheets(&screenplay).unwrap();
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].number, Some("1".into()));
+        assert_eq!(sheets[0].heading, "INT. DINER - NIGHT");
+        assert_eq!(sheets[0].environment, Environment::Int);
+        assert_eq!(sheets[0].location, Some("INT. DINER".into()));
+        assert_eq!(
+            sheets[0].time_of_day,
+            Some(TimeOfDay::Night("NIGHT".into()))
+        );
+        assert_eq!(sheets[0].cast, vec!["ALICE".to_string()]);
+        // Bob never speaks, so he isn't cast
+        assert_eq!(
+            sheets[0].props_and_extras,
+            vec!["BOB", "BRIEFCASE", "WAITERS"]
+        );
+        assert!(sheets[0].length_eighths.is_some());
+        // The briefcase was already introduced
+        assert_eq!(sheets[1].props_and_extras, vec!["REVOLVER", "OLD RAG"]);
+        assert_eq!(
+            breakdown::get_breakdown_sheet(&screenplay, &sheets[1].scene_id),
+            Some(sheets[1].clone())
+        );
+
+        let csv = breakdown::get_breakdown_csv(&sheets);
+        let mut csv_lines = csv.lines();
+        assert_eq!(
+            csv_lines.next(),
+            Some("Scene,Heading,INT/EXT,Location,Time of Day,Pages,Cast,Props/Extras")
+        );
+        assert_eq!(
+            csv_lines.next(),
+            Some("1,INT. DINER - NIGHT,INT,INT. DINER,NIGHT,1/8,ALICE,BOB; BRIEFCASE; WAITERS")
+        );
+
+        let json = breakdown::get_breakdown_json(&sheets).unwrap();
+        let parsed: Vec<breakdown::BreakdownSheet> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, sheets);
+    }
+
+    #[test]
+    fn screenplay_diff() {
+        use crate::diff::WordChange;
+
+        let old_draft = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. DINER - NIGHT #1#\n\
+            \n\
+            ALICE\n\
+            Is that it?\n\
+            \n\
+            INT. KITCHEN - NIGHT #5#\n\
+            \n\
+            Eggs sizzle.\n\
+            \n\
+            EXT. PARKING LOT - NIGHT #2#\n\
+            \n\
+            BOB\n\
+            Nobody followed us.\n\
+            \n\
+            INT. CAR - NIGHT #3#\n\
+            \n\
+            They drive.\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let mut new_draft = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. CAR - NIGHT #3#\n\
+            \n\
+            They drive.\n\
+            \n\
+            INT. DINER - NIGHT #1#\n\
+            \n\
+            ALICE\n\
+            Is that really it?\n\
+            \n\
+            INT. KITCHEN - NIGHT #5#\n\
+            \n\
+            Eggs sizzle.\n\
+            \n\
+            INT. MOTEL - NIGHT #4#\n\
+            \n\
+            CAROL\n\
+            You're late.\n",
+            None,
+            None,
+        )
+        .unwrap();
+        // The writer marked the dialogue change, but not the new scene
+        let dialogue = new_draft
+            .pages
+            .iter_mut()
+            .flat_map(|p| p.lines.iter_mut())
+            .find(|l| l.text_elements.iter().any(|e| e.text.contains("really")))
+            .unwrap();
+        dialogue.revised = true;
+
+        let diff = diff::get_screenplay_diff(&old_draft, &new_draft);
+        let scenes: Vec<(Option<&str>, bool, bool, bool, bool)> = diff
+            .scenes
+            .iter()
+            .map(|s| {
+                (
+                    s.number.as_deref(),
+                    s.is_added(),
+                    s.is_omitted(),
+                    s.moved,
+                    s.is_changed(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            scenes,
+            vec![
+                (Some("3"), false, false, true, false),
+                (Some("1"), false, false, false, true),
+                (Some("5"), false, false, false, false),
+                (Some("2"), false, true, false, true),
+                (Some("4"), true, false, false, true),
+            ]
+        );
+
+        let changed_line = &diff.scenes[1].line_changes[0];
+        assert!(changed_line.old.is_some() && changed_line.new.is_some());
+        assert_eq!(
+            changed_line.words,
+            vec![
+                WordChange::Same("Is".into()),
+                WordChange::Same("that".into()),
+                WordChange::Added("really".into()),
+                WordChange::Same("it?".into()),
+            ]
+        );
+
+        assert_eq!(diff.added_characters, vec!["CAROL".to_string()]);
+        assert_eq!(diff.removed_characters, vec!["BOB".to_string()]);
+        assert_eq!(diff.page_count_delta(), 0);
+        // The heading, character and dialogue of the new scene
+        assert_eq!(diff.unmarked_changes.len(), 3);
+
+        // A scene replaced with an OMITTED placeholder is omitted, in its place
+        let omitted_draft = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. DINER - NIGHT #1#\n\
+            \n\
+            ALICE\n\
+            Is that it?\n\
+            \n\
+            .OMITTED #5#\n\
+            \n\
+            EXT. PARKING LOT - NIGHT #2#\n\
+            \n\
+            BOB\n\
+            Nobody followed us.\n\
+            \n\
+            INT. CAR - NIGHT #3#\n\
+            \n\
+            They drive.\n",
+            None,
+            None,
+        )
+        .unwrap();
+        let diff = diff::get_screenplay_diff(&old_draft, &omitted_draft);
+        assert_eq!(diff.scenes.len(), 4);
+        let omitted = &diff.scenes[1];
+        assert_eq!(omitted.number.as_deref(), Some("5"));
+        assert!(omitted.is_omitted() && !omitted.is_added() && !omitted.moved);
+        assert!(omitted.new_scene.is_some());
+        assert!(omitted.heading.starts_with("INT. KITCHEN"));
+        // The heading and the action
+        assert_eq!(omitted.line_changes.len(), 2);
+        assert!(omitted.line_changes.iter().all(|c| c.new.is_none()));
+        assert!(
+            !diff
+                .scenes
+                .iter()
+                .any(|s| s.is_omitted() && s.number.as_deref() != Some("5"))
+        );
+        assert!(diff.unmarked_changes.is_empty());
+    }
+
+    #[test]
+    fn stripboard() {
+        use crate::stripboard::StripboardEntry;
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. DINER - NIGHT #1#\n\
+            \n\
+            ALICE\n\
+            Is that it?\n\
+            \n\
+            BOB\n\
+            That's it.\n\
+            \n\
+            EXT. PARKING LOT - NIGHT #2#\n\
+            \n\
+            ALICE\n\
+            Nobody followed us.\n\
+            \n\
+            INT. DINER - DAY #3#\n\
+            \n\
+            ALICE\n\
+            One more coffee.\n",
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut board = stripboard::get_stripboard(&screenplay).unwrap();
+        let cast: Vec<&str> = board.cast.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(cast, vec!["ALICE", "BOB"]);
+        let strips = board.get_strips();
+        assert_eq!(strips.len(), 3);
+        assert_eq!(strips[0].cast_ids, vec![1, 2]);
+        assert_eq!(strips[1].location, Some("EXT. PARKING LOT".into()));
+        assert_eq!(board.get_cast_member(2).unwrap().name, "BOB");
+
+        // Shoot the diner first, day before night
+        board.group_by_location_and_time_of_day();
+        board.move_entry(1, 0);
+        board.insert_day_break(2);
+        let numbers: Vec<Option<String>> = board
+            .entries
+            .iter()
+            .map(|e| match e {
+                StripboardEntry::Strip(s) => s.number.clone(),
+                StripboardEntry::DayBreak => None,
+            })
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![Some("3".into()), Some("1".into()), None, Some("2".into())]
+        );
+        assert_eq!(board.get_shoot_days().len(), 2);
+        assert!(
+            day_out_of_days::get_day_out_of_days_for_shoot_days(
+                &screenplay,
+                board.get_shoot_days()
+            )
+            .is_some()
+        );
+
+        let csv = stripboard::get_stripboard_csv(&board);
+        let mut csv_lines = csv.lines();
+        assert_eq!(
+            csv_lines.next(),
+            Some("Day,Scene,INT/EXT,Location,Time of Day,Pages,Cast")
+        );
+        assert_eq!(csv_lines.next(), Some("1,3,INT,INT. DINER,DAY,1/8,1"));
+        assert_eq!(
+            csv_lines.next(),
+            Some("1,1,INT,INT. DINER,NIGHT,1/8,\"1, 2\"")
+        );
+        assert_eq!(
+            csv_lines.next(),
+            Some("2,2,EXT,EXT. PARKING LOT,NIGHT,1/8,1")
+        );
+
+        let text = stripboard::get_stripboard_text(&board);
+        assert!(text.contains("--- END OF DAY 1 -- 2/8 pages ---\n"));
+        assert!(text.ends_with("CAST\n1. ALICE\n2. BOB\n"));
+
+        let parking_lot = &screenplay
+            .scenes
+            .values()
+            .find(|s| s.environment == crate::screenplay_document::Environment::Ext)
+            .unwrap()
+            .story_locations[0];
+        let board =
+            stripboard::get_stripboard_for_locations(&screenplay, vec![parking_lot]).unwrap();
+        assert_eq!(board.get_strips().len(), 1);
+        assert_eq!(board.cast.len(), 1);
+    }
+
+    #[test]
+    fn day_out_of_days() {
+        use crate::day_out_of_days::DoodCode;
+
+        let screenplay = fountain_parser::get_screenplay_doc_from_fountain_str(
+            "INT. DINER - NIGHT #1#\n\
+            \n\
+            ALICE\n\
+            Is that it?\n\
+            \n\
+            BOB\n\
+            That's it.\n\
+            \n\
+            EXT. PARKING LOT - NIGHT #2#\n\
+            \n\
+            ALICE\n\
+            Nobody followed us.\n\
+            \n\
+            INT. CAR - NIGHT #3#\n\
+            \n\
+            CAROL\n\
+            Get in.\n\
+            \n\
+            INT. DINER - DAY #4#\n\
+            \n\
+            ALICE\n\
+            One more coffee.\n",
+            None,
+            None,
+        )
+        .unwrap();
+
+        let dood = day_out_of_days::get_day_out_of_days(&screenplay, None).unwrap();
+        assert_eq!(dood.shoot_days.len(), 4);
+        let codes: Vec<(&str, Vec<Option<DoodCode>>)> = dood
+            .rows
+            .iter()
+            .map(|r| (r.character.name.as_str(), r.days.clone()))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (
+                    "ALICE",
+                    vec![
+                        Some(DoodCode::StartWork),
+                        Some(DoodCode::Work),
+                        Some(DoodCode::Hold),
+                        Some(DoodCode::WorkFinish)
+                    ]
+                ),
+                (
+                    "BOB",
+                    vec![Some(DoodCode::StartWorkFinish), None, None, None]
+                ),
+                (
+                    "CAROL",
+                    vec![None, None, Some(DoodCode::StartWorkFinish), None]
+                ),
+            ]
+        );
+
+        // Shoot both diner scenes first
+        let scenes: Vec<_> = reports::get_all_scenes_ordered(&screenplay)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| *id)
+            .collect();
+        let dood = day_out_of_days::get_day_out_of_days_for_shoot_days(
+            &screenplay,
+            vec![vec![scenes[0], scenes[3]], vec![scenes[1], scenes[2]]],
+        )
+        .unwrap();
+        let csv = day_out_of_days::get_day_out_of_days_csv(&dood);
+        let mut csv_lines = csv.lines();
+        assert_eq!(
+            csv_lines.next(),
+            Some("Cast,Day 1,Day 2,Work,Hold,Total,Start,Finish")
+        );
+        assert_eq!(csv_lines.next(), Some("ALICE,SW,WF,2,0,2,1,2"));
+        assert_eq!(csv_lines.next(), Some("BOB,SWF,,1,0,1,1,1"));
+        assert_eq!(csv_lines.next(), Some("CAROL,,SWF,1,0,1,2,2"));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        use crate::pdf_document::ElementIndentationsInches;
+        use crate::screenplay_document::{SceneHeadingElement, ScreenplayDocument};
+
+        assert_eq!(
+            serde_json::to_string(&SPType::SP_SCENE_HEADING(SceneHeadingElement::Location))
+                .unwrap(),
+            r#"{"SP_SCENE_HEADING":"Location"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&SPType::SP_ACTION).unwrap(),
+            r#""SP_ACTION""#
+        );
+
+        let indentations = ElementIndentationsPoints::us_letter_default(&None);
+        let mut mock_pdf: pdf_document::PDFDocument = PDFDocument::default();
+        let mut page = pdf_document::Page::default();
+        page.lines.push(get_scene_heading_line(
+            "INT.",
+            "HOUSE - KITCHEN - NIGHT",
+            "1",
+            &indentations,
+        ));
+        page.lines.push(_create_pdfline_with_word(
+            "BOB".to_string(),
+            indentations.character,
+            Some(2.5),
+        ));
+        page.lines.push(_create_pdfline_with_word(
+            "Hello.".to_string(),
+            indentations.dialogue,
+            Some(2.33),
+        ));
+        mock_pdf.pages.push(page);
+
+        let screenplays: Vec<ScreenplayDocument> = vec![
+            pdf_parser::get_screenplay_doc_from_pdf_obj(
+                mock_pdf,
+                Some(ElementIndentationsInches::us_letter_default()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+            fountain_parser::get_screenplay_doc_from_fountain_filepath(
+                "test_data/FountainTest.fountain".into(),
+                None,
+                None,
+            )
+            .unwrap(),
+            fdx_parser::get_screenplay_doc_from_fdx_filepath(
+                "test_data/FDXTest.fdx".into(),
+                None,
+                None,
+            )
+            .unwrap(),
+        ];
+        for screenplay in screenplays {
+            let json = screenplay.to_json().unwrap();
+            assert_eq!(ScreenplayDocument::from_json(&json).unwrap(), screenplay);
+        }
+    }
+
+    #[test]
+    fn fdx_writing() {
+        use crate::reports;
+
+        let original = fdx_parser::get_screenplay_doc_from_fdx_filepath(
+            "test_data/FDXTest.fdx".into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let fdx = fdx_writer::get_fdx_str_from_screenplay_doc(&original, None, None);
+        assert!(fdx.contains("<Paragraph Number=\"2A\" Type=\"Scene Heading\">"));
+        assert!(fdx.contains("<Text RevisionID=\"1\">Is anyone home?</Text>"));
+        assert!(fdx.contains("<DualDialogue>"));
+        assert!(fdx.contains("<Character>ALICE</Character>"));
+        assert!(fdx.contains("<Location>HOUSE - KITCHEN</Location>"));
+        assert!(fdx.contains("<Location>BASEBALL FIELD - PITCHER&apos;S MOUND</Location>"));
+        assert!(fdx.contains("<TimeOfDay>NIGHT</TimeOfDay>"));
+
+        // Writing and re-parsing should give back the same script
+        let reparsed = fdx_parser::get_screenplay_doc_from_fdx_str(&fdx, None, None).unwrap();
+        assert_eq!(reparsed.revisions, original.revisions);
+        assert_eq!(reparsed.characters.len(), original.characters.len());
+        assert_eq!(reparsed.pages.len(), original.pages.len());
+        for (original_page, reparsed_page) in original.pages.iter().zip(&reparsed.pages) {
+            assert_eq!(reparsed_page.page_number, original_page.page_number);
+            assert_eq!(reparsed_page.revised, original_page.revised);
+            let line_types = |page: &screenplay_document::Page| -> Vec<Option<SPType>> {
+                page.lines.iter().map(|l| l.line_type).collect()
+            };
+            assert_eq!(line_types(reparsed_page), line_types(original_page));
+        }
+        let scenes = |doc| -> Vec<(Option<screenplay_document::SceneNumber>, bool)> {
+            reports::get_all_scenes_ordered(doc)
+                .unwrap()
+                .iter()
+                .map(|(_, s)| (s.number.clone(), s.revised))
+                .collect()
+        };
+        assert_eq!(scenes(&reparsed), scenes(&original));
+    }
 }
@@ -1,31 +1,58 @@
-use std::fmt::Error;
-
+use crate::error::ParseError;
 use crate::pdf_document;
 use crate::pdf_document::ElementIndentationsInches;
 use crate::pdf_document::ElementIndentationsPoints;
+use crate::pdf_document::PageSize;
 use crate::pdf_document::TextPosition;
 use crate::pdf_parser;
 use crate::screenplay_document;
 use crate::screenplay_document::EnvironmentStrings;
 use crate::screenplay_document::TimeOfDayCollection;
+use crate::screenplay_document::TransitionStrings;
 use mupdf_basic_text_extractor;
 
-pub fn get_pdf_obj_from_filepath(path: String) -> Result<pdf_document::PDFDocument, Box<dyn std::error::Error>> {
-    use mupdf_basic_text_extractor:: {Doc, Fragment, Line, Page};
-    let doc_result: Result<Doc, Box<dyn std::error::Error>> = mupdf_basic_text_extractor::get_structured_document_from_filepath(path);
+/// Gets the size of every page, in points.
+///
+/// The text extractor doesn't keep the page bounds, so they're read from the PDF directly.
+fn get_page_sizes(path: &str) -> Result<Vec<PageSize>, ParseError> {
+    let document =
+        mupdf::Document::open(path).map_err(|e| ParseError::BackendError(Box::new(e)))?;
+    let mut page_sizes: Vec<PageSize> = Vec::new();
+    for page in document
+        .pages()
+        .map_err(|e| ParseError::BackendError(Box::new(e)))?
+    {
+        let bounds = page
+            .and_then(|p| p.bounds())
+            .map_err(|e| ParseError::BackendError(Box::new(e)))?;
+        page_sizes.push(PageSize {
+            width: bounds.width() as f64,
+            height: bounds.height() as f64,
+        });
+    }
+    Ok(page_sizes)
+}
+
+pub fn get_pdf_obj_from_filepath(path: String) -> Result<pdf_document::PDFDocument, ParseError> {
+    use mupdf_basic_text_extractor::{Doc, Fragment, Line, Page};
+    let mut page_sizes = get_page_sizes(&path)?.into_iter();
+    let doc_result: Result<Doc, Box<dyn std::error::Error>> =
+        mupdf_basic_text_extractor::get_structured_document_from_filepath(path);
 
     match doc_result {
         Err(e) => {
-            return Err(e);
-
+            // The extractor's errors aren't `Send`, so only their message is kept
+            return Err(ParseError::BackendError(e.to_string().into()));
         }
         Ok(old_pdf_doc) => {
-
             let mut new_doc = pdf_document::PDFDocument::default();
-        
+
             for page in old_pdf_doc.pages {
-                let mut new_page = pdf_document::Page::default();
-        
+                let mut new_page = pdf_document::Page {
+                    page_size: page_sizes.next().unwrap_or_default(),
+                    ..Default::default()
+                };
+
                 for line in page.lines {
                     let mut new_line = pdf_document::Line::default();
                     for frag in line.text_fragments {
@@ -35,20 +62,19 @@ pub fn get_pdf_obj_from_filepath(path: String) -> Result<pdf_document::PDFDocume
                             bbox_width: frag.bbox_width,
                             position: TextPosition {
                                 x: frag.x,
-                                y: frag.y
+                                y: frag.y,
                             },
                             font_name: frag.font_name,
                             font_size: frag.font_size,
-                            font_character_width: 7.2
+                            font_character_width: 7.2,
                         };
                         new_line.words.push(new_word);
-        
                     }
                     new_page.lines.push(new_line);
                 }
                 new_doc.pages.push(new_page);
             }
-        
+
             return Ok(new_doc);
         }
     }
@@ -60,32 +86,15 @@ pub fn get_screenplay_doc_from_filepath(
     revision_marker_opt: Option<String>,
     time_of_day_strs_opt: Option<TimeOfDayCollection>,
     env_strs_opt: Option<EnvironmentStrings>,
-) -> Result<screenplay_document::ScreenplayDocument, Box<dyn std::error::Error>>{
-    
-    let doc_result = get_pdf_obj_from_filepath(path);
-
-    match doc_result {
-        Ok(new_doc) => {
-            if let Some(parsed_screenplay) = pdf_parser::get_screenplay_doc_from_pdf_obj(
-                new_doc, 
-                element_indentations, 
-                revision_marker_opt, 
-                time_of_day_strs_opt, 
-                env_strs_opt
-            ) {
-                return Ok(parsed_screenplay);
-            }
-            Err(Box::new(Error))
-
-        }
-        Err(e) => {
-            return Err(e);
-
-        }
-    }
-
-    
-
-
-
-}
\ No newline at end of file
+    transition_strs_opt: Option<TransitionStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    let new_doc = get_pdf_obj_from_filepath(path)?;
+    pdf_parser::get_screenplay_doc_from_pdf_obj(
+        new_doc,
+        element_indentations,
+        revision_marker_opt,
+        time_of_day_strs_opt,
+        env_strs_opt,
+        transition_strs_opt,
+    )
+}
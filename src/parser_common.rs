@@ -0,0 +1,1186 @@
+//! Parsing logic shared between the PDF parser and the plain-text importers.
+//!
+//! Once a `Line` has been typed, building the `Scene`s, `LocationNode` tree
+//! and `Character`s of a `ScreenplayDocument` is the same, no matter where the line came from.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::pdf_document::ElementIndentationsPoints;
+use crate::screenplay_document::{
+    Character, CharacterID, Continuity, DraftDate, Environment, EnvironmentStrings, Line,
+    LocationID, LocationNode, Page, PageNumber, Revision, RevisionColor, RevisionColorSequence,
+    SPType, Scene, SceneHeadingElement, SceneID, SceneNumber, ScreenplayCoordinate,
+    ScreenplayDocument, TextElement, TimeOfDayCollection, TimePeriod, TitlePage,
+};
+
+/// Separates the sides of a split scene heading, i.e. `INT. CAR / EXT. ROAD - NIGHT`.
+pub(crate) const SPLIT_HEADING_SEPARATOR: &str = "/";
+
+/// Gets the type of a word within a Scene Heading, based on the type of the previous element.
+///
+/// Returns `None` if the previous element doesn't tell us anything about the current word.
+pub(crate) fn get_type_for_scene_heading_word(
+    word_text: &String,
+    previous_element: &SceneHeadingElement,
+    new_line: &Line,
+    time_of_day_strs: &TimeOfDayCollection,
+    environment_strs: &EnvironmentStrings,
+) -> Option<SPType> {
+    use SPType::SP_SCENE_HEADING;
+
+    // A `/` between locations may start the other side of a split heading, i.e. `INT. CAR / EXT. ROAD`
+    if matches!(
+        previous_element,
+        SceneHeadingElement::Location | SceneHeadingElement::SubLocation
+    ) && new_line
+        .text_elements
+        .last()
+        .is_some_and(|e| e.text == SPLIT_HEADING_SEPARATOR)
+        && get_environment(word_text, environment_strs).is_some()
+    {
+        return Some(SP_SCENE_HEADING(SceneHeadingElement::Environment));
+    }
+
+    match previous_element {
+        SceneHeadingElement::TimeOfDay => {
+            if word_text == "-" {
+                return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
+            }
+            None
+        }
+        SceneHeadingElement::Separator => {
+            let type_before_separator = match new_line
+                .text_elements
+                .len()
+                .checked_sub(2)
+                .and_then(|idx| new_line.text_elements.get(idx))
+            {
+                None => SPType::NONE,
+                Some(t) => match &t.element_type {
+                    None => SPType::NONE,
+                    Some(e) => *e,
+                },
+            };
+
+            if time_of_day_strs.is_time_of_day(word_text) {
+                return Some(SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay));
+            }
+
+            match type_before_separator {
+                SPType::NONE => None, // ? Something has gone very wrong...
+                SP_SCENE_HEADING(SceneHeadingElement::Location) => {
+                    Some(SP_SCENE_HEADING(SceneHeadingElement::SubLocation))
+                }
+                SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay)
+                | SP_SCENE_HEADING(SceneHeadingElement::SlugOther) => {
+                    Some(SP_SCENE_HEADING(SceneHeadingElement::SlugOther))
+                }
+                _ => {
+                    // Just a guess -- `diagnostics::get_diagnostics` reports these
+                    Some(SP_SCENE_HEADING(SceneHeadingElement::SlugOther))
+                }
+            }
+        }
+        SceneHeadingElement::SubLocation => {
+            if word_text == "-" {
+                return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
+            }
+            Some(SP_SCENE_HEADING(SceneHeadingElement::SubLocation))
+        }
+        SceneHeadingElement::Location => {
+            if word_text == "-" {
+                return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
+            }
+            Some(SP_SCENE_HEADING(SceneHeadingElement::Location))
+        }
+        SceneHeadingElement::Environment => Some(SP_SCENE_HEADING(SceneHeadingElement::Location)),
+        _ => None,
+    }
+}
+
+/// Adds any Characters named on this line to the document, if they don't exist yet.
+///
+/// A line may name more than one character (i.e. both sides of a dual dialogue block).
+pub(crate) fn add_characters_from_line(screenplay_doc: &mut ScreenplayDocument, line: &Line) {
+    for character_type in [
+        SPType::SP_CHARACTER,
+        SPType::SP_DD_L_CHARACTER,
+        SPType::SP_DD_R_CHARACTER,
+    ] {
+        let mut character_name = String::new();
+        for element in &line.text_elements {
+            if element.element_type == Some(character_type) {
+                if !character_name.is_empty() {
+                    character_name.push(' ');
+                }
+                character_name.push_str(&element.text);
+            }
+        }
+        if character_name.is_empty() {
+            continue;
+        }
+
+        let character_exists_in_doc = screenplay_doc
+            .characters
+            .iter()
+            .any(|character| character.name == character_name);
+
+        if !character_exists_in_doc {
+            let new_character = Character {
+                name: character_name,
+                id: CharacterID::new(),
+            };
+            screenplay_doc.characters.insert(new_character);
+        }
+    }
+}
+
+/// Finds (or adds) the location path for the elements of one side of a Scene Heading,
+/// and returns the ID of its last location.
+fn get_location_for_heading_elements(
+    screenplay_doc: &mut ScreenplayDocument,
+    elements: &[TextElement],
+) -> Option<LocationID> {
+    let mut root_location_string: String = String::new();
+
+    let mut current_sub_location_string = String::new();
+    let mut full_path: Vec<String> = Vec::new();
+    let mut root_location_done = false;
+
+    for element in elements {
+        match element.element_type {
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Location))
+            | Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment)) => {
+                if !root_location_string.is_empty() {
+                    root_location_string.push(' ');
+                }
+                root_location_string.push_str(&element.text.clone());
+            }
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator)) => {
+                if root_location_done {
+                    if !current_sub_location_string.is_empty() {
+                        full_path.push(current_sub_location_string.clone());
+                        current_sub_location_string = String::new();
+                    }
+                } else {
+                    root_location_done = true;
+                    full_path.push(root_location_string.clone());
+                }
+            }
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::SubLocation)) => {
+                if !current_sub_location_string.is_empty() {
+                    current_sub_location_string.push(' ');
+                }
+                current_sub_location_string.push_str(&element.text.clone());
+            }
+            _ => {
+                if !root_location_string.is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut location_id_to_insert: Option<LocationID> = None;
+    let mut exists: bool = false;
+
+    for (existing_id, existing_root) in &screenplay_doc.locations {
+        if existing_root.superlocation.is_some() {
+            continue;
+        }
+        if root_location_string == existing_root.string {
+            location_id_to_insert = Some(existing_id.clone());
+            exists = true;
+        }
+    }
+
+    if !exists {
+        let new_root_id = LocationID::new();
+
+        let new_root_location: LocationNode = LocationNode {
+            string: root_location_string.clone(),
+            sublocations: HashSet::new(),
+            superlocation: None,
+        };
+        screenplay_doc
+            .locations
+            .insert(new_root_id.clone(), new_root_location);
+        location_id_to_insert = Some(new_root_id);
+    }
+
+    // Subpath parsing and insertion
+
+    if let Some((id, s_path)) = crate::reports::location_path_exists(screenplay_doc, &full_path) {
+        let mut current_id = id.clone();
+        location_id_to_insert = Some(id.clone());
+
+        for pathstring in s_path {
+            if let Some(location) = &mut screenplay_doc.locations.get_mut(&current_id) {
+                let new_id = LocationID::new();
+
+                let new_location = LocationNode {
+                    string: pathstring.clone(),
+                    sublocations: HashSet::new(),
+                    superlocation: Some(current_id.clone()),
+                };
+                current_id = new_id.clone();
+                location_id_to_insert = Some(new_id.clone());
+                location.add_sublocation(new_id.clone());
+                screenplay_doc
+                    .locations
+                    .insert(new_id.clone(), new_location);
+            }
+        }
+    }
+    location_id_to_insert
+}
+
+/// Merges every part of a Scene Heading which is a `Continuity` or `TimePeriod` into one
+/// element of that type, and returns the first of each.
+///
+/// Each part between separators (or in parentheses) is matched as a whole, so `MOMENTS LATER`
+/// is one `Continuity`. Parts with the Environment, Location or Time of Day are left alone.
+fn type_continuity_and_time_periods(
+    new_line: &mut Line,
+    time_of_day_strs: &TimeOfDayCollection,
+) -> (Option<Continuity>, Option<TimePeriod>) {
+    let mut parts: Vec<Vec<usize>> = vec![Vec::new()];
+    for (idx, element) in new_line.text_elements.iter().enumerate() {
+        match element.element_type {
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator)) => {
+                parts.push(Vec::new());
+                continue;
+            }
+            _ if element.text.starts_with('(') => parts.push(Vec::new()),
+            _ => {}
+        }
+        if let Some(part) = parts.last_mut() {
+            part.push(idx);
+        }
+        if element.text.ends_with(')') {
+            parts.push(Vec::new());
+        }
+    }
+
+    let mut continuity: Option<Continuity> = None;
+    let mut time_period: Option<TimePeriod> = None;
+    let mut merges: Vec<(usize, usize, SceneHeadingElement)> = Vec::new();
+    for part in parts.into_iter().filter(|p| !p.is_empty()) {
+        let elements: Vec<&TextElement> = part
+            .iter()
+            .map(|idx| &new_line.text_elements[*idx])
+            .collect();
+        let parenthesized = elements[0].text.starts_with('(');
+        let can_retype = elements.iter().all(|e| match e.element_type {
+            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Location)) => parenthesized,
+            Some(SPType::SP_SCENE_HEADING(
+                SceneHeadingElement::SubLocation | SceneHeadingElement::SlugOther,
+            )) => true,
+            _ => false,
+        });
+        if !can_retype {
+            continue;
+        }
+        let text = elements
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        let new_type = if let Some(found) = time_of_day_strs.continuity.get_continuity(&text) {
+            continuity = continuity.or(Some(found));
+            SceneHeadingElement::Continuity
+        } else if let Some(found) = time_of_day_strs.time_periods.get_time_period(&text) {
+            time_period = time_period.or(Some(found));
+            SceneHeadingElement::TimePeriod
+        } else {
+            continue;
+        };
+        merges.push((part[0], part.len(), new_type));
+    }
+    // Merging shifts the elements after it, so go from the end
+    for (start, count, new_type) in merges.into_iter().rev() {
+        merge_elements(new_line, start, count, SPType::SP_SCENE_HEADING(new_type));
+    }
+    (continuity, time_period)
+}
+
+/// Builds a new `Scene` from a Scene Heading line, and inserts it into the document.
+///
+/// Any locations in the heading which don't exist yet are added to the `LocationNode` tree.
+/// Each side of a split heading (`INT. CAR / EXT. ROAD`) gets its own location path,
+/// in `Scene.story_locations`. Continuity (`CONTINUOUS`) and time periods (`FLASHBACK`)
+/// are typed first, so they aren't mistaken for sub-locations.
+///
+/// The new `SceneID` is also assigned to the line.
+pub(crate) fn add_scene_from_heading_line(
+    screenplay_doc: &mut ScreenplayDocument,
+    new_line: &mut Line,
+    start: ScreenplayCoordinate,
+    environment: Environment,
+    time_of_day_strs: &TimeOfDayCollection,
+) -> SceneID {
+    let (continuity, time_period) = type_continuity_and_time_periods(new_line, time_of_day_strs);
+
+    // Location Parsing -- each side of a split heading has its own location path
+    let mut side_starts: Vec<usize> = vec![0];
+    for idx in 1..new_line.text_elements.len() {
+        if new_line.text_elements[idx].element_type
+            == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment))
+            && new_line.text_elements[idx - 1].text == SPLIT_HEADING_SEPARATOR
+        {
+            new_line.text_elements[idx - 1].element_type =
+                Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator));
+            side_starts.push(idx);
+        }
+    }
+    side_starts.push(new_line.text_elements.len());
+
+    let mut story_locations: Vec<LocationID> = Vec::new();
+    for side in side_starts.windows(2) {
+        if let Some(id) = get_location_for_heading_elements(
+            screenplay_doc,
+            &new_line.text_elements[side[0]..side[1]],
+        ) && !story_locations.contains(&id)
+        {
+            story_locations.push(id);
+        }
+    }
+
+    // Scene Insertion
+    let new_scene = Scene {
+        number: new_line
+            .scene_number
+            .as_ref()
+            .map(|num| SceneNumber(num.clone())),
+        environment,
+        start,
+        end: None,
+        length_eighths: None,
+        revised: new_line.revised,
+        omitted: false,
+        story_locations,
+        story_time_of_day: {
+            let maybe_time: Vec<TextElement> = new_line
+                .text_elements
+                .iter()
+                .filter(|el| {
+                    el.element_type
+                        == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay))
+                })
+                .cloned()
+                .collect();
+            match maybe_time.first() {
+                None => None,
+                Some(time) => time_of_day_strs.get_time_of_day(&time.text),
+            }
+        },
+        continuity,
+        time_period,
+        story_day: None,
+    };
+    let new_scene_id = SceneID::new();
+    new_line.scene_id = Some(new_scene_id);
+    screenplay_doc.scenes.insert(new_scene_id, new_scene);
+    new_scene_id
+}
+
+/// The text which replaces a scene heading when a scene is omitted from a locked script.
+const OMITTED_STRS: [&str; 3] = ["OMITTED", "OMIT", "SCENE OMITTED"];
+
+/// Checks if the content of a line (or heading) is only `OMITTED`, ignoring case and punctuation.
+pub(crate) fn is_omitted_text(text: &str) -> bool {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_uppercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+    OMITTED_STRS.contains(&words.join(" ").as_str())
+}
+
+/// Builds a new omitted `Scene` from an `SP_OMITTED` line, and inserts it into the document.
+///
+/// Omitted scenes have no location or time of day; their environment is `Ext`,
+/// like any other scene without one. The new `SceneID` is also assigned to the line.
+pub(crate) fn add_omitted_scene(
+    screenplay_doc: &mut ScreenplayDocument,
+    new_line: &mut Line,
+    start: ScreenplayCoordinate,
+) -> SceneID {
+    let new_scene = Scene {
+        number: new_line
+            .scene_number
+            .as_ref()
+            .map(|num| SceneNumber(num.clone())),
+        environment: Environment::Ext,
+        start,
+        end: None,
+        length_eighths: None,
+        revised: new_line.revised,
+        omitted: true,
+        story_locations: Vec::new(),
+        continuity: None,
+        time_period: None,
+        story_day: None,
+        story_time_of_day: None,
+    };
+    let new_scene_id = SceneID::new();
+    new_line.scene_id = Some(new_scene_id);
+    screenplay_doc.scenes.insert(new_scene_id, new_scene);
+    new_scene_id
+}
+
+/// Guesses the type of a title page line from its text, its alignment,
+/// and the lines before it.
+///
+/// Title pages are free-form, so this is only a heuristic.
+pub(crate) fn get_title_page_line_type(
+    text: &str,
+    centered: bool,
+    previous_type: Option<SPType>,
+    has_title: bool,
+) -> SPType {
+    let lowercase = text.to_lowercase();
+    if lowercase.contains("draft")
+        || lowercase.contains("revis")
+        || (!centered && get_draft_date(text).date.is_some())
+    {
+        SPType::TP_DRAFT_DATE
+    } else if !centered {
+        SPType::TP_CONTACT
+    } else if !has_title {
+        SPType::TP_TITLE
+    } else if lowercase == "by" || lowercase.ends_with(" by") {
+        SPType::TP_BYLINE
+    } else if lowercase.starts_with("based on") {
+        SPType::TP_SOURCE
+    } else if matches!(
+        previous_type,
+        Some(SPType::TP_BYLINE) | Some(SPType::TP_AUTHOR)
+    ) {
+        SPType::TP_AUTHOR
+    } else if previous_type == Some(SPType::TP_SOURCE) {
+        SPType::TP_SOURCE
+    } else {
+        SPType::SP_OTHER
+    }
+}
+
+/// Finds the date within some text, i.e. `Revised Draft - October 18, 2026`,
+/// and returns the text of the date along with it.
+///
+/// The whole text is tried first, then every shorter run of words from either end.
+fn find_date(text: &str) -> Option<(String, DateTime<Utc>)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut candidates: Vec<String> = Vec::new();
+    for len in (1..=words.len()).rev() {
+        candidates.push(words[words.len() - len..].join(" "));
+        candidates.push(words[..len].join(" "));
+    }
+    candidates.iter().find_map(|candidate| {
+        let candidate = candidate.trim_matches(|c: char| !c.is_alphanumeric());
+        // Plain numbers would be read as timestamps
+        if candidate.is_empty() || candidate.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        // Dates without a time are given the current time, so only the day is kept
+        dateparser::parse_with_timezone(candidate, &chrono::Utc)
+            .ok()
+            .map(|date| {
+                (
+                    candidate.to_string(),
+                    date.date_naive().and_time(NaiveTime::MIN).and_utc(),
+                )
+            })
+    })
+}
+
+/// Finds the date within a draft date line, i.e. `Revised Draft - October 18, 2026`.
+pub(crate) fn get_draft_date(text: &str) -> DraftDate {
+    DraftDate {
+        text: text.to_string(),
+        date: find_date(text).map(|(_, date)| date),
+    }
+}
+
+/// Gets the text of the date within a revision label, i.e. `03/14/26` in `Blue Rev. 03/14/26`.
+pub(crate) fn get_revision_date_text(label: &str) -> Option<String> {
+    find_date(label).map(|(text, _)| text)
+}
+
+/// Checks if text reads like a revision label, i.e. it names a revision color or has a date.
+pub(crate) fn is_revision_label_text(text: &str, color_sequence: &RevisionColorSequence) -> bool {
+    color_sequence.get_color_for_str(text).is_some() || find_date(text).is_some()
+}
+
+/// Parses a revision label, i.e. `Blue Rev. 03/14/26`, for its color and date.
+pub(crate) fn get_revision(label: &str, color_sequence: &RevisionColorSequence) -> Revision {
+    Revision {
+        color: color_sequence
+            .get_color_for_str(label)
+            .unwrap_or(RevisionColor::White),
+        date: find_date(label).map(|(_, date)| date),
+        label: label.to_string(),
+    }
+}
+
+/// Builds the `TitlePage` from the typed lines of a title page.
+///
+/// Returns `None` if the page has no title page lines.
+pub(crate) fn get_title_page(page: &Page) -> Option<TitlePage> {
+    let mut title_page = TitlePage::default();
+    let mut has_title_page_lines = false;
+
+    let join = |existing: &mut Option<String>, text: String| match existing {
+        Some(existing) => {
+            existing.push(' ');
+            existing.push_str(&text);
+        }
+        None => *existing = Some(text),
+    };
+
+    for line in &page.lines {
+        let text: Vec<&str> = line
+            .text_elements
+            .iter()
+            .filter(|e| e.element_type == line.line_type)
+            .map(|e| e.text.as_str())
+            .collect();
+        let text = text.join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        match line.line_type {
+            Some(SPType::TP_TITLE) => join(&mut title_page.title, text),
+            Some(SPType::TP_BYLINE) => join(&mut title_page.credit, text),
+            Some(SPType::TP_AUTHOR) => title_page.authors.push(text),
+            Some(SPType::TP_SOURCE) => join(&mut title_page.source, text),
+            Some(SPType::TP_DRAFT_DATE) => title_page.draft_dates.push(get_draft_date(&text)),
+            Some(SPType::TP_CONTACT) => title_page.contact.push(text),
+            _ => continue,
+        }
+        has_title_page_lines = true;
+    }
+
+    if !has_title_page_lines {
+        return None;
+    }
+    Some(title_page)
+}
+
+/// Which column of a dialogue block a line belongs to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum DialogueSide {
+    Single,
+    Left,
+    Right,
+}
+impl DialogueSide {
+    pub(crate) fn character_type(&self) -> SPType {
+        match self {
+            DialogueSide::Single => SPType::SP_CHARACTER,
+            DialogueSide::Left => SPType::SP_DD_L_CHARACTER,
+            DialogueSide::Right => SPType::SP_DD_R_CHARACTER,
+        }
+    }
+    pub(crate) fn extension_type(&self) -> SPType {
+        match self {
+            DialogueSide::Single => SPType::SP_CHARACTER_EXTENSION,
+            DialogueSide::Left => SPType::SP_DD_L_CHARACTER_EXTENSION,
+            DialogueSide::Right => SPType::SP_DD_R_CHARACTER_EXTENSION,
+        }
+    }
+    pub(crate) fn parenthetical_type(&self) -> SPType {
+        match self {
+            DialogueSide::Single => SPType::SP_PARENTHETICAL,
+            DialogueSide::Left => SPType::SP_DD_L_PARENTHETICAL,
+            DialogueSide::Right => SPType::SP_DD_R_PARENTHETICAL,
+        }
+    }
+    pub(crate) fn dialogue_type(&self) -> SPType {
+        match self {
+            DialogueSide::Single => SPType::SP_DIALOGUE,
+            DialogueSide::Left => SPType::SP_DD_L_DIALOGUE,
+            DialogueSide::Right => SPType::SP_DD_R_DIALOGUE,
+        }
+    }
+    pub(crate) fn character_line_type(&self) -> SPType {
+        match self {
+            DialogueSide::Single => SPType::SP_CHARACTER,
+            _ => SPType::SP_DUAL_CHARACTERS,
+        }
+    }
+    pub(crate) fn dialogue_line_type(&self, element_type: SPType) -> SPType {
+        match self {
+            DialogueSide::Single => element_type,
+            _ => SPType::SP_DUAL_DIALOGUES,
+        }
+    }
+}
+
+/// Splits text into whitespace-separated `TextElement`s of a single type,
+/// keeping track of the whitespace before each element.
+pub(crate) fn get_text_elements(text: &str, element_type: Option<SPType>) -> Vec<TextElement> {
+    let mut elements: Vec<TextElement> = Vec::new();
+    let mut whitespace_chars: u64 = 0;
+    for word in text.trim().split(' ') {
+        if word.is_empty() {
+            whitespace_chars += 1;
+            continue;
+        }
+        elements.push(TextElement {
+            text: word.to_string(),
+            element_type,
+            preceding_whitespace_chars: if elements.is_empty() {
+                0
+            } else {
+                whitespace_chars + 1
+            },
+            element_position: None,
+        });
+        whitespace_chars = 0;
+    }
+    elements
+}
+
+/// Word-wraps a list of elements into lines no wider than `width` characters.
+pub(crate) fn wrap_text_elements(
+    elements: Vec<TextElement>,
+    width: usize,
+) -> Vec<Vec<TextElement>> {
+    let mut wrapped: Vec<Vec<TextElement>> = Vec::new();
+    let mut current: Vec<TextElement> = Vec::new();
+    let mut current_width: usize = 0;
+
+    for mut element in elements {
+        let element_width = element.text.chars().count();
+        let whitespace = element.preceding_whitespace_chars as usize;
+        if !current.is_empty() && current_width + whitespace + element_width > width {
+            wrapped.push(current);
+            current = Vec::new();
+            current_width = 0;
+        }
+        if current.is_empty() {
+            element.preceding_whitespace_chars = 0;
+            current_width = element_width;
+        } else {
+            current_width += whitespace + element_width;
+        }
+        current.push(element);
+    }
+    if !current.is_empty() {
+        wrapped.push(current);
+    }
+    wrapped
+}
+
+/// Gets the `Environment` for the first word of a plain-text scene heading.
+///
+/// Fountain doesn't require the period after `INT` or `EXT`,
+/// so we also try matching the word with the periods added back in.
+pub(crate) fn get_environment(
+    word: &str,
+    environment_strs: &EnvironmentStrings,
+) -> Option<Environment> {
+    let upper = word.to_uppercase();
+    let mut candidates: Vec<String> = vec![word.to_string(), upper.clone()];
+    if !upper.ends_with('.') {
+        candidates.push(format!("{}.", upper));
+    }
+    if upper.contains('/') {
+        let with_periods: Vec<String> = upper
+            .trim_end_matches('.')
+            .split('/')
+            .map(|part| format!("{}.", part.trim_end_matches('.')))
+            .collect();
+        candidates.push(with_periods.join("/"));
+    }
+    for candidate in &candidates {
+        if let Some(env) = Environment::from_str(candidate, environment_strs) {
+            return Some(env);
+        }
+    }
+    if upper.trim_end_matches('.') == "EST" {
+        return Some(Environment::Ext);
+    }
+    None
+}
+
+/// Merges `count` elements of a line, starting at `start`, into one element of `element_type`.
+///
+/// The merged element keeps the whitespace between the words, and the position of the first one.
+fn merge_elements(line: &mut Line, start: usize, count: usize, element_type: SPType) {
+    let mut merged: Vec<TextElement> = line.text_elements.drain(start..start + count).collect();
+    let mut element = merged.remove(0);
+    for next in merged {
+        element
+            .text
+            .push_str(&" ".repeat(next.preceding_whitespace_chars.max(1) as usize));
+        element.text.push_str(&next.text);
+    }
+    element.element_type = Some(element_type);
+    line.text_elements.insert(start, element);
+}
+
+/// Merges multi-word Environments and Times of Day in a Scene Heading into single elements,
+/// i.e. `INT. / EXT.` or `HIGH NOON`.
+///
+/// The longest match wins. Environments are matched at the start of each side of the heading,
+/// and Times of Day at the start of each part after a separator.
+pub(crate) fn merge_heading_phrases(
+    line: &mut Line,
+    time_of_day_strs: &TimeOfDayCollection,
+    environment_strs: &EnvironmentStrings,
+) {
+    let is_heading_word = |e: &TextElement| {
+        matches!(
+            e.element_type,
+            Some(SPType::SP_SCENE_HEADING(
+                SceneHeadingElement::Environment
+                    | SceneHeadingElement::Location
+                    | SceneHeadingElement::SubLocation
+                    | SceneHeadingElement::TimeOfDay
+                    | SceneHeadingElement::SlugOther
+            ))
+        )
+    };
+    let get_words = |line: &Line, start: usize| -> Vec<String> {
+        line.text_elements[start..]
+            .iter()
+            .take_while(|e| is_heading_word(e))
+            .map(|e| e.text.clone())
+            .collect()
+    };
+
+    let first_word = line.text_elements.iter().position(is_heading_word);
+    let mut idx = 0;
+    while idx < line.text_elements.len() {
+        let element_type = line.text_elements[idx].element_type;
+        let previous_type = idx
+            .checked_sub(1)
+            .and_then(|previous| line.text_elements[previous].element_type);
+        let words = get_words(line, idx);
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+
+        if Some(idx) == first_word
+            || element_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment))
+        {
+            if let Some((len, _)) = environment_strs.get_longest_match(&words) {
+                merge_elements(
+                    line,
+                    idx,
+                    len,
+                    SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment),
+                );
+            }
+        } else if previous_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator))
+            && let Some((len, _)) = time_of_day_strs.get_longest_match(&words)
+        {
+            merge_elements(
+                line,
+                idx,
+                len,
+                SPType::SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay),
+            );
+        }
+        idx += 1;
+    }
+}
+
+/// Gets the `Environment` of a typed Scene Heading line, from every `Environment` element in it.
+///
+/// A split heading (`INT. CAR / EXT. ROAD`) is a `Combo` of each side, in order.
+pub(crate) fn get_heading_environment(
+    line: &Line,
+    environment_strs: &EnvironmentStrings,
+) -> Option<Environment> {
+    let sides: Vec<Environment> = line
+        .text_elements
+        .iter()
+        .filter(|e| {
+            e.element_type == Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment))
+        })
+        .filter_map(|e| get_environment(&e.text, environment_strs))
+        .collect();
+    Environment::from_sides(sides)
+}
+
+/// Builds a fully-typed Scene Heading line from its text.
+///
+/// Returns the line, and the `Environment` of the scene (`Ext` if there isn't one).
+/// A heading which is only `OMITTED` is an `SP_OMITTED` line instead.
+pub(crate) fn get_scene_heading_line(
+    heading_text: &str,
+    scene_number: Option<String>,
+    time_of_day_strs: &TimeOfDayCollection,
+    environment_strs: &EnvironmentStrings,
+) -> (Line, Environment) {
+    let mut new_line = Line {
+        line_type: Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line)),
+        scene_number: scene_number.clone(),
+        ..Default::default()
+    };
+    if is_omitted_text(heading_text) {
+        new_line.line_type = Some(SPType::SP_OMITTED);
+        new_line.text_elements = get_text_elements(heading_text, Some(SPType::SP_OMITTED));
+    }
+
+    for mut element in get_text_elements(heading_text, None) {
+        if new_line.line_type == Some(SPType::SP_OMITTED) {
+            break;
+        }
+        let previous_type = new_line.text_elements.last().and_then(|e| e.element_type);
+        let new_type = match previous_type {
+            None => {
+                if get_environment(&element.text, environment_strs).is_some() {
+                    Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment))
+                } else {
+                    Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Location))
+                }
+            }
+            Some(SPType::SP_SCENE_HEADING(heading_element)) => get_type_for_scene_heading_word(
+                &element.text,
+                &heading_element,
+                &new_line,
+                time_of_day_strs,
+                environment_strs,
+            )
+            .or(Some(SPType::SP_SCENE_HEADING(
+                SceneHeadingElement::SlugOther,
+            ))),
+            Some(_) => Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::SlugOther)),
+        };
+        element.element_type = new_type;
+        new_line.text_elements.push(element);
+    }
+
+    if let Some(number) = scene_number {
+        new_line.text_elements.push(TextElement {
+            text: number,
+            element_type: Some(SPType::SP_SCENENUM),
+            preceding_whitespace_chars: 1,
+            element_position: None,
+        });
+    }
+
+    merge_heading_phrases(&mut new_line, time_of_day_strs, environment_strs);
+    let environment =
+        get_heading_environment(&new_line, environment_strs).unwrap_or(Environment::Ext);
+    (new_line, environment)
+}
+
+/// Builds a character cue line, with any extensions (`(V.O.)`, `(CONT'D)`) typed separately.
+pub(crate) fn get_character_line(text: &str, side: DialogueSide) -> Line {
+    let mut new_line = Line {
+        line_type: Some(side.character_line_type()),
+        ..Default::default()
+    };
+    let mut in_extension = false;
+    for mut element in get_text_elements(text, None) {
+        if element.text.starts_with('(') {
+            in_extension = true;
+        }
+        element.element_type = if in_extension {
+            Some(side.extension_type())
+        } else {
+            Some(side.character_type())
+        };
+        new_line.text_elements.push(element);
+    }
+    new_line
+}
+
+/// Word-wraps text into lines of a single type.
+pub(crate) fn get_wrapped_lines(
+    text: &str,
+    element_type: SPType,
+    line_type: SPType,
+    width: usize,
+) -> Vec<Line> {
+    wrap_text_elements(get_text_elements(text, Some(element_type)), width)
+        .into_iter()
+        .map(|text_elements| Line {
+            text_elements,
+            line_type: Some(line_type),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Lines without a position are assumed to be this tall, in points.
+const LINE_HEIGHT_PTS: f64 = 12.0;
+
+/// Lines which count towards the end of a scene.
+pub(crate) fn is_scene_content_line(line: &Line) -> bool {
+    !line.blank
+        && !line.text_elements.is_empty()
+        && !matches!(
+            line.line_type,
+            None | Some(SPType::NONE)
+                | Some(SPType::SP_PAGE_HEADER)
+                | Some(SPType::SP_FOOTER)
+                | Some(SPType::SP_MORE_CONTINUED)
+        )
+}
+
+/// How far a line is below the top of the content zone, in points.
+///
+/// Uses the line's position if it has one (i.e. from a PDF), otherwise counts the lines above it.
+fn get_line_depth(
+    page: &Page,
+    line_idx: usize,
+    content_zone: Option<&ElementIndentationsPoints>,
+) -> f64 {
+    if let Some(zone) = content_zone
+        && let Some(position) = page
+            .lines
+            .get(line_idx)
+            .and_then(|l| l.text_elements.first())
+            .and_then(|e| e.element_position)
+    {
+        return zone.top - position.y;
+    }
+    let lines_above: u64 = page
+        .lines
+        .iter()
+        .take(line_idx)
+        .map(|l| l.preceding_empty_lines + 1)
+        .sum();
+    let empty_lines = page
+        .lines
+        .get(line_idx)
+        .map_or(0, |l| l.preceding_empty_lines);
+    (lines_above + empty_lines) as f64 * LINE_HEIGHT_PTS
+}
+
+/// Sets the `end` and `length_eighths` of every scene in the document.
+///
+/// `content_zones` has the indentations of each page, for documents with positioned lines.
+/// Pages without one are `lines_per_page` lines tall.
+///
+/// A scene runs from the top of its heading to the top of the next scene's heading,
+/// or to the end of the last line, for the last scene.
+pub(crate) fn set_scene_ends_and_lengths(
+    screenplay_doc: &mut ScreenplayDocument,
+    content_zones: &[ElementIndentationsPoints],
+    lines_per_page: u64,
+) {
+    let mut starts: Vec<(SceneID, ScreenplayCoordinate)> = screenplay_doc
+        .scenes
+        .iter()
+        .map(|(id, scene)| (*id, scene.start.clone()))
+        .collect();
+    starts.sort_by_key(|(_, start)| (start.page, start.line));
+
+    for (idx, (scene_id, start)) in starts.iter().enumerate() {
+        let next_start = starts.get(idx + 1).map(|(_, next)| next);
+
+        let mut end: Option<ScreenplayCoordinate> = None;
+        let mut page_fraction: f64 = 0.0;
+        let last_page = match next_start {
+            Some(next) => next.page,
+            None => screenplay_doc.pages.len().saturating_sub(1),
+        };
+        for page_idx in start.page..=last_page {
+            let Some(page) = screenplay_doc.pages.get(page_idx) else {
+                continue;
+            };
+            let content_zone = content_zones.get(page_idx);
+            let content_height = match content_zone {
+                Some(zone) => zone.top - zone.bottom,
+                None => lines_per_page as f64 * LINE_HEIGHT_PTS,
+            };
+            if content_height <= 0.0 {
+                continue;
+            }
+
+            let first_line = if page_idx == start.page {
+                start.line
+            } else {
+                0
+            };
+            let end_line = match next_start {
+                Some(next) if next.page == page_idx => next.line,
+                _ => page.lines.len(),
+            };
+            if let Some(last_line) = (first_line..end_line)
+                .rev()
+                .find(|l| page.lines.get(*l).is_some_and(is_scene_content_line))
+            {
+                end = Some(ScreenplayCoordinate {
+                    page: page_idx,
+                    line: last_line,
+                    element: None,
+                });
+            }
+
+            let top = if page_idx == start.page {
+                get_line_depth(page, start.line, content_zone)
+            } else {
+                0.0
+            };
+            let bottom = match next_start {
+                Some(next) if next.page == page_idx => {
+                    get_line_depth(page, next.line, content_zone)
+                }
+                _ if page_idx < last_page => content_height,
+                _ => match &end {
+                    Some(end) if end.page == page_idx => {
+                        get_line_depth(page, end.line, content_zone) + LINE_HEIGHT_PTS
+                    }
+                    _ => 0.0,
+                },
+            };
+            page_fraction += (bottom - top).max(0.0) / content_height;
+        }
+
+        let Some(scene) = screenplay_doc.scenes.get_mut(scene_id) else {
+            continue;
+        };
+        scene.end = end;
+        if scene.omitted {
+            scene.length_eighths = Some(0);
+        } else {
+            scene.length_eighths = Some(((page_fraction * 8.0).round() as u64).max(1));
+        }
+    }
+}
+
+/// Keeps track of the current page while lines are added,
+/// and registers scenes and characters as their lines are added.
+///
+/// Plain-text formats have no pages of their own, so an element which doesn't fit
+/// within `lines_per_page` is moved to the next page.
+pub(crate) struct DocumentBuilder<'a> {
+    pub(crate) screenplay_doc: ScreenplayDocument,
+    pub(crate) current_page: Page,
+    pub(crate) content_pages_count: usize,
+    current_page_lines_count: u64,
+    pending_empty_lines: u64,
+    lines_per_page: u64,
+    time_of_day_strs: &'a TimeOfDayCollection,
+}
+impl<'a> DocumentBuilder<'a> {
+    pub(crate) fn new(time_of_day_strs: &'a TimeOfDayCollection, lines_per_page: u64) -> Self {
+        let mut builder = DocumentBuilder {
+            screenplay_doc: ScreenplayDocument::new(),
+            current_page: Page::default(),
+            content_pages_count: 0,
+            current_page_lines_count: 0,
+            pending_empty_lines: 0,
+            lines_per_page,
+            time_of_day_strs,
+        };
+        builder.start_new_page();
+        builder
+    }
+
+    fn start_new_page(&mut self) {
+        self.content_pages_count += 1;
+        self.current_page = Page {
+            page_number: Some(PageNumber(self.content_pages_count.to_string())),
+            ..Default::default()
+        };
+        self.current_page_lines_count = 0;
+    }
+
+    pub(crate) fn page_break(&mut self) {
+        if self.current_page.lines.is_empty() {
+            return;
+        }
+        let finished_page = std::mem::take(&mut self.current_page);
+        self.screenplay_doc.pages.push(finished_page);
+        self.start_new_page();
+        self.pending_empty_lines = 0;
+    }
+
+    /// Breaks the page if needed, so that the next line lands on the page numbered `page_number`.
+    ///
+    /// Used when the source document knows its own pagination better than our estimate does.
+    pub(crate) fn sync_to_page_number(&mut self, page_number: usize) {
+        if page_number <= self.content_pages_count {
+            return;
+        }
+        self.page_break();
+        self.content_pages_count = page_number;
+        self.current_page.page_number = Some(PageNumber(page_number.to_string()));
+    }
+
+    pub(crate) fn add_blank_lines(&mut self, count: u64) {
+        self.pending_empty_lines += count;
+    }
+
+    /// Adds the lines of a single element. The element is moved to the next page,
+    /// if it doesn't fit on the current one.
+    ///
+    /// Returns the index of the first added line on the current page.
+    pub(crate) fn add_element(
+        &mut self,
+        lines: Vec<Line>,
+        environment: Option<Environment>,
+    ) -> usize {
+        let element_height = self.pending_empty_lines + lines.len() as u64;
+        if self.current_page_lines_count + element_height > self.lines_per_page {
+            self.page_break();
+        }
+
+        let first_line_idx = self.current_page.lines.len();
+        for (idx, mut new_line) in lines.into_iter().enumerate() {
+            if idx == 0 && !self.current_page.lines.is_empty() {
+                new_line.preceding_empty_lines = self.pending_empty_lines;
+                self.current_page_lines_count += self.pending_empty_lines;
+            }
+            self.pending_empty_lines = 0;
+
+            match new_line.line_type {
+                Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line)) => {
+                    let start = ScreenplayCoordinate {
+                        page: self.screenplay_doc.pages.len(),
+                        line: self.current_page.lines.len(),
+                        element: None,
+                    };
+                    add_scene_from_heading_line(
+                        &mut self.screenplay_doc,
+                        &mut new_line,
+                        start,
+                        environment.clone().unwrap_or(Environment::Ext),
+                        self.time_of_day_strs,
+                    );
+                }
+                Some(SPType::SP_OMITTED) => {
+                    let start = ScreenplayCoordinate {
+                        page: self.screenplay_doc.pages.len(),
+                        line: self.current_page.lines.len(),
+                        element: None,
+                    };
+                    add_omitted_scene(&mut self.screenplay_doc, &mut new_line, start);
+                }
+                Some(SPType::SP_CHARACTER) | Some(SPType::SP_DUAL_CHARACTERS) => {
+                    add_characters_from_line(&mut self.screenplay_doc, &new_line);
+                }
+                _ => {}
+            }
+
+            if new_line.revised {
+                self.current_page.revised = true;
+            }
+            self.current_page.lines.push(new_line);
+            self.current_page_lines_count += 1;
+        }
+        first_line_idx
+    }
+
+    pub(crate) fn finish(mut self) -> ScreenplayDocument {
+        if !self.current_page.lines.is_empty() {
+            self.screenplay_doc.pages.push(self.current_page);
+        }
+        self.screenplay_doc.title_page = self.screenplay_doc.pages.first().and_then(get_title_page);
+        set_scene_ends_and_lengths(&mut self.screenplay_doc, &[], self.lines_per_page);
+        self.screenplay_doc
+    }
+}
@@ -1,5 +1,8 @@
+use crate::screenplay_document::PageFormat;
+use serde::{Deserialize, Serialize};
+
 // TODO: impl defaults for standard US-LETTER indentations
-#[derive(Default)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ElementIndentationsInches {
     pub pagewidth: f64,
     pub pageheight: f64,
@@ -24,11 +27,47 @@ impl ElementIndentationsInches {
             pagewidth: 8.5,
             action: 1.5,
             character: 3.7,
-            dialogue:2.5,
+            dialogue: 2.5,
+            parenthetical: 3.1,
+        }
+    }
+
+    /// The default indentations for A4 screenplays.
+    ///
+    /// Element indentations are the same as US-Letter; the page is narrower and taller,
+    /// so only the right and top margins move.
+    pub fn a4_default() -> Self {
+        ElementIndentationsInches {
+            top: 10.69,
+            bottom: 1.0,
+            left: 1.5,
+            right: 7.02,
+            pageheight: 11.69,
+            pagewidth: 8.27,
+            action: 1.5,
+            character: 3.7,
+            dialogue: 2.5,
             parenthetical: 3.1,
         }
     }
 
+    /// Gets the default indentations for a page format. `PageFormat::OTHER` uses US-Letter.
+    pub fn default_for_page_format(page_format: &PageFormat) -> Self {
+        match page_format {
+            PageFormat::A4 => Self::a4_default(),
+            PageFormat::US | PageFormat::OTHER => Self::us_letter_default(),
+        }
+    }
+
+    /// Gets the page format these indentations were made for, from the page width and height.
+    pub fn page_format(&self) -> Option<PageFormat> {
+        PageSize {
+            width: self.pagewidth * 72.0,
+            height: self.pageheight * 72.0,
+        }
+        .get_page_format()
+    }
+
     pub fn top(mut self, new_top: f64) -> Self {
         self.top = new_top;
         self
@@ -75,7 +114,10 @@ impl ElementIndentationsInches {
         self
     }
 
-    pub fn from_points(indentations: &ElementIndentationsPoints, resolution: &f64) -> ElementIndentationsInches {
+    pub fn from_points(
+        indentations: &ElementIndentationsPoints,
+        resolution: &f64,
+    ) -> ElementIndentationsInches {
         ElementIndentationsInches {
             top: indentations.top / resolution,
             bottom: indentations.bottom / resolution,
@@ -86,12 +128,12 @@ impl ElementIndentationsInches {
             action: indentations.action / resolution,
             character: indentations.character / resolution,
             dialogue: indentations.dialogue / resolution,
-            parenthetical: indentations.parenthetical / resolution
+            parenthetical: indentations.parenthetical / resolution,
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct ElementIndentationsPoints {
     pub pagewidth: f64,
     pub pageheight: f64,
@@ -106,58 +148,63 @@ pub struct ElementIndentationsPoints {
     pub parenthetical: f64,
 }
 impl ElementIndentationsPoints {
-
     /// Gets a default struct of Indentations in Points for US-Letter formatted screenplays.
-    /// 
+    ///
     /// Takes an optional resolution. `None` will use a default of 72.0 point-per-inch resolution.
     pub fn us_letter_default(resolution: &Option<f64>) -> Self {
         let mut current_resolution: f64 = 72.0;
         if let Some(r) = resolution {
-            current_resolution = r.clone()
+            current_resolution = *r
         }
 
         let indentations = ElementIndentationsInches::us_letter_default();
-        
+
         ElementIndentationsPoints {
             top: indentations.top * current_resolution,
             bottom: indentations.bottom * current_resolution,
             pagewidth: indentations.pagewidth * current_resolution,
             pageheight: indentations.pageheight * current_resolution,
             left: indentations.left * current_resolution,
-            right: indentations.right *current_resolution,
+            right: indentations.right * current_resolution,
             action: indentations.action * current_resolution,
             character: indentations.character * current_resolution,
-            dialogue: indentations.dialogue *current_resolution,
-            parenthetical: indentations.parenthetical * current_resolution
+            dialogue: indentations.dialogue * current_resolution,
+            parenthetical: indentations.parenthetical * current_resolution,
         }
     }
 
-    pub fn from_inches(indentations: &ElementIndentationsInches, resolution: &Option<f64>) -> ElementIndentationsPoints {
-        let mut current_resolution: f64 = 72.0;
-            if let Some(r) = resolution {
-                current_resolution = r.clone()
-            }
-            
-            ElementIndentationsPoints {
-                top: indentations.top * current_resolution,
-                bottom: indentations.bottom * current_resolution,
-                pagewidth: indentations.pagewidth * current_resolution,
-                pageheight: indentations.pageheight * current_resolution,
-                left: indentations.left * current_resolution,
-                right: indentations.right *current_resolution,
-                action: indentations.action * current_resolution,
-                character: indentations.character * current_resolution,
-                dialogue: indentations.dialogue *current_resolution,
-                parenthetical: indentations.parenthetical * current_resolution
-            }
+    /// Gets a default struct of Indentations in Points for A4 formatted screenplays.
+    ///
+    /// Takes an optional resolution. `None` will use a default of 72.0 point-per-inch resolution.
+    pub fn a4_default(resolution: &Option<f64>) -> Self {
+        Self::from_inches(&ElementIndentationsInches::a4_default(), resolution)
     }
-}
-
-
 
+    pub fn from_inches(
+        indentations: &ElementIndentationsInches,
+        resolution: &Option<f64>,
+    ) -> ElementIndentationsPoints {
+        let mut current_resolution: f64 = 72.0;
+        if let Some(r) = resolution {
+            current_resolution = *r
+        }
 
+        ElementIndentationsPoints {
+            top: indentations.top * current_resolution,
+            bottom: indentations.bottom * current_resolution,
+            pagewidth: indentations.pagewidth * current_resolution,
+            pageheight: indentations.pageheight * current_resolution,
+            left: indentations.left * current_resolution,
+            right: indentations.right * current_resolution,
+            action: indentations.action * current_resolution,
+            character: indentations.character * current_resolution,
+            dialogue: indentations.dialogue * current_resolution,
+            parenthetical: indentations.parenthetical * current_resolution,
+        }
+    }
+}
 
-#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TextPosition {
     pub x: f64,
     pub y: f64,
@@ -167,24 +214,54 @@ pub struct PageSize {
     pub width: f64,
     pub height: f64,
 }
+impl PageSize {
+    /// Page sizes within this many points of a standard size are that size.
+    const TOLERANCE_PTS: f64 = 4.0;
+
+    /// Classifies the page size (in points) as US-Letter, A4, or something else.
+    ///
+    /// Returns `None` if the size is unknown (i.e. zero).
+    pub fn get_page_format(&self) -> Option<PageFormat> {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return None;
+        }
+        // Landscape pages are the same format
+        let (short, long) = if self.width < self.height {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        let is_size = |width: f64, height: f64| {
+            (short - width).abs() <= Self::TOLERANCE_PTS
+                && (long - height).abs() <= Self::TOLERANCE_PTS
+        };
+        if is_size(612.0, 792.0) {
+            Some(PageFormat::US)
+        } else if is_size(595.28, 841.89) {
+            Some(PageFormat::A4)
+        } else {
+            Some(PageFormat::OTHER)
+        }
+    }
+}
 #[derive(Default, Debug)]
 pub struct Word {
     pub text: String,
     pub bbox_width: f64,
     pub bbox_height: f64,
-    pub position:TextPosition,
+    pub position: TextPosition,
     pub font_name: Option<String>,
     pub font_size: f64,
     pub font_character_width: f64,
 }
 #[derive(Default, Debug)]
 pub struct Line {
-    pub words:Vec<Word>
+    pub words: Vec<Word>,
 }
 #[derive(Default, Debug)]
 pub struct Page {
     pub lines: Vec<Line>,
-    pub page_size: PageSize
+    pub page_size: PageSize,
 }
 #[derive(Default)]
 pub struct PDFDocument {
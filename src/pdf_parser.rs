@@ -1,101 +1,193 @@
 //! This module is responsible for interpereting a (hopefully properlyformatted)
 //! PDF document into a usable, semantically-typed ScreenplayDocument structure.
 
-use core::num;
-use core::time;
 use std::collections::HashMap;
-use std::collections::HashSet;
-use std::hash::Hash;
-use std::ops::Not;
-use std::process::id;
-use std::sync::RwLockReadGuard;
-use std::thread::panicking;
-
-use uuid::Uuid;
+use std::ops::Range;
 
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::Severity;
+use crate::error::ParseError;
+use crate::parser_common;
 use crate::pdf_document;
 use crate::pdf_document::ElementIndentationsInches;
 use crate::pdf_document::ElementIndentationsPoints;
-use crate::screenplay_document::Character;
 use crate::screenplay_document::Environment;
 use crate::screenplay_document::EnvironmentStrings;
-use crate::screenplay_document::LocationID;
-use crate::screenplay_document::LocationNode;
+use crate::screenplay_document::PageFormat;
 use crate::screenplay_document::PageNumber;
+use crate::screenplay_document::RevisionColorSequence;
 use crate::screenplay_document::SPType;
+use crate::screenplay_document::TransitionStrings;
+use crate::story_days::StoryDayMarkers;
 
 use crate::screenplay_document;
-use crate::screenplay_document::Scene;
 use crate::screenplay_document::SceneHeadingElement;
-use crate::screenplay_document::SceneID;
-use crate::screenplay_document::SceneNumber;
 use crate::screenplay_document::ScreenplayCoordinate;
-use crate::screenplay_document::TextElement;
 
+mod dual_dialogue;
 pub mod indentations_deducer;
+mod title_page;
 
+/// Deduces the `ElementIndentationsInches` of a PDF document, by clustering the
+/// x-positions of the first word of each line, and the y-positions of the content lines.
+///
+/// Returns `None` if the indentations could not be deduced (i.e. the document is empty).
 pub fn deduce_indentations(
     pdfdoc: &pdf_document::PDFDocument,
 ) -> Option<ElementIndentationsInches> {
-    unimplemented!();
-    let mut x_pos_vec: Vec<f64> = Vec::default();
-
-    let mut lines_count = 0;
-    for page in &pdfdoc.pages {
-        for ln in &page.lines {
-            if let Some(word) = ln.words.first() {
-                x_pos_vec.push(word.position.x);
-                lines_count += 1;
-            }
-        }
-    }
-
-    let mut x_freq_map: HashMap<i32, i32> = HashMap::new();
+    indentations_deducer::deduce_indentations(pdfdoc)
+}
 
-    for entry in x_pos_vec {
-        let rounded = entry.round() as i32;
-        let new_ent = x_freq_map.entry(rounded).or_insert(0);
-        *new_ent += 1;
+fn _get_char_width(pdf_word: &pdf_document::Word) -> f64 {
+    if pdf_word.font_character_width > 0.0 {
+        return pdf_word.font_character_width;
     }
+    // should be ~7.2 for 12-point font
+    pdf_word.font_size * 0.6
+}
 
-    let mut x_freq_keys: Vec<i32> = x_freq_map.keys().cloned().collect();
-    x_freq_keys.sort();
-
-    for fk in &x_freq_keys {
-        let v = x_freq_map.get(&fk);
-        println!(
-            "INDENT_INCHES: {:10.2} FREQUENCY: {:6.2}%",
-            fk.clone() as f64 / 72.0,
-            {
-                if let Some(freq) = v {
-                    let fr = *freq;
-                    (fr as f64 / lines_count as f64) * 100.0
-                } else {
-                    0.0
-                }
-            }
-        )
+fn _get_word_end_x(pdf_word: &pdf_document::Word) -> f64 {
+    if pdf_word.bbox_width > 0.0 {
+        return pdf_word.position.x + pdf_word.bbox_width;
     }
+    pdf_word.position.x + pdf_word.text.chars().count() as f64 * _get_char_width(pdf_word)
+}
 
-    None
+/// Gets the indentations to parse a page with.
+///
+/// Indentations passed in by the user are always used. Otherwise, the deduced indentations
+/// are used, unless this page is a different format than the document they were deduced from
+/// (i.e. an A4 page in a US-Letter script), in which case that format's defaults are used.
+fn _get_indentations_for_page(
+    supplied_indentations: &Option<ElementIndentationsInches>,
+    deduced_indentations: &Option<ElementIndentationsInches>,
+    page_format: &Option<PageFormat>,
+) -> ElementIndentationsInches {
+    if let Some(indentations) = supplied_indentations {
+        return indentations.clone();
+    }
+    match (deduced_indentations, page_format) {
+        (Some(deduced), Some(format)) if deduced.page_format().as_ref() != Some(format) => {
+            ElementIndentationsInches::default_for_page_format(format)
+        }
+        (Some(deduced), _) => deduced.clone(),
+        (None, Some(format)) => ElementIndentationsInches::default_for_page_format(format),
+        (None, None) => ElementIndentationsInches::us_letter_default(),
+    }
 }
 
 fn _is_word_within_content_zone(
     pdf_word: &pdf_document::Word,
     element_indentaions_pts: &ElementIndentationsPoints,
 ) -> bool {
-    todo!()
+    pdf_word.position.y < element_indentaions_pts.top
+        && pdf_word.position.y > element_indentaions_pts.bottom
+        && pdf_word.position.x >= element_indentaions_pts.left
+        && pdf_word.position.x < element_indentaions_pts.right
 }
 
-fn _check_non_content_type(
+/// Gets the name of the indentation nearest to a word, and how far away it is in points.
+fn _get_nearest_indentation(
     pdf_word: &pdf_document::Word,
-    new_line: &screenplay_document::Line,
     element_indentaions_pts: &ElementIndentationsPoints,
-    time_of_day_strs: &screenplay_document::TimeOfDayCollection,
-    environment_strs: &screenplay_document::EnvironmentStrings,
+) -> (&'static str, f64) {
+    [
+        ("Action", element_indentaions_pts.action),
+        ("Character", element_indentaions_pts.character),
+        ("Dialogue", element_indentaions_pts.dialogue),
+        ("Parenthetical", element_indentaions_pts.parenthetical),
+    ]
+    .into_iter()
+    .map(|(name, x)| (name, (pdf_word.position.x - x).abs()))
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .unwrap_or(("Action", 0.0))
+}
+
+/// Gets the types of the words on a PDF line, if the whole line is a transition.
+///
+/// Right-aligned transitions start somewhere past the Character indentation,
+/// depending on their length, so they can't be typed word-by-word.
+/// Left-aligned transitions (i.e. `FADE IN:`) start on the Action indentation.
+fn _get_transition_types(
+    pdf_line: &pdf_document::Line,
+    element_indentaions_pts: &ElementIndentationsPoints,
+    transition_strs: &TransitionStrings,
     r_marker: &String,
-) -> Option<SPType> {
-    todo!()
+) -> Option<Vec<Option<SPType>>> {
+    let position_tolerance: f64 = 0.01;
+
+    let first_word = pdf_line.words.first()?;
+    if first_word.position.y >= element_indentaions_pts.top
+        || first_word.position.y <= element_indentaions_pts.bottom
+    {
+        return None;
+    }
+
+    let mut transition_words: Vec<&str> = Vec::new();
+    let mut first_x: Option<f64> = None;
+    for pdf_word in &pdf_line.words {
+        if pdf_word.text == *r_marker {
+            continue;
+        }
+        // Transitions never have scene numbers
+        if pdf_word.position.x < element_indentaions_pts.left {
+            return None;
+        }
+        first_x.get_or_insert(pdf_word.position.x);
+        transition_words.push(&pdf_word.text);
+    }
+    let first_x = first_x?;
+    let transition_text = transition_words.join(" ");
+
+    let is_left_aligned = (first_x - element_indentaions_pts.action).abs() <= position_tolerance;
+    let is_transition = if is_left_aligned {
+        transition_strs.is_left_aligned_transition(&transition_text)
+    } else {
+        first_x > element_indentaions_pts.character
+            && transition_strs.is_right_aligned_transition(&transition_text)
+    };
+    if !is_transition {
+        return None;
+    }
+
+    Some(
+        pdf_line
+            .words
+            .iter()
+            .map(|pdf_word| {
+                if pdf_word.text == *r_marker {
+                    Some(SPType::SP_LINE_REVISION_MARKER)
+                } else {
+                    Some(SPType::SP_TRANSITION)
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Finds the words of an environment at the start of a line's content (after any scene number),
+/// so multi-word environments like `INT. / EXT.` are typed together.
+fn _get_environment_phrase(
+    pdf_line: &pdf_document::Line,
+    element_indentaions_pts: &ElementIndentationsPoints,
+    environment_strs: &EnvironmentStrings,
+) -> Option<Range<usize>> {
+    let position_tolerance: f64 = 0.01;
+    let start = pdf_line
+        .words
+        .iter()
+        .position(|w| w.position.x >= element_indentaions_pts.left)?;
+    if (pdf_line.words[start].position.x - element_indentaions_pts.action).abs()
+        > position_tolerance
+    {
+        return None;
+    }
+    let words: Vec<&str> = pdf_line.words[start..]
+        .iter()
+        .map(|w| w.text.as_str())
+        .collect();
+    let (len, _) = environment_strs.get_longest_match(&words)?;
+    Some(start..start + len)
 }
 
 fn _get_type_for_word(
@@ -113,7 +205,7 @@ fn _get_type_for_word(
         None => SPType::NONE,
         Some(e) => match &e.element_type {
             None => SPType::NONE,
-            Some(t) => t.clone(),
+            Some(t) => *t,
         },
     };
 
@@ -134,65 +226,20 @@ fn _get_type_for_word(
     // first pass of Word Type -- check previous element types first
     match previous_element_type {
         // if previous type was "content" types...
-        SPType::SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay) => {
-            if pdf_word.text == "-".to_string() {
-                return Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator));
-            }
-            return None;
-        }
-        SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator) => {
-            let type_before_separator =
-                match new_line.text_elements.get(new_line.text_elements.len() - 2) {
-                    None => SPType::NONE,
-                    Some(t) => match &t.element_type {
-                        None => SPType::NONE,
-                        Some(e) => e.clone(),
-                    },
-                };
-
-            if time_of_day_strs.is_time_of_day(&pdf_word.text) {
-                return Some(SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay));
-            }
-
-            match type_before_separator {
-                SPType::NONE => return None, // ? Something has gone very wrong...
-                SPType::SP_SCENE_HEADING(SceneHeadingElement::Location) => {
-                    return Some(SP_SCENE_HEADING(SceneHeadingElement::SubLocation));
-                }
-                SPType::SP_SCENE_HEADING(SceneHeadingElement::Location) => {
-                    if pdf_word.text == "-".to_string() {
-                        return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
-                    }
-                    return Some(SP_SCENE_HEADING(SceneHeadingElement::SubLocation));
-                }
-                SPType::SP_SCENE_HEADING(SceneHeadingElement::TimeOfDay)
-                | SP_SCENE_HEADING(SceneHeadingElement::SlugOther) => {
-                    return Some(SP_SCENE_HEADING(SceneHeadingElement::SlugOther));
-                }
-                _ => {
-                    dbg!(&pdf_word.text);
-                    dbg!(type_before_separator);
-                    //panic!();
-                    return Some(SP_SCENE_HEADING(SceneHeadingElement::SlugOther));
-                }
-            }
-        }
-        SPType::SP_PARENTHETICAL => return Some(previous_element_type.clone()),
-        SPType::SP_SCENE_HEADING(SceneHeadingElement::SubLocation) => {
-            if pdf_word.text == "-".to_string() {
-                return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
-            }
-            return Some(SP_SCENE_HEADING(SceneHeadingElement::SubLocation));
-        }
-        SPType::SP_SCENE_HEADING(SceneHeadingElement::Location) => {
-            if pdf_word.text == "-".to_string() {
-                return Some(SP_SCENE_HEADING(SceneHeadingElement::Separator));
-            }
-            return Some(SP_SCENE_HEADING(SceneHeadingElement::Location));
-        }
-        SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment) => {
-            return Some(SP_SCENE_HEADING(SceneHeadingElement::Location));
-        }
+        SPType::SP_SCENE_HEADING(
+            heading_element @ (SceneHeadingElement::TimeOfDay
+            | SceneHeadingElement::Separator
+            | SceneHeadingElement::SubLocation
+            | SceneHeadingElement::Location
+            | SceneHeadingElement::Environment),
+        ) => parser_common::get_type_for_scene_heading_word(
+            &pdf_word.text,
+            &heading_element,
+            new_line,
+            time_of_day_strs,
+            environment_strs,
+        ),
+        SPType::SP_PARENTHETICAL => Some(previous_element_type),
         SPType::SP_CHARACTER => {
             // TODO:
             // Create a function that checks all the "non-content-types"
@@ -201,33 +248,31 @@ fn _get_type_for_word(
             // if it's non-content, then return that type
             // else if it's content, then handle that within this block
             if pdf_word.text.starts_with("(") {
-                return Some(SPType::SP_CHARACTER_EXTENSION);
+                Some(SPType::SP_CHARACTER_EXTENSION)
             } else {
                 if pdf_word.text == *r_marker {
                     return Some(SPType::SP_LINE_REVISION_MARKER);
                 }
-                return Some(SPType::SP_CHARACTER);
+                Some(SPType::SP_CHARACTER)
             }
         }
         SPType::SP_DD_L_CHARACTER => {
             if pdf_word.text.starts_with("(") {
-                return Some(SPType::SP_DD_L_CHARACTER_EXTENSION);
+                Some(SPType::SP_DD_L_CHARACTER_EXTENSION)
             } else {
-                return Some(SPType::SP_DD_L_CHARACTER);
+                Some(SPType::SP_DD_L_CHARACTER)
             }
         }
         SPType::SP_DD_R_CHARACTER => {
             if pdf_word.text.starts_with("(") {
-                return Some(SPType::SP_DD_R_CHARACTER_EXTENSION);
+                Some(SPType::SP_DD_R_CHARACTER_EXTENSION)
             } else {
-                return Some(SPType::SP_DD_R_CHARACTER);
+                Some(SPType::SP_DD_R_CHARACTER)
             }
         }
         SPType::SP_DD_L_CHARACTER_EXTENSION
         | SPType::SP_DD_R_CHARACTER_EXTENSION
-        | SPType::SP_CHARACTER_EXTENSION => {
-            return Some(previous_element_type.clone());
-        }
+        | SPType::SP_CHARACTER_EXTENSION => Some(previous_element_type),
 
         _ => {
             // ------------- INDENTATION PARSING --------------------------
@@ -252,13 +297,8 @@ fn _get_type_for_word(
                     }
                     return Some(SPType::SP_SCENENUM);
                 } else {
-                    let _within_tolerance = |target| {
-                        if (&pdf_word.position.x - &target).abs() > position_tolerance {
-                            return false;
-                        } else {
-                            return true;
-                        };
-                    };
+                    let _within_tolerance =
+                        |target: f64| (pdf_word.position.x - target).abs() <= position_tolerance;
 
                     //Within Vertical AND Horizontal Content Zone after this point
 
@@ -266,23 +306,23 @@ fn _get_type_for_word(
                     if _within_tolerance(element_indentaions_pts.action) {
                         //TODO: FIXME: Let user PASS IN INT_EXT PATTERNS (i.e. for non-english scripts)
 
-                        if let Some(_) = Environment::from_str(&pdf_word.text, environment_strs) {
+                        if Environment::from_str(&pdf_word.text, environment_strs).is_some() {
                             return Some(SP_SCENE_HEADING(SceneHeadingElement::Environment));
-                        } else if new_line.line_type == None {
+                        } else if new_line.line_type.is_none() {
                             return Some(SPType::SP_ACTION);
                         };
                     }
                     if _within_tolerance(element_indentaions_pts.character)
-                        && new_line.line_type == None
+                        && new_line.line_type.is_none()
                     {
                         return Some(SPType::SP_CHARACTER);
                     } else if _within_tolerance(element_indentaions_pts.dialogue)
-                        && new_line.line_type == None
+                        && new_line.line_type.is_none()
                     {
                         return Some(SPType::SP_DIALOGUE);
                     } else if _within_tolerance(element_indentaions_pts.parenthetical)
                         && pdf_word.text.starts_with("(")
-                        && new_line.line_type == None
+                        && new_line.line_type.is_none()
                     {
                         return Some(SPType::SP_PARENTHETICAL);
                     } else {
@@ -295,7 +335,7 @@ fn _get_type_for_word(
             // pdf_word.text == "17A.".to_string() {println!("PAGENUMBER FOUND!----------------");}
             if pdf_word.position.y >= element_indentaions_pts.top {
                 let wordwidth: f64 = char_width * f64::from(pdf_word.text.len() as i32);
-                let rightedge: f64 = wordwidth + pdf_word.position.x;
+                let _rightedge: f64 = wordwidth + pdf_word.position.x;
                 if pdf_word.position.x < element_indentaions_pts.pagewidth / 3.0 {
                     return Some(SPType::NON_CONTENT_TOP);
                 } else if (element_indentaions_pts.pagewidth - pdf_word.position.x)
@@ -312,25 +352,89 @@ fn _get_type_for_word(
                 | pdf_word.text.contains("(CONTINUED)")
                 | pdf_word.text.contains("(CONT'D)")
             {
-                return Some(SPType::SP_MORE_CONTINUED);
+                Some(SPType::SP_MORE_CONTINUED)
             } else {
-                return Some(SPType::NON_CONTENT_BOTTOM);
+                Some(SPType::NON_CONTENT_BOTTOM)
             }
         }
     }
 }
 
+/// Parses a `PDFDocument` into a `ScreenplayDocument`.
+///
+/// Any of the optional arguments which are `None` use their defaults (or deduced values,
+/// for the indentations).
+///
+/// Returns a `ParseError` if the document has no pages, no content,
+/// or a scene heading with an unknown environment.
 pub fn get_screenplay_doc_from_pdf_obj(
     doc: pdf_document::PDFDocument,
     element_indent_in_opt: Option<ElementIndentationsInches>,
     rev_marker_opt: Option<String>,
     time_of_day_strs_opt: Option<screenplay_document::TimeOfDayCollection>,
     env_strs_opt: Option<EnvironmentStrings>,
-) -> Option<screenplay_document::ScreenplayDocument> {
+    transition_strs_opt: Option<TransitionStrings>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
+    _get_screenplay_doc_from_pdf_obj(
+        doc,
+        element_indent_in_opt,
+        rev_marker_opt,
+        time_of_day_strs_opt,
+        env_strs_opt,
+        transition_strs_opt,
+        &mut Vec::new(),
+    )
+}
+
+/// Parses a `PDFDocument` into a `ScreenplayDocument`, the same as `get_screenplay_doc_from_pdf_obj`,
+/// and also returns the `Diagnostic`s for anything in the document which should be reviewed.
+///
+/// Along with the checks from `diagnostics::get_diagnostics`, this reports any words
+/// which didn't match an indentation.
+pub fn get_screenplay_doc_and_diagnostics_from_pdf_obj(
+    doc: pdf_document::PDFDocument,
+    element_indent_in_opt: Option<ElementIndentationsInches>,
+    rev_marker_opt: Option<String>,
+    time_of_day_strs_opt: Option<screenplay_document::TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+    transition_strs_opt: Option<TransitionStrings>,
+) -> Result<(screenplay_document::ScreenplayDocument, Vec<Diagnostic>), ParseError> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let screenplay_doc = _get_screenplay_doc_from_pdf_obj(
+        doc,
+        element_indent_in_opt,
+        rev_marker_opt,
+        time_of_day_strs_opt,
+        env_strs_opt,
+        transition_strs_opt,
+        &mut diagnostics,
+    )?;
+    // Lines with a misplaced word are usually left untyped, which is already explained
+    let misplaced_lines: Vec<(usize, usize)> = diagnostics
+        .iter()
+        .map(|d| (d.coordinate.page, d.coordinate.line))
+        .collect();
+    diagnostics.extend(
+        crate::diagnostics::get_diagnostics(&screenplay_doc)
+            .into_iter()
+            .filter(|d| !misplaced_lines.contains(&(d.coordinate.page, d.coordinate.line))),
+    );
+    Ok((screenplay_doc, diagnostics))
+}
+
+fn _get_screenplay_doc_from_pdf_obj(
+    doc: pdf_document::PDFDocument,
+    element_indent_in_opt: Option<ElementIndentationsInches>,
+    rev_marker_opt: Option<String>,
+    time_of_day_strs_opt: Option<screenplay_document::TimeOfDayCollection>,
+    env_strs_opt: Option<EnvironmentStrings>,
+    transition_strs_opt: Option<TransitionStrings>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<screenplay_document::ScreenplayDocument, ParseError> {
     use screenplay_document::ScreenplayDocument;
 
-    if doc.pages.len() < 1 {
-        return None;
+    if doc.pages.is_empty() {
+        return Err(ParseError::EmptyDocument);
     }
 
     let time_of_day_strs: screenplay_document::TimeOfDayCollection;
@@ -347,6 +451,13 @@ pub fn get_screenplay_doc_from_pdf_obj(
         environment_strs = EnvironmentStrings::default();
     }
 
+    let transition_strs: TransitionStrings;
+    if let Some(tss) = transition_strs_opt {
+        transition_strs = tss;
+    } else {
+        transition_strs = TransitionStrings::default();
+    }
+
     let r_marker;
     if let Some(rm) = rev_marker_opt {
         r_marker = rm;
@@ -354,18 +465,29 @@ pub fn get_screenplay_doc_from_pdf_obj(
         r_marker = "*".to_string();
     }
 
+    let deduced_indentations = match element_indent_in_opt {
+        Some(_) => None,
+        None => deduce_indentations(&doc),
+    };
+
     let mut new_screenplay_doc: ScreenplayDocument = ScreenplayDocument::default();
+    // The indentations of each page which was added to the document
+    let mut content_zones: Vec<ElementIndentationsPoints> = Vec::new();
 
-    for pdf_page in doc.pages.iter() {
-        if pdf_page.lines.len() < 1 {
+    for (page_idx, pdf_page) in doc.pages.iter().enumerate() {
+        if pdf_page.lines.is_empty() {
             continue;
         };
         // TODO: abstract out the "line handling" logic into "fn get_line()"??
 
-        let mut new_page = screenplay_document::Page::default();
+        let page_format = pdf_page.page_size.get_page_format();
+        let mut new_page = screenplay_document::Page {
+            page_format,
+            ..Default::default()
+        };
 
         let mut prev_line_y_pos: f64 = 0.0;
-        let mut line_height: f64 = 12.0; //This line height could be identified either here in-line or in
+        let line_height: f64 = 12.0; //This line height could be identified either here in-line or in
         // a pre-processing scan of the document
         // in-line might be better, to do it page-by-page as we go, rather than keep dictionaries/hashmaps
 
@@ -375,51 +497,117 @@ pub fn get_screenplay_doc_from_pdf_obj(
         // like a frankenscript from multiple writers
         // We should let the user pass in multiple ranges of indentations, optionally
         // but that's not necessary right now for basic functionality
-        let mut current_resolution: f64 = 72.0;
-        let element_indentaions_pts;
-        if let Some(ref indentations) = element_indent_in_opt {
-            element_indentaions_pts =
-                ElementIndentationsPoints::from_inches(indentations, &Some(current_resolution));
-        } else {
-            element_indentaions_pts =
-                ElementIndentationsPoints::us_letter_default(&Some(current_resolution));
+        let current_resolution: f64 = 72.0;
+        let element_indentaions_pts = ElementIndentationsPoints::from_inches(
+            &_get_indentations_for_page(
+                &element_indent_in_opt,
+                &deduced_indentations,
+                &page_format,
+            ),
+            &Some(current_resolution),
+        );
+
+        // The title page has no scenes or page number, so it skips the usual parsing
+        if page_idx == 0
+            && let Some(title_page) =
+                title_page::get_title_page(pdf_page, &element_indentaions_pts, &environment_strs)
+        {
+            new_screenplay_doc.title_page = parser_common::get_title_page(&title_page);
+            new_screenplay_doc.pages.push(screenplay_document::Page {
+                page_format,
+                ..title_page
+            });
+            content_zones.push(element_indentaions_pts);
+            continue;
         }
-        for (pdf_l_idx, pdf_line) in pdf_page.lines.iter().enumerate() {
-            if pdf_line.words.len() < 1 {
+
+        let mut dual_dialogue_state: Option<dual_dialogue::DualDialogueState> = None;
+        // Top margin text above the first content line is part of the page header
+        let mut has_content_line = false;
+        for pdf_line in pdf_page.lines.iter() {
+            if pdf_line.words.is_empty() {
                 continue;
             };
 
+            // Lines which can't be typed word-by-word
+            let line_word_types = dual_dialogue::get_dual_dialogue_types(
+                pdf_line,
+                &mut dual_dialogue_state,
+                &element_indentaions_pts,
+            )
+            .or_else(|| {
+                _get_transition_types(
+                    pdf_line,
+                    &element_indentaions_pts,
+                    &transition_strs,
+                    &r_marker,
+                )
+            });
+
+            let environment_phrase =
+                _get_environment_phrase(pdf_line, &element_indentaions_pts, &environment_strs);
+
             let mut new_line = screenplay_document::Line::default();
+            // The page and line are filled in once the line is added to the page
+            let mut line_diagnostics: Vec<Diagnostic> = Vec::new();
             let mut previous_element_type: SPType = SPType::NONE;
-            let mut word_counter: usize = 0;
-            for pdf_word in pdf_line.words.iter() {
+            for (word_counter, pdf_word) in pdf_line.words.iter().enumerate() {
                 //println!("Iterating over PDF WORDS!");
                 let mut new_text_element = screenplay_document::TextElement::default();
 
-                let new_word_type: Option<SPType> = _get_type_for_word(
-                    &pdf_word,
-                    &new_line,
-                    &element_indentaions_pts,
-                    &time_of_day_strs,
-                    &environment_strs,
-                    &r_marker,
-                );
+                let new_word_type: Option<SPType> = match line_word_types
+                    .as_ref()
+                    .and_then(|types| types.get(word_counter).copied().flatten())
+                {
+                    Some(line_word_type) => Some(line_word_type),
+                    None if environment_phrase
+                        .as_ref()
+                        .is_some_and(|phrase| phrase.contains(&word_counter)) =>
+                    {
+                        Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment))
+                    }
+                    None => _get_type_for_word(
+                        pdf_word,
+                        &new_line,
+                        &element_indentaions_pts,
+                        &time_of_day_strs,
+                        &environment_strs,
+                        &r_marker,
+                    ),
+                };
 
                 //println!("New type! {:?}", new_word_type);
-                new_text_element.element_position = Some(pdf_word.position.clone());
+                if new_word_type.is_none()
+                    && new_line.line_type.is_none()
+                    && _is_word_within_content_zone(pdf_word, &element_indentaions_pts)
+                {
+                    let (indentation, distance) =
+                        _get_nearest_indentation(pdf_word, &element_indentaions_pts);
+                    let char_width = _get_char_width(pdf_word);
+                    line_diagnostics.push(Diagnostic {
+                        coordinate: ScreenplayCoordinate {
+                            page: 0,
+                            line: 0,
+                            element: Some(word_counter as u64),
+                        },
+                        severity: Severity::Warning,
+                        message: format!(
+                            "'{}' didn't match any indentation; nearest is {} ({:.2} pt away)",
+                            pdf_word.text, indentation, distance
+                        ),
+                        confidence: (1.0 - distance / (2.0 * char_width)).clamp(0.0, 1.0),
+                    });
+                }
+                new_text_element.element_position = Some(pdf_word.position);
 
                 if let Some(nwt) = new_word_type {
                     match nwt {
                         // Assign proper LINE TYPEs based on current WORD type
-                        SPType::SP_DIALOGUE => {
-                            if new_line.line_type == None {
-                                new_line.line_type = Some(SPType::SP_DIALOGUE);
-                            }
+                        SPType::SP_DIALOGUE if new_line.line_type.is_none() => {
+                            new_line.line_type = Some(SPType::SP_DIALOGUE);
                         }
-                        SPType::SP_PARENTHETICAL => {
-                            if new_line.line_type == None {
-                                new_line.line_type = Some(SPType::SP_PARENTHETICAL);
-                            }
+                        SPType::SP_PARENTHETICAL if new_line.line_type.is_none() => {
+                            new_line.line_type = Some(SPType::SP_PARENTHETICAL);
                         }
                         SPType::SP_DD_L_PARENTHETICAL
                         | SPType::SP_DD_R_PARENTHETICAL
@@ -427,24 +615,28 @@ pub fn get_screenplay_doc_from_pdf_obj(
                         | SPType::SP_DD_R_DIALOGUE => {
                             new_line.line_type = Some(SPType::SP_DUAL_DIALOGUES);
                         }
-                        SPType::SP_CHARACTER => {
-                            if new_line.line_type == None {
-                                new_line.line_type = Some(SPType::SP_CHARACTER);
-                            }
+                        SPType::SP_DD_L_MORE_CONTINUED | SPType::SP_DD_R_MORE_CONTINUED
+                            if new_line.line_type.is_none() =>
+                        {
+                            new_line.line_type = Some(SPType::SP_DUAL_DIALOGUES);
                         }
-                        SPType::SP_DD_L_CHARACTER | SPType::SP_DD_R_CHARACTER => {
-                            if new_line.line_type == None {
-                                new_line.line_type = Some(SPType::SP_DUAL_CHARACTERS);
-                            }
+                        SPType::SP_CHARACTER if new_line.line_type.is_none() => {
+                            new_line.line_type = Some(SPType::SP_CHARACTER);
                         }
-                        SPType::SP_ACTION => {
-                            if new_line.line_type == None {
-                                new_line.line_type = Some(SPType::SP_ACTION);
-                            }
+                        SPType::SP_DD_L_CHARACTER | SPType::SP_DD_R_CHARACTER
+                            if new_line.line_type.is_none() =>
+                        {
+                            new_line.line_type = Some(SPType::SP_DUAL_CHARACTERS);
+                        }
+                        SPType::SP_ACTION if new_line.line_type.is_none() => {
+                            new_line.line_type = Some(SPType::SP_ACTION);
+                        }
+                        SPType::SP_TRANSITION if new_line.line_type.is_none() => {
+                            new_line.line_type = Some(SPType::SP_TRANSITION);
                         }
                         SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment) => {
                             use screenplay_document::SceneHeadingElement;
-                            if new_line.line_type == None {
+                            if new_line.line_type.is_none() {
                                 new_line.line_type =
                                     Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line));
                             }
@@ -455,15 +647,14 @@ pub fn get_screenplay_doc_from_pdf_obj(
                         // any element needs to still be available in the screenplay document, so we can
                         // re-assign it after parsing, if necessary
                         SPType::SP_PAGENUM => {
-                            if new_line.line_type == None {
+                            if new_line.line_type.is_none() {
                                 new_line.line_type = Some(SPType::SP_PAGE_HEADER);
                             }
                             new_page.page_number = Some(PageNumber(pdf_word.text.clone()));
                             //continue;
                         }
                         SPType::SP_PAGE_REVISION_LABEL => {
-                            // TODO: parse revision label for COLOR and DATE
-                            // then ADD metadata to PAGE
+                            // The label's color and date are parsed once the whole page is typed
                             new_page.revised = true;
                             //continue;
                         }
@@ -490,20 +681,16 @@ pub fn get_screenplay_doc_from_pdf_obj(
                                     .trim_matches('.')
                                     .to_string(),
                             );
-                            if let Some(sn) = maybe_scene_num {
-                                if !sn.is_empty() {
-                                    new_line.scene_number = Some(sn);
-                                    use screenplay_document::SceneHeadingElement;
-                                    match new_line.line_type {
-                                        Some(SPType::NONE) => {
-                                            new_line.line_type = Some(SPType::SP_SCENE_HEADING(
-                                                SceneHeadingElement::Line,
-                                            ));
-                                        }
-                                        _ => {}
-                                    }
-                                    previous_element_type = SPType::SP_SCENENUM;
+                            if let Some(sn) = maybe_scene_num
+                                && !sn.is_empty()
+                            {
+                                new_line.scene_number = Some(sn);
+                                use screenplay_document::SceneHeadingElement;
+                                if let Some(SPType::NONE) = new_line.line_type {
+                                    new_line.line_type =
+                                        Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line));
                                 }
+                                previous_element_type = SPType::SP_SCENENUM;
                             }
 
                             //continue;
@@ -518,39 +705,38 @@ pub fn get_screenplay_doc_from_pdf_obj(
                     }
                 }
 
-                new_text_element.element_type = new_word_type.clone();
+                new_text_element.element_type = new_word_type;
                 new_text_element.text = pdf_word.text.clone();
 
                 // -------- WHITESPACING --------
 
                 // CALCULATE PRECEDING WHITESPACE CHARS, IF ANY
 
-                if word_counter > 0 {
-                    if let Some(last_word) = pdf_line.words.last() {
-                        let char_width: f64 = 7.2;
-                        let whitespace_chars: u64 = u64::from(
-                            ((pdf_word.position.x - (last_word.position.x + last_word.bbox_width))
-                                / char_width)
-                                .round() as u64,
-                        );
-
-                        if whitespace_chars >= 1 {
-                            match previous_element_type {
-                                SPType::SP_SCENENUM | SPType::SP_LINE_REVISION_MARKER => {
-                                    new_text_element.preceding_whitespace_chars = 0;
-                                }
-                                _ => {
-                                    new_text_element.preceding_whitespace_chars = whitespace_chars;
-                                }
+                if word_counter > 0
+                    && let Some(last_word) = pdf_line.words.last()
+                {
+                    let char_width: f64 = 7.2;
+                    let whitespace_chars: u64 = ((pdf_word.position.x
+                        - (last_word.position.x + last_word.bbox_width))
+                        / char_width)
+                        .round() as u64;
+
+                    if whitespace_chars >= 1 {
+                        match previous_element_type {
+                            SPType::SP_SCENENUM | SPType::SP_LINE_REVISION_MARKER => {
+                                new_text_element.preceding_whitespace_chars = 0;
+                            }
+                            _ => {
+                                new_text_element.preceding_whitespace_chars = whitespace_chars;
                             }
-                        } else {
-                            //FIXME: WTF does this whole if block even do???? Why does this print so often???
-                            //println!("NEW TEXT ELEMENT OVERLAPS PREVIOUS ELEMENT! Assigned 1 unit of preceding whtiespace...");
-                            new_text_element.preceding_whitespace_chars = 1
                         }
-                    };
-                }
-                if let Some(new_type) = new_word_type.clone() {
+                    } else {
+                        //FIXME: WTF does this whole if block even do???? Why does this print so often???
+                        //println!("NEW TEXT ELEMENT OVERLAPS PREVIOUS ELEMENT! Assigned 1 unit of preceding whtiespace...");
+                        new_text_element.preceding_whitespace_chars = 1
+                    }
+                };
+                if let Some(new_type) = new_word_type {
                     previous_element_type = new_type;
                 } else {
                     previous_element_type = SPType::NONE;
@@ -558,8 +744,6 @@ pub fn get_screenplay_doc_from_pdf_obj(
 
                 new_line.text_elements.push(new_text_element);
                 //println!("Pushing new text element!");
-
-                word_counter += 1;
             }
             //Add number of preceding blank lines to this line
             let cur_y_pos = pdf_line.words.first().unwrap().position.y;
@@ -576,40 +760,54 @@ pub fn get_screenplay_doc_from_pdf_obj(
                 continue;
             }
 
+            // OMITTED scenes keep their scene numbers in the margins, but have no heading
+            let is_scene_number_type = |t: &Option<SPType>| {
+                matches!(
+                    t,
+                    Some(SPType::SP_SCENENUM) | Some(SPType::SP_LINE_REVISION_MARKER)
+                )
+            };
+            let content_text = new_line
+                .text_elements
+                .iter()
+                .filter(|te| !is_scene_number_type(&te.element_type))
+                .map(|te| te.text.as_str())
+                .collect::<Vec<&str>>()
+                .join(" ");
+            if new_line.scene_number.is_some()
+                && !matches!(new_line.line_type, Some(SPType::SP_SCENE_HEADING(_)))
+                && parser_common::is_omitted_text(&content_text)
+            {
+                new_line.line_type = Some(SPType::SP_OMITTED);
+                for te in &mut new_line.text_elements {
+                    if !is_scene_number_type(&te.element_type) {
+                        te.element_type = Some(SPType::SP_OMITTED);
+                    }
+                }
+            }
+
             match new_line.line_type {
                 None => {}
+                Some(SPType::SP_OMITTED) => {
+                    let start = ScreenplayCoordinate {
+                        page: new_screenplay_doc.pages.len(),
+                        line: new_page.lines.len(),
+                        element: None,
+                    };
+                    parser_common::add_omitted_scene(&mut new_screenplay_doc, &mut new_line, start);
+                }
                 // CHARACTER PARSING
-                Some(SPType::SP_CHARACTER) => {
-                    let mut character_name = String::new();
-                    for element in &new_line.text_elements {
-                        if element.element_type == Some(SPType::SP_CHARACTER) {
-                            if !character_name.is_empty() {
-                                character_name.push(' ');
-                            }
-                            character_name.push_str(&element.text);
-                        }
-                    }
-                    //panic!();
-                    
-                    let mut character_exists_in_doc = false;
-                    if !&new_screenplay_doc.characters.is_empty() {
-                        for character in &new_screenplay_doc.characters {
-                            if character.name == character_name {
-                                character_exists_in_doc = true;
-                            }
-                        }
-                    }
-                    if !character_exists_in_doc{
-                        
-                        let new_character = Character {
-                            name: character_name,
-                            id: screenplay_document::CharacterID::new(),
-                        };
-                        new_screenplay_doc.characters.insert(new_character);
-                    }
+                Some(SPType::SP_CHARACTER) | Some(SPType::SP_DUAL_CHARACTERS) => {
+                    parser_common::add_characters_from_line(&mut new_screenplay_doc, &new_line);
                 }
                 // SCENE / LOCATION PARSING
                 Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line)) => {
+                    parser_common::merge_heading_phrases(
+                        &mut new_line,
+                        &time_of_day_strs,
+                        &environment_strs,
+                    );
+
                     // Environment Parsing
                     let maybe_first_word = &new_line
                         .text_elements
@@ -621,154 +819,36 @@ pub fn get_screenplay_doc_from_pdf_obj(
                         .take(1)
                         .next();
 
+                    let start = ScreenplayCoordinate {
+                        page: new_screenplay_doc.pages.len(),
+                        line: new_page.lines.len(),
+                        element: None,
+                    };
+
                     let mut new_line_env = Environment::Ext;
 
                     if let Some(fw) = maybe_first_word {
-                        new_line_env = Environment::from_str(&fw.text, &environment_strs).unwrap();
-                    }
-
-                    // Location Parsing
-
-                    let mut root_location_string: String = String::new();
-
-                    let mut current_sub_location_string = String::new();
-                    let mut full_path: Vec<String> = Vec::new();
-                    let mut root_location_done = false;
-
-                    for element in &new_line.text_elements {
-                        match element.element_type {
-                            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Location))
-                            | Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Environment)) => {
-                                if !root_location_string.is_empty() {
-                                    root_location_string.push(' ');
-                                }
-                                root_location_string.push_str(&element.text.clone());
-                            }
-                            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Separator)) => {
-                                if root_location_done {
-                                    if !current_sub_location_string.is_empty() {
-                                        full_path.push(current_sub_location_string.clone());
-                                        current_sub_location_string = String::new();
-                                    }
-                                } else {
-                                    root_location_done = true;
-                                    full_path.push(root_location_string.clone());
-                                }
-                            }
-                            Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::SubLocation)) => {
-                                if !current_sub_location_string.is_empty() {
-                                    current_sub_location_string.push(' ');
-                                }
-                                current_sub_location_string.push_str(&element.text.clone());
-                            }
-                            _ => {
-                                if !root_location_string.is_empty() {
-                                    break;
-                                }
-                            }
-                        }
-                    }
-
-                    let mut location_id_to_insert: Option<LocationID> = None;
-                    let mut exists: bool = false;
-
-                    for (existing_id, existing_root) in &new_screenplay_doc.locations {
-                        if existing_root.superlocation.is_some() {
-                            continue;
-                        }
-                        if root_location_string == existing_root.string {
-                            location_id_to_insert = Some(existing_id.clone());
-                            exists = true;
-                        }
-                    }
-
-                    if !exists {
-                        location_id_to_insert = Some(LocationID::new());
-
-                        let new_root_location: LocationNode = LocationNode {
-                            string: root_location_string.clone(),
-                            sublocations: HashSet::new(),
-                            superlocation: None,
+                        let Some(env) = Environment::from_str(&fw.text, &environment_strs) else {
+                            return Err(ParseError::UnknownEnvironment {
+                                coordinate: start,
+                                text: fw.text.clone(),
+                            });
                         };
-                        new_screenplay_doc
-                            .locations
-                            .insert(location_id_to_insert.clone().unwrap(), new_root_location);
+                        new_line_env = env;
                     }
-
-                    // Subpath parsing and insertion
-
-                    if let Some((id, s_path)) =
-                        crate::reports::location_path_exists(&new_screenplay_doc, &full_path)
-                        
+                    if let Some(env) =
+                        parser_common::get_heading_environment(&new_line, &environment_strs)
                     {
-                        let mut current_id = id.clone();
-
-                        for pathstring in s_path {
-                            if let Some(location) =
-                                &mut new_screenplay_doc.locations.get_mut(&current_id)
-                            {
-                                let new_id = LocationID::new();
-
-                                let new_location = LocationNode {
-                                    string: pathstring.clone(),
-                                    sublocations: HashSet::new(),
-                                    superlocation: Some(current_id.clone()),
-                                };
-                                current_id = new_id.clone();
-                                location_id_to_insert = Some(new_id.clone());
-                                location.add_sublocation(new_id.clone());
-                                new_screenplay_doc
-                                    .locations
-                                    .insert(new_id.clone(), new_location);
-                            }
-                        }
+                        new_line_env = env;
                     }
 
-                    // Scene Insertion
-                    let new_scene = Scene {
-                        number: {
-                            if let Some(num) = &new_line.scene_number.clone() {
-                                Some(SceneNumber(num.clone()))
-                            } else {
-                                None
-                            }
-                        },
-                        environment: new_line_env,
-                        start: ScreenplayCoordinate {
-                            page: new_screenplay_doc.pages.len(),
-                            line: new_page.lines.len(),
-                            element: None,
-                        },
-                        revised: new_line.revised,
-                        story_locations: {
-                            if let Some(id) = location_id_to_insert {
-                                vec![id.clone()]
-                            } else {
-                                Vec::new()
-                            }
-                        },
-                        story_time_of_day: {
-                            let maybe_time: Vec<TextElement> = new_line
-                                .text_elements
-                                .iter()
-                                .filter(|el| {
-                                    el.element_type
-                                        == Some(SPType::SP_SCENE_HEADING(
-                                            SceneHeadingElement::TimeOfDay,
-                                        ))
-                                })
-                                .map(|el| el.clone())
-                                .collect();
-                            match maybe_time.is_empty() {
-                                true => None,
-                                false => time_of_day_strs
-                                    .get_time_of_day(&maybe_time.first().unwrap().text),
-                            }
-                        },
-                    };
-                    let new_scene_id = SceneID::new();
-                    new_line.scene_id = Some(new_scene_id.clone());
-                    new_screenplay_doc.scenes.insert(new_scene_id, new_scene);
+                    parser_common::add_scene_from_heading_line(
+                        &mut new_screenplay_doc,
+                        &mut new_line,
+                        start,
+                        new_line_env,
+                        &time_of_day_strs,
+                    );
                 }
                 _ => {}
             }
@@ -776,11 +856,10 @@ pub fn get_screenplay_doc_from_pdf_obj(
             // line number fixing
             if let Some(SPType::SP_SCENE_HEADING(SceneHeadingElement::Line)) = new_line.line_type {
                 for te in &mut new_line.text_elements {
-                    if te.element_type == None
+                    if te.element_type.is_none()
                         && te.text == new_line.scene_number.clone().unwrap_or("_N?N_".to_string())
                     {
                         te.element_type = Some(SPType::SP_SCENENUM);
-                        println!("{:?}", te.element_type);
                     }
                 }
             } else {
@@ -789,7 +868,7 @@ pub fn get_screenplay_doc_from_pdf_obj(
                 // else leave it alone
                 let mut last_element_type: &Option<SPType> = &None;
                 for te in &mut new_line.text_elements {
-                    if te.element_type == None {
+                    if te.element_type.is_none() {
                         match new_line.line_type {
                             Some(SPType::SP_ACTION) => {
                                 te.element_type = Some(SPType::SP_ACTION);
@@ -809,24 +888,80 @@ pub fn get_screenplay_doc_from_pdf_obj(
                             _ => {}
                         }
                     }
+                    last_element_type = &te.element_type
+                }
 
-                    if new_line.line_type == Some(SPType::SP_PAGE_HEADER)
-                        && te.element_type == Some(SPType::NON_CONTENT_TOP)
-                    {
-                        te.element_type = Some(SPType::SP_PAGE_REVISION_LABEL);
+                // Top margin text is only a revision label if it has a revision color or date,
+                // so titles and "CONTINUED:" headers aren't mistaken for one
+                let top_margin_text = new_line
+                    .text_elements
+                    .iter()
+                    .filter(|te| te.element_type == Some(SPType::NON_CONTENT_TOP))
+                    .map(|te| te.text.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(" ");
+                if (new_line.line_type == Some(SPType::SP_PAGE_HEADER) || !has_content_line)
+                    && parser_common::is_revision_label_text(
+                        &top_margin_text,
+                        &RevisionColorSequence::default(),
+                    )
+                {
+                    for te in &mut new_line.text_elements {
+                        if te.element_type == Some(SPType::NON_CONTENT_TOP) {
+                            te.element_type = Some(SPType::SP_PAGE_REVISION_LABEL);
+                        }
                     }
-                    last_element_type = &te.element_type
+                }
+                // A revision label on its own line, without the page number
+                if new_line.line_type.is_none()
+                    && new_line
+                        .text_elements
+                        .iter()
+                        .any(|te| te.element_type == Some(SPType::SP_PAGE_REVISION_LABEL))
+                {
+                    new_line.line_type = Some(SPType::SP_PAGE_HEADER);
                 }
             }
 
+            for mut diagnostic in line_diagnostics {
+                diagnostic.coordinate.page = new_screenplay_doc.pages.len();
+                diagnostic.coordinate.line = new_page.lines.len();
+                diagnostics.push(diagnostic);
+            }
+            has_content_line |= parser_common::is_scene_content_line(&new_line);
             new_page.lines.push(new_line);
         }
         if new_page.lines.is_empty() {
             continue;
         }
 
+        let revision_label = new_page
+            .lines
+            .iter()
+            .flat_map(|l| &l.text_elements)
+            .filter(|te| te.element_type == Some(SPType::SP_PAGE_REVISION_LABEL))
+            .map(|te| te.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        if !revision_label.is_empty() {
+            new_page.revised = true;
+            new_page.revision_date = parser_common::get_revision_date_text(&revision_label);
+            new_page.revision_label = Some(revision_label);
+        }
+
         new_screenplay_doc.pages.push(new_page);
+        content_zones.push(element_indentaions_pts);
     }
 
-    Some(new_screenplay_doc)
+    // Every page has a content zone, so the lines per page are never used
+    parser_common::set_scene_ends_and_lengths(&mut new_screenplay_doc, &content_zones, 0);
+
+    new_screenplay_doc.set_revisions(&RevisionColorSequence::default());
+
+    new_screenplay_doc.set_story_days(&StoryDayMarkers::default(), &HashMap::new());
+
+    if new_screenplay_doc.pages.is_empty() {
+        return Err(ParseError::NoContentPages);
+    }
+    Ok(new_screenplay_doc)
 }
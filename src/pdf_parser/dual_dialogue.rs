@@ -0,0 +1,227 @@
+//! Detection of side-by-side (dual) dialogue blocks.
+//!
+//! Dual dialogue columns don't sit at any of the regular element indentations,
+//! so they can't be typed word-by-word like the rest of the document.
+//! Instead, a dual dialogue block is detected by its first line: two character cues
+//! on the same line, separated by a wide gap. Every line directly below it, with no
+//! blank lines in between, belongs to the block. Each word is assigned to the left or right
+//! column by its x-position.
+
+use super::{_get_char_width, _get_word_end_x};
+use crate::pdf_document::{self, ElementIndentationsPoints};
+use crate::screenplay_document::SPType;
+
+/// Words further apart than this many character widths are in separate columns.
+const COLUMN_GAP_CHARS: f64 = 2.0;
+
+/// The state of the dual dialogue block the parser is currently in.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DualDialogueState {
+    /// Runs starting left of this x-position belong to the left column.
+    split_x: f64,
+    last_line_y: f64,
+    line_height: f64,
+    left_in_parenthetical: bool,
+    right_in_parenthetical: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Left,
+    Right,
+}
+
+fn is_more_or_continued(text: &str) -> bool {
+    text.contains("(MORE)") || text.contains("(CONT'D)") || text.contains("(CONTINUED)")
+}
+
+/// A character cue is uppercase, except for its extensions.
+fn is_character_cue(run: &[(usize, &pdf_document::Word)]) -> bool {
+    let name: String = run
+        .iter()
+        .map(|(_, w)| w.text.as_str())
+        .take_while(|text| !text.starts_with('('))
+        .collect();
+    name.chars().any(|c| c.is_alphabetic()) && !name.chars().any(|c| c.is_lowercase())
+}
+
+/// Splits the content words of a line into runs of words, separated by wide gaps.
+///
+/// Each word keeps its index in the PDF line.
+fn get_word_runs<'a>(
+    pdf_line: &'a pdf_document::Line,
+    element_indentations_pts: &ElementIndentationsPoints,
+) -> Vec<Vec<(usize, &'a pdf_document::Word)>> {
+    let mut runs: Vec<Vec<(usize, &pdf_document::Word)>> = Vec::new();
+    let mut previous_end_x: Option<f64> = None;
+
+    for (idx, pdf_word) in pdf_line.words.iter().enumerate() {
+        let x = pdf_word.position.x;
+        let y = pdf_word.position.y;
+        if x < element_indentations_pts.left
+            || x >= element_indentations_pts.right
+            || y >= element_indentations_pts.top
+            || y <= element_indentations_pts.bottom
+        {
+            continue;
+        }
+        let starts_new_run = match (previous_end_x, runs.last()) {
+            (Some(end_x), Some(_)) => x - end_x > COLUMN_GAP_CHARS * _get_char_width(pdf_word),
+            _ => true,
+        };
+        if starts_new_run {
+            runs.push(Vec::new());
+        }
+        if let Some(run) = runs.last_mut() {
+            run.push((idx, pdf_word));
+        }
+        previous_end_x = Some(_get_word_end_x(pdf_word));
+    }
+    runs
+}
+
+/// Types the words of a dual dialogue character line.
+fn get_character_types(
+    run: &[(usize, &pdf_document::Word)],
+    column: Column,
+) -> Vec<(usize, SPType)> {
+    let (character_type, extension_type, more_continued_type) = match column {
+        Column::Left => (
+            SPType::SP_DD_L_CHARACTER,
+            SPType::SP_DD_L_CHARACTER_EXTENSION,
+            SPType::SP_DD_L_MORE_CONTINUED,
+        ),
+        Column::Right => (
+            SPType::SP_DD_R_CHARACTER,
+            SPType::SP_DD_R_CHARACTER_EXTENSION,
+            SPType::SP_DD_R_MORE_CONTINUED,
+        ),
+    };
+    let mut in_extension = false;
+    run.iter()
+        .map(|(idx, pdf_word)| {
+            if pdf_word.text.starts_with('(') {
+                in_extension = true;
+            }
+            let word_type = if is_more_or_continued(&pdf_word.text) {
+                more_continued_type
+            } else if in_extension {
+                extension_type
+            } else {
+                character_type
+            };
+            (*idx, word_type)
+        })
+        .collect()
+}
+
+impl DualDialogueState {
+    /// Types the words of a line of dialogue or parentheticals within the block.
+    fn get_dialogue_types(&mut self, run: &[(usize, &pdf_document::Word)]) -> Vec<(usize, SPType)> {
+        let Some((_, first_word)) = run.first() else {
+            return Vec::new();
+        };
+        let column = if first_word.position.x < self.split_x {
+            Column::Left
+        } else {
+            Column::Right
+        };
+        let (parenthetical_type, dialogue_type, more_continued_type, in_parenthetical) =
+            match column {
+                Column::Left => (
+                    SPType::SP_DD_L_PARENTHETICAL,
+                    SPType::SP_DD_L_DIALOGUE,
+                    SPType::SP_DD_L_MORE_CONTINUED,
+                    &mut self.left_in_parenthetical,
+                ),
+                Column::Right => (
+                    SPType::SP_DD_R_PARENTHETICAL,
+                    SPType::SP_DD_R_DIALOGUE,
+                    SPType::SP_DD_R_MORE_CONTINUED,
+                    &mut self.right_in_parenthetical,
+                ),
+            };
+
+        run.iter()
+            .map(|(idx, pdf_word)| {
+                if is_more_or_continued(&pdf_word.text) {
+                    return (*idx, more_continued_type);
+                }
+                if pdf_word.text.starts_with('(') {
+                    *in_parenthetical = true;
+                }
+                if *in_parenthetical {
+                    if pdf_word.text.ends_with(')') {
+                        *in_parenthetical = false;
+                    }
+                    return (*idx, parenthetical_type);
+                }
+                (*idx, dialogue_type)
+            })
+            .collect()
+    }
+}
+
+/// Gets the types of the words on a PDF line, if it's part of a dual dialogue block.
+///
+/// `dual_dialogue_state` keeps track of the current block between lines,
+/// and is reset once a line no longer belongs to the block.
+///
+/// Returns `None` if the line isn't part of a dual dialogue block. Otherwise, returns one
+/// type for each word in the line. Words outside of the content zone (i.e. scene numbers
+/// and revision markers) are left as `None`, to be typed as usual.
+pub(crate) fn get_dual_dialogue_types(
+    pdf_line: &pdf_document::Line,
+    dual_dialogue_state: &mut Option<DualDialogueState>,
+    element_indentations_pts: &ElementIndentationsPoints,
+) -> Option<Vec<Option<SPType>>> {
+    let runs = get_word_runs(pdf_line, element_indentations_pts);
+    let (_, first_word) = runs.first()?.first()?;
+    let line_y = first_word.position.y;
+    let mut word_types: Vec<Option<SPType>> = vec![None; pdf_line.words.len()];
+
+    // Continuing the current block
+    if let Some(state) = dual_dialogue_state {
+        let y_delta = state.last_line_y - line_y;
+        if y_delta > 0.0 && y_delta <= state.line_height * 1.5 {
+            state.last_line_y = line_y;
+            for run in &runs {
+                for (idx, word_type) in state.get_dialogue_types(run) {
+                    word_types[idx] = Some(word_type);
+                }
+            }
+            return Some(word_types);
+        }
+        *dual_dialogue_state = None;
+    }
+
+    // Starting a new block, with two character cues side by side
+    let [left_run, right_run] = runs.as_slice() else {
+        return None;
+    };
+    let (_, left_word) = left_run.first()?;
+    let (_, right_word) = right_run.first()?;
+    let min_left_cue_x =
+        element_indentations_pts.action + COLUMN_GAP_CHARS * _get_char_width(left_word);
+    if left_word.position.x <= min_left_cue_x
+        || !is_character_cue(left_run)
+        || !is_character_cue(right_run)
+    {
+        return None;
+    }
+
+    for (idx, word_type) in get_character_types(left_run, Column::Left)
+        .into_iter()
+        .chain(get_character_types(right_run, Column::Right))
+    {
+        word_types[idx] = Some(word_type);
+    }
+    *dual_dialogue_state = Some(DualDialogueState {
+        split_x: (left_word.position.x + right_word.position.x) / 2.0,
+        last_line_y: line_y,
+        line_height: left_word.font_size.max(1.0),
+        left_in_parenthetical: false,
+        right_in_parenthetical: false,
+    });
+    Some(word_types)
+}
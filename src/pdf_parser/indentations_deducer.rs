@@ -1 +1,419 @@
+//! Deduces the `ElementIndentationsInches` of a `PDFDocument` by clustering
+//! the x-positions of the first word of every line, and the y-positions of the
+//! lines that start on those clusters.
+//!
+//! Different screenwriting programs (Final Draft, Fade In, WriterDuet, Highland...)
+//! all use slightly different margins, so rather than hand-tuning indentations
+//! for each one, we just look at where the text actually sits on the page.
 
+use std::collections::HashMap;
+
+use crate::pdf_document::{self, ElementIndentationsInches};
+
+/// Words whose x-positions are within this many points of each other
+/// are considered to be on the same indentation.
+const CLUSTER_TOLERANCE_PTS: f64 = 2.0;
+
+/// A cluster must contain at least this fraction of all lines
+/// to be considered a "real" indentation, rather than noise.
+const MIN_CLUSTER_FREQUENCY: f64 = 0.02;
+
+/// Any gap between two words on the same line wider than this many characters
+/// means the following word is probably in the margin (scene numbers, revision markers...)
+const MARGIN_GAP_CHARS: f64 = 4.0;
+
+const DEFAULT_RESOLUTION: f64 = 72.0;
+
+#[derive(Default, Debug)]
+struct XCluster {
+    /// Exact x-positions (in hundredths of a point) and how often they occur
+    positions: HashMap<i64, usize>,
+    count: usize,
+    uppercase_count: usize,
+    parenthesis_count: usize,
+}
+impl XCluster {
+    /// The most common exact x-position in this cluster.
+    ///
+    /// The parser compares positions with a very small tolerance,
+    /// so we want the real position the PDF uses, not an average.
+    fn mode(&self) -> f64 {
+        let mut best: (i64, usize) = (0, 0);
+        for (pos, count) in &self.positions {
+            if *count > best.1 || (*count == best.1 && *pos < best.0) {
+                best = (*pos, *count);
+            }
+        }
+        best.0 as f64 / 100.0
+    }
+
+    fn min(&self) -> f64 {
+        self.positions.keys().min().copied().unwrap_or(0) as f64 / 100.0
+    }
+
+    fn max(&self) -> f64 {
+        self.positions.keys().max().copied().unwrap_or(0) as f64 / 100.0
+    }
+
+    fn contains(&self, x: f64) -> bool {
+        x >= self.min() - CLUSTER_TOLERANCE_PTS && x <= self.max() + CLUSTER_TOLERANCE_PTS
+    }
+
+    fn uppercase_ratio(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.uppercase_count as f64 / self.count as f64
+    }
+
+    fn parenthesis_ratio(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.parenthesis_count as f64 / self.count as f64
+    }
+}
+
+/// Scene numbers and revision markers sit in the left margin,
+/// so they shouldn't be counted as the first word of a content line.
+fn is_margin_word(word: &pdf_document::Word) -> bool {
+    let text = word.text.trim_matches('*').trim_matches('.');
+    if word.text.chars().all(|c| c == '*') {
+        return true;
+    }
+    if text.is_empty() || text.len() > 6 {
+        return false;
+    }
+    text.chars().any(|c| c.is_ascii_digit()) && text.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn is_uppercase_word(text: &str) -> bool {
+    let mut has_letter = false;
+    for c in text.chars() {
+        if c.is_lowercase() {
+            return false;
+        }
+        if c.is_alphabetic() {
+            has_letter = true;
+        }
+    }
+    has_letter
+}
+
+/// Page numbers are right-aligned in the header, and end with a period (`12.`, `12A.`)
+fn is_page_number_word(word: &pdf_document::Word, pagewidth: f64) -> bool {
+    word.position.x > pagewidth * 0.75
+        && word.text.ends_with('.')
+        && word.text.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn is_more_or_continued_line(line: &pdf_document::Line) -> bool {
+    line.words
+        .iter()
+        .any(|w| w.text.contains("(MORE)") || w.text.contains("CONTINUED"))
+}
+
+/// Returns the first word of a line that isn't a margin element, if any.
+fn first_content_word(line: &pdf_document::Line) -> Option<&pdf_document::Word> {
+    line.words.iter().find(|w| !is_margin_word(w))
+}
+
+fn cluster_first_words(pdfdoc: &pdf_document::PDFDocument) -> (Vec<XCluster>, usize) {
+    let mut first_words: Vec<&pdf_document::Word> = Vec::new();
+    for page in &pdfdoc.pages {
+        for ln in &page.lines {
+            if is_more_or_continued_line(ln) {
+                continue;
+            }
+            if let Some(word) = first_content_word(ln) {
+                first_words.push(word);
+            }
+        }
+    }
+    let lines_count = first_words.len();
+
+    first_words.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+
+    let mut clusters: Vec<XCluster> = Vec::new();
+    let mut last_x: Option<f64> = None;
+    for word in first_words {
+        let x = word.position.x;
+        let start_new_cluster = match last_x {
+            None => true,
+            Some(lx) => x - lx > CLUSTER_TOLERANCE_PTS,
+        };
+        if start_new_cluster {
+            clusters.push(XCluster::default());
+        }
+        let Some(cluster) = clusters.last_mut() else {
+            continue;
+        };
+        *cluster
+            .positions
+            .entry((x * 100.0).round() as i64)
+            .or_insert(0) += 1;
+        cluster.count += 1;
+        if is_uppercase_word(&word.text) {
+            cluster.uppercase_count += 1;
+        }
+        if word.text.starts_with('(') {
+            cluster.parenthesis_count += 1;
+        }
+        last_x = Some(x);
+    }
+
+    (clusters, lines_count)
+}
+
+/// The most common vertical distance between two consecutive lines.
+fn deduce_line_height(pdfdoc: &pdf_document::PDFDocument) -> f64 {
+    let mut deltas: HashMap<i64, usize> = HashMap::new();
+    for page in &pdfdoc.pages {
+        let mut prev_y: Option<f64> = None;
+        for ln in &page.lines {
+            let Some(word) = ln.words.first() else {
+                continue;
+            };
+            if let Some(py) = prev_y {
+                let delta = (py - word.position.y).abs();
+                if delta > 1.0 {
+                    *deltas.entry((delta * 10.0).round() as i64).or_insert(0) += 1;
+                }
+            }
+            prev_y = Some(word.position.y);
+        }
+    }
+    let mut best: (i64, usize) = (120, 0);
+    for (delta, count) in deltas {
+        if count > best.1 || (count == best.1 && delta < best.0) {
+            best = (delta, count);
+        }
+    }
+    best.0 as f64 / 10.0
+}
+
+/// Finds the x-position which separates content text from anything in the right-hand margin.
+///
+/// Content words are contiguous from the start of a line; a margin element
+/// (right-hand scene number, revision marker) comes after a wide gap.
+/// Dual dialogue columns are also separated by a wide gap, but contain words, not numbers.
+fn deduce_right_margin(
+    pdfdoc: &pdf_document::PDFDocument,
+    content_clusters: &[&XCluster],
+    char_width: f64,
+) -> Option<f64> {
+    let mut content_max: Option<f64> = None;
+    let mut margin_min: Option<f64> = None;
+
+    for page in &pdfdoc.pages {
+        for ln in &page.lines {
+            let Some(first) = first_content_word(ln) else {
+                continue;
+            };
+            if !content_clusters
+                .iter()
+                .any(|c| c.contains(first.position.x))
+            {
+                continue;
+            }
+            let mut in_margin = false;
+            let mut prev_right_edge: Option<f64> = None;
+            for word in &ln.words {
+                if word.position.x < first.position.x {
+                    continue;
+                }
+                // Text after a wide gap may also be the right column of a dual dialogue block
+                if let Some(edge) = prev_right_edge
+                    && word.position.x - edge > char_width * MARGIN_GAP_CHARS
+                {
+                    in_margin =
+                        is_margin_word(word) || !word.text.chars().any(|c| c.is_alphabetic());
+                }
+                if in_margin {
+                    margin_min =
+                        Some(margin_min.map_or(word.position.x, |m| m.min(word.position.x)));
+                } else {
+                    content_max =
+                        Some(content_max.map_or(word.position.x, |m| m.max(word.position.x)));
+                }
+                prev_right_edge = Some(word.position.x + word.bbox_width);
+            }
+        }
+    }
+
+    match (content_max, margin_min) {
+        (Some(c), Some(m)) if m > c => Some((c + m) / 2.0),
+        (Some(c), _) => Some(c + char_width),
+        _ => None,
+    }
+}
+
+/// Deduces the top and bottom margins from the y-positions of content lines
+/// and of the page-number header lines.
+fn deduce_vertical_margins(
+    pdfdoc: &pdf_document::PDFDocument,
+    content_clusters: &[&XCluster],
+    pagewidth: f64,
+    line_height: f64,
+) -> Option<(f64, f64)> {
+    let mut header_floor: Option<f64> = None;
+    for page in &pdfdoc.pages {
+        for ln in &page.lines {
+            if ln.words.iter().any(|w| is_page_number_word(w, pagewidth)) {
+                let Some(word) = ln.words.first() else {
+                    continue;
+                };
+                header_floor =
+                    Some(header_floor.map_or(word.position.y, |h| h.min(word.position.y)));
+            }
+        }
+    }
+
+    let mut content_top: Option<f64> = None;
+    let mut content_bottom: Option<f64> = None;
+    for page in &pdfdoc.pages {
+        for ln in &page.lines {
+            if is_more_or_continued_line(ln) {
+                continue;
+            }
+            let Some(first) = first_content_word(ln) else {
+                continue;
+            };
+            if !content_clusters
+                .iter()
+                .any(|c| c.contains(first.position.x))
+            {
+                continue;
+            }
+            let y = first.position.y;
+            if let Some(h) = header_floor
+                && y >= h
+            {
+                continue;
+            }
+            content_top = Some(content_top.map_or(y, |t| t.max(y)));
+            content_bottom = Some(content_bottom.map_or(y, |b| b.min(y)));
+        }
+    }
+
+    let (content_top, content_bottom) = (content_top?, content_bottom?);
+    let top = match header_floor {
+        Some(h) => (content_top + h) / 2.0,
+        None => content_top + line_height / 2.0,
+    };
+    let bottom = (content_bottom - line_height / 2.0).max(0.0);
+    Some((top, bottom))
+}
+
+/// Deduces the `ElementIndentationsInches` for a `PDFDocument`.
+///
+/// Returns `None` if the document has no text, or if no Action indentation can be found.
+///
+/// Any indentation which cannot be found (i.e. a script without parentheticals)
+/// falls back to the US-Letter default, offset relative to the deduced Action indentation.
+pub fn deduce_indentations(
+    pdfdoc: &pdf_document::PDFDocument,
+) -> Option<ElementIndentationsInches> {
+    // Anything which can't be deduced falls back to the defaults for this page format
+    let default_indentations = pdfdoc
+        .pages
+        .first()
+        .and_then(|p| p.page_size.get_page_format())
+        .map(|format| ElementIndentationsInches::default_for_page_format(&format))
+        .unwrap_or_else(ElementIndentationsInches::us_letter_default);
+    let resolution = DEFAULT_RESOLUTION;
+
+    let (clusters, lines_count) = cluster_first_words(pdfdoc);
+    if lines_count == 0 {
+        return None;
+    }
+
+    let significant: Vec<&XCluster> = clusters
+        .iter()
+        .filter(|c| c.count as f64 / lines_count as f64 >= MIN_CLUSTER_FREQUENCY)
+        .collect();
+
+    // Action is the left-most indentation that any real amount of text starts on
+    let action_cluster = *significant.first()?;
+    let action = action_cluster.mode();
+
+    let most_frequent_where = |predicate: &dyn Fn(&XCluster) -> bool| -> Option<&XCluster> {
+        significant
+            .iter()
+            .filter(|c| c.mode() > action + CLUSTER_TOLERANCE_PTS && predicate(c))
+            .max_by_key(|c| c.count)
+            .copied()
+    };
+
+    let parenthetical_cluster = most_frequent_where(&|c| c.parenthesis_ratio() > 0.8);
+    let character_cluster = most_frequent_where(&|c| {
+        c.uppercase_ratio() > 0.8
+            && c.parenthesis_ratio() < 0.5
+            && parenthetical_cluster.is_none_or(|p| c.mode() > p.mode())
+    });
+    let dialogue_cluster = most_frequent_where(&|c| {
+        c.parenthesis_ratio() < 0.5
+            && character_cluster.is_none_or(|ch| c.mode() < ch.mode())
+            && parenthetical_cluster.is_none_or(|p| c.mode() < p.mode())
+    });
+
+    let relative_to_action =
+        |default: f64| action + (default - default_indentations.action) * resolution;
+
+    let character = character_cluster
+        .map(|c| c.mode())
+        .unwrap_or_else(|| relative_to_action(default_indentations.character));
+    let dialogue = dialogue_cluster
+        .map(|c| c.mode())
+        .unwrap_or_else(|| relative_to_action(default_indentations.dialogue));
+    let parenthetical = parenthetical_cluster
+        .map(|c| c.mode())
+        .unwrap_or_else(|| relative_to_action(default_indentations.parenthetical));
+
+    let (pagewidth, pageheight) = match pdfdoc.pages.first() {
+        Some(p) if p.page_size.width > 0.0 && p.page_size.height > 0.0 => {
+            (p.page_size.width, p.page_size.height)
+        }
+        _ => (
+            default_indentations.pagewidth * resolution,
+            default_indentations.pageheight * resolution,
+        ),
+    };
+
+    let char_width = pdfdoc
+        .pages
+        .iter()
+        .flat_map(|p| p.lines.iter())
+        .flat_map(|l| l.words.first())
+        .map(|w| w.font_size * 0.6)
+        .next()
+        .unwrap_or(7.2);
+
+    let mut content_clusters: Vec<&XCluster> = vec![action_cluster];
+    content_clusters.extend(character_cluster);
+    content_clusters.extend(dialogue_cluster);
+    content_clusters.extend(parenthetical_cluster);
+
+    let right = deduce_right_margin(pdfdoc, &content_clusters, char_width)
+        .unwrap_or(default_indentations.right * resolution);
+
+    let line_height = deduce_line_height(pdfdoc);
+    let (top, bottom) = deduce_vertical_margins(pdfdoc, &content_clusters, pagewidth, line_height)
+        .unwrap_or((
+            default_indentations.top * resolution,
+            default_indentations.bottom * resolution,
+        ));
+
+    Some(ElementIndentationsInches {
+        pagewidth: pagewidth / resolution,
+        pageheight: pageheight / resolution,
+        left: action / resolution,
+        right: right / resolution,
+        top: top / resolution,
+        bottom: bottom / resolution,
+        action: action / resolution,
+        character: character / resolution,
+        dialogue: dialogue / resolution,
+        parenthetical: parenthetical / resolution,
+    })
+}
@@ -0,0 +1,130 @@
+//! Detection of the title page of a PDF document.
+//!
+//! A title page has no page number and no scene headings, and starts with a
+//! centered line (the title). Every line is then typed by its text and alignment,
+//! the same way as an FDX title page.
+
+use super::{_get_char_width, _get_environment_phrase, _get_word_end_x};
+use crate::parser_common;
+use crate::pdf_document::{self, ElementIndentationsPoints};
+use crate::screenplay_document::{self, Environment, EnvironmentStrings, SPType};
+
+/// A line is centered if its center is within this many character widths of the page center.
+const CENTERED_TOLERANCE_CHARS: f64 = 2.0;
+
+fn is_centered(pdf_line: &pdf_document::Line, pagewidth: f64) -> bool {
+    let (Some(first), Some(last)) = (pdf_line.words.first(), pdf_line.words.last()) else {
+        return false;
+    };
+    let center = (first.position.x + _get_word_end_x(last)) / 2.0;
+    (center - pagewidth / 2.0).abs() <= CENTERED_TOLERANCE_CHARS * _get_char_width(first)
+}
+
+fn is_title_page(
+    pdf_page: &pdf_document::Page,
+    element_indentaions_pts: &ElementIndentationsPoints,
+    environment_strs: &EnvironmentStrings,
+) -> bool {
+    let has_page_number = pdf_page.lines.iter().flat_map(|l| &l.words).any(|w| {
+        w.position.y >= element_indentaions_pts.top
+            && w.position.x > element_indentaions_pts.pagewidth * 0.75
+            && w.text.ends_with('.')
+            && w.text.starts_with(|c: char| c.is_ascii_digit())
+    });
+    let has_scene_heading = pdf_page.lines.iter().any(|l| {
+        l.words
+            .iter()
+            .find(|w| w.position.x >= element_indentaions_pts.left)
+            .is_some_and(|w| Environment::from_str(&w.text, environment_strs).is_some())
+            || _get_environment_phrase(l, element_indentaions_pts, environment_strs).is_some()
+    });
+    if has_page_number || has_scene_heading {
+        return false;
+    }
+    pdf_page
+        .lines
+        .iter()
+        .find(|l| !l.words.is_empty())
+        .is_some_and(|l| is_centered(l, element_indentaions_pts.pagewidth))
+}
+
+/// Parses a PDF page as a title page, with every line typed as a title page element.
+///
+/// Returns `None` if the page doesn't look like a title page.
+pub(crate) fn get_title_page(
+    pdf_page: &pdf_document::Page,
+    element_indentaions_pts: &ElementIndentationsPoints,
+    environment_strs: &EnvironmentStrings,
+) -> Option<screenplay_document::Page> {
+    if !is_title_page(pdf_page, element_indentaions_pts, environment_strs) {
+        return None;
+    }
+
+    let mut title_page = screenplay_document::Page::default();
+    let mut previous_type: Option<SPType> = None;
+    let mut prev_line_y_pos: Option<f64> = None;
+
+    for pdf_line in &pdf_page.lines {
+        let Some(first_word) = pdf_line.words.first() else {
+            continue;
+        };
+        let line_height = first_word.font_size.max(1.0);
+        let preceding_empty_lines = match prev_line_y_pos {
+            Some(prev_y) => {
+                ((prev_y - first_word.position.y) / line_height)
+                    .round()
+                    .max(1.0) as u64
+                    - 1
+            }
+            None => 0,
+        };
+        prev_line_y_pos = Some(first_word.position.y);
+
+        let text: Vec<&str> = pdf_line.words.iter().map(|w| w.text.as_str()).collect();
+        let text = text.join(" ");
+        let centered = is_centered(pdf_line, element_indentaions_pts.pagewidth);
+        let has_title = title_page
+            .lines
+            .iter()
+            .any(|l| l.line_type == Some(SPType::TP_TITLE));
+
+        // Long titles wrap onto multiple lines
+        let line_type =
+            if centered && previous_type == Some(SPType::TP_TITLE) && preceding_empty_lines == 0 {
+                SPType::TP_TITLE
+            } else {
+                parser_common::get_title_page_line_type(&text, centered, previous_type, has_title)
+            };
+
+        let mut new_line = screenplay_document::Line {
+            line_type: Some(line_type),
+            preceding_empty_lines,
+            ..Default::default()
+        };
+        let mut previous_end_x: Option<f64> = None;
+        for pdf_word in &pdf_line.words {
+            let preceding_whitespace_chars = match previous_end_x {
+                Some(end_x) => ((pdf_word.position.x - end_x) / _get_char_width(pdf_word))
+                    .round()
+                    .max(1.0) as u64,
+                None => 0,
+            };
+            new_line
+                .text_elements
+                .push(screenplay_document::TextElement {
+                    text: pdf_word.text.clone(),
+                    element_type: Some(line_type),
+                    preceding_whitespace_chars,
+                    element_position: Some(pdf_word.position),
+                });
+            previous_end_x = Some(_get_word_end_x(pdf_word));
+        }
+        title_page.lines.push(new_line);
+        previous_type = Some(line_type);
+    }
+
+    if title_page.lines.is_empty() {
+        return None;
+    }
+    Some(title_page)
+}
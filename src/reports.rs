@@ -1,6 +1,6 @@
-use std::{
-    collections::{HashMap, HashSet},
-};
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
 
 use crate::screenplay_document::{self, SPType};
 
@@ -14,7 +14,7 @@ use crate::screenplay_document::{self, SPType};
 pub fn get_full_location_path_for_leaf_node<'a>(
     screenplay_doc: &'a crate::screenplay_document::ScreenplayDocument,
     location: &'a screenplay_document::LocationID,
-) -> Option<(Vec<&'a screenplay_document::LocationID>)> {
+) -> Option<Vec<&'a screenplay_document::LocationID>> {
     let mut current_location_node_id: &screenplay_document::LocationID = location;
     let mut id_path: Vec<&screenplay_document::LocationID> = vec![current_location_node_id];
     loop {
@@ -62,6 +62,23 @@ pub fn get_full_string_for_location_path<'a>(
     Some(path_string)
 }
 
+/// Returns the full Location path of every side of a scene's heading as one string,
+/// joined by ` / `, i.e. `INT. CAR / EXT. ROAD`.
+pub fn get_full_string_for_scene_locations(
+    screenplay_doc: &crate::screenplay_document::ScreenplayDocument,
+    scene: &screenplay_document::Scene,
+) -> Option<String> {
+    let paths: Vec<String> = scene
+        .story_locations
+        .iter()
+        .filter_map(|id| get_full_string_for_location_path(screenplay_doc, id))
+        .collect();
+    if paths.is_empty() {
+        return None;
+    }
+    Some(paths.join(" / "))
+}
+
 /// Determines if a "location path" exists.
 ///
 /// Returns `None` if nothing matches the root of the path.
@@ -95,7 +112,7 @@ pub fn location_path_exists<'a>(
                 return Some((id, Vec::from(&path[1..])));
             }
 
-            return location.subpath_exists(&id, &path[1..], screenplay_document);
+            return location.subpath_exists(id, &path[1..], screenplay_document);
         }
     }
 
@@ -129,22 +146,20 @@ pub fn get_location_root_for_node<'a>(
     let Some(superlocation) = &location.superlocation else {
         return Some(location_id);
     };
-    return get_location_root_for_node(screenplay_document, &superlocation);
+    get_location_root_for_node(screenplay_document, superlocation)
 }
 
 pub fn get_all_location_leafs<'a>(
     screenplay_document: &'a screenplay_document::ScreenplayDocument,
     location_id: &'a screenplay_document::LocationID,
 ) -> Option<Vec<&'a screenplay_document::LocationID>> {
-    let Some(location) = screenplay_document.locations.get(location_id) else {
-        return None;
-    };
+    let location = screenplay_document.locations.get(location_id)?;
     if location.sublocations.is_empty() {
         return Some(vec![location_id]);
     }
     let mut location_id_set: HashSet<&screenplay_document::LocationID> = HashSet::new();
     for sublocation_id in &location.sublocations {
-        let Some(subset) = get_all_location_leafs(screenplay_document, &sublocation_id) else {
+        let Some(subset) = get_all_location_leafs(screenplay_document, sublocation_id) else {
             continue;
         };
         location_id_set.extend(subset);
@@ -166,7 +181,7 @@ pub fn filter_locations_by_character_speaking<'a>(
 
     scenes_with_character
         .iter()
-        .map(|(s, scn)| &scn.story_locations)
+        .map(|(_s, scn)| &scn.story_locations)
         .for_each(|lcv| {
             //TODO: filter this by ones that
             lcv.iter().for_each(|lc| {
@@ -187,7 +202,7 @@ pub fn get_all_locations_with_character_speaking<'a>(
     character: &'a screenplay_document::Character,
 ) -> Option<Vec<&'a screenplay_document::LocationID>> {
     let all_locations = &screenplay_document.locations;
-    let all_locations_vec: Vec<_> = all_locations.iter().map(|(id, scn)| id).collect();
+    let all_locations_vec: Vec<_> = all_locations.keys().collect();
 
     filter_locations_by_character_speaking(screenplay_document, all_locations_vec, character)
 }
@@ -197,10 +212,7 @@ pub fn filter_locations_by_page_idx<'a>(
     locations: Vec<&'a screenplay_document::LocationID>,
     page: usize,
 ) -> Option<Vec<&'a screenplay_document::LocationID>> {
-    let Some(locations_on_page) = get_all_locations_on_page_by_index(screenplay_document, page)
-    else {
-        return None;
-    };
+    let locations_on_page = get_all_locations_on_page_by_index(screenplay_document, page)?;
 
     let mut filtered_locations: Vec<&screenplay_document::LocationID> = Vec::new();
 
@@ -224,7 +236,7 @@ pub fn get_all_locations_on_page_by_index(
     let scenes = get_all_scenes_on_page_by_index(screenplay_document, page)?;
     scenes
         .iter()
-        .filter_map(|(scene_id, scene)| {
+        .filter_map(|(_scene_id, scene)| {
             if scene.story_locations.is_empty() {
                 return None;
             }
@@ -248,26 +260,88 @@ pub fn get_all_locations_on_page_by_index(
 // A) HashMap<ScreenplayCoordinate, &Line>
 // B) Line
 
+/// Gets every line of a scene, from its heading to its last line, with their coordinates.
+pub fn get_all_lines_for_scene<'a>(
+    screenplay_document: &'a screenplay_document::ScreenplayDocument,
+    scene_id: &screenplay_document::SceneID,
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
+> {
+    let scene = screenplay_document.scenes.get(scene_id)?;
+    let mut lines: Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )> = Vec::new();
+    'pages: for (p_idx, page) in screenplay_document
+        .pages
+        .iter()
+        .enumerate()
+        .skip(scene.start.page)
+    {
+        for (l_idx, line) in page.lines.iter().enumerate() {
+            if (p_idx, l_idx) < (scene.start.page, scene.start.line) {
+                continue;
+            }
+            let past_end = match &scene.end {
+                Some(end) => (p_idx, l_idx) > (end.page, end.line),
+                None => {
+                    line.line_type
+                        == Some(SPType::SP_SCENE_HEADING(
+                            screenplay_document::SceneHeadingElement::Line,
+                        ))
+                        && (p_idx, l_idx) != (scene.start.page, scene.start.line)
+                }
+            };
+            if past_end {
+                break 'pages;
+            }
+            lines.push((
+                screenplay_document::ScreenplayCoordinate {
+                    page: p_idx,
+                    line: l_idx,
+                    element: None,
+                },
+                line,
+            ));
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines)
+}
+
 pub fn filter_lines_by_multiple_scenes<'a>(
     screenplay_document: &crate::screenplay_document::ScreenplayDocument,
-    lines: &Vec<(screenplay_document::ScreenplayCoordinate, &'a screenplay_document::Line)>,
+    lines: &Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
     scenes_filter: Vec<(&screenplay_document::SceneID, &screenplay_document::Scene)>,
-) -> Option<Vec<(screenplay_document::ScreenplayCoordinate, &'a screenplay_document::Line)>> {
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
+> {
     if scenes_filter.is_empty() {
         //panic!("EMPTY FILTER!");
         return None;
     }
 
-    let mut scn_filtered_lines: Vec<
-        (screenplay_document::ScreenplayCoordinate,
-        &screenplay_document::Line)
-    > = Vec::new();
+    let mut scn_filtered_lines: Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )> = Vec::new();
 
     lines
         .iter()
-        .filter(|(coord, ln)| {
-            let Some((scene_id, scene)) =
-                get_scene_for_screenplay_coordinate(screenplay_document, &coord)
+        .filter(|(coord, _ln)| {
+            let Some((scene_id, _scene)) =
+                get_scene_for_screenplay_coordinate(screenplay_document, coord)
             else {
                 return false;
             };
@@ -295,32 +369,63 @@ pub fn filter_lines_by_multiple_scenes<'a>(
 
 pub fn filter_lines_by_multiple_locations<'a>(
     screenplay_doc: &'a screenplay_document::ScreenplayDocument,
-    lines: &Vec<(screenplay_document::ScreenplayCoordinate, &'a screenplay_document::Line)>,
+    lines: &Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
     locations_filter: Vec<&screenplay_document::LocationID>,
-) -> Option<Vec<(screenplay_document::ScreenplayCoordinate, &'a screenplay_document::Line)>> {
-    let Some(scenes_ordered) = get_all_scenes_ordered(screenplay_doc) else {
-        return None;
-    };
-    let Some(scenes_filtered) =
-        filter_scenes_by_locations(screenplay_doc, scenes_ordered, locations_filter)
-    else {
-        return None;
-    };
-    return filter_lines_by_multiple_scenes(screenplay_doc, lines, scenes_filtered);
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
+> {
+    let scenes_ordered = get_all_scenes_ordered(screenplay_doc)?;
+    let scenes_filtered =
+        filter_scenes_by_locations(screenplay_doc, scenes_ordered, locations_filter)?;
+    filter_lines_by_multiple_scenes(screenplay_doc, lines, scenes_filtered)
 }
 
-
+/// Gets the dialogue type of the column of a dual dialogue character line
+/// which belongs to this character, if any.
+fn get_dual_dialogue_type_for_character(
+    line: &screenplay_document::Line,
+    character: &screenplay_document::Character,
+) -> Option<SPType> {
+    [
+        (SPType::SP_DD_L_CHARACTER, SPType::SP_DD_L_DIALOGUE),
+        (SPType::SP_DD_R_CHARACTER, SPType::SP_DD_R_DIALOGUE),
+    ]
+    .into_iter()
+    .find(|(character_type, _)| {
+        let name: Vec<&str> = line
+            .text_elements
+            .iter()
+            .filter(|te| te.element_type == Some(*character_type))
+            .map(|te| te.text.as_str())
+            .collect();
+        name.join(" ") == character.name
+    })
+    .map(|(_, dialogue_type)| dialogue_type)
+}
 
 pub fn get_all_lines_of_dialogue_for_character<'a>(
     screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
     character: &'a screenplay_document::Character,
-) -> Option<Vec<(screenplay_document::ScreenplayCoordinate, &'a screenplay_document::Line)>> {
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
+> {
     let mut lines_with_coords: Vec<(
         screenplay_document::ScreenplayCoordinate,
-        &screenplay_document::Line)
-    > = Vec::new();
+        &screenplay_document::Line,
+    )> = Vec::new();
     //let mut lines: Vec<&Line> = Vec::new();
-    let mut is_dialogue = false;
+    // The dialogue type of the current block; a dual dialogue block only
+    // belongs to the character in one of its columns
+    let mut dialogue_type: Option<SPType> = None;
 
     for (p_index, page) in screenplay_document.pages.iter().enumerate() {
         for (l_index, line) in page.lines.iter().enumerate() {
@@ -332,27 +437,52 @@ pub fn get_all_lines_of_dialogue_for_character<'a>(
                 continue;
             }
             //println!("MIGHT BE CHARACTER OR DIALOGUE");
-            if character.is_line(line) {
-                //println!("Oh boy!!!! | {:?} | {:?}", line.line_type, line.text_elements);
-                is_dialogue = true;
-                continue;
+            match line.line_type {
+                Some(SPType::SP_CHARACTER) => {
+                    dialogue_type = character.is_line(line).then_some(SPType::SP_DIALOGUE);
+                    continue;
+                }
+                Some(SPType::SP_DUAL_CHARACTERS) => {
+                    dialogue_type = get_dual_dialogue_type_for_character(line, character);
+                    continue;
+                }
+                _ => {}
             }
-            if !is_dialogue {
+            let Some(current_dialogue_type) = dialogue_type else {
                 continue;
-            }
-            match line.line_type {
-                Some(SPType::SP_DIALOGUE) | Some(SPType::SP_DUAL_DIALOGUES) => {
-                    lines_with_coords.push(
-                        (screenplay_document::ScreenplayCoordinate {
+            };
+            match (line.line_type, current_dialogue_type) {
+                (Some(SPType::SP_DIALOGUE), SPType::SP_DIALOGUE) => {
+                    lines_with_coords.push((
+                        screenplay_document::ScreenplayCoordinate {
                             page: p_index,
                             line: l_index,
                             element: None,
                         },
-                        line)
-                    );
+                        line,
+                    ));
+                }
+                (Some(SPType::SP_DUAL_DIALOGUES), SPType::SP_DD_L_DIALOGUE)
+                | (Some(SPType::SP_DUAL_DIALOGUES), SPType::SP_DD_R_DIALOGUE) => {
+                    // Lines from the other column are skipped, but don't end the block
+                    let Some(e_index) = line
+                        .text_elements
+                        .iter()
+                        .position(|te| te.element_type == Some(current_dialogue_type))
+                    else {
+                        continue;
+                    };
+                    lines_with_coords.push((
+                        screenplay_document::ScreenplayCoordinate {
+                            page: p_index,
+                            line: l_index,
+                            element: Some(e_index as u64),
+                        },
+                        line,
+                    ));
                 }
                 _ => {
-                    is_dialogue = false;
+                    dialogue_type = None;
                     continue;
                 }
             }
@@ -364,6 +494,137 @@ pub fn get_all_lines_of_dialogue_for_character<'a>(
     Some(lines_with_coords)
 }
 
+/// Gets all transitions (`CUT TO:`, `FADE OUT.`, etc.) in the document, in document order.
+pub fn get_all_transitions(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )>,
+> {
+    let mut lines_with_coords: Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )> = Vec::new();
+    for (p_index, page) in screenplay_document.pages.iter().enumerate() {
+        for (l_index, line) in page.lines.iter().enumerate() {
+            if line.line_type != Some(SPType::SP_TRANSITION) {
+                continue;
+            }
+            lines_with_coords.push((
+                screenplay_document::ScreenplayCoordinate {
+                    page: p_index,
+                    line: l_index,
+                    element: None,
+                },
+                line,
+            ));
+        }
+    }
+    if lines_with_coords.is_empty() {
+        return None;
+    }
+    Some(lines_with_coords)
+}
+
+/// Gets all transitions within a scene, in document order.
+///
+/// A transition belongs to the scene it follows, so a `FADE IN:` before
+/// the first scene heading doesn't belong to any scene.
+pub fn get_transitions_for_scene<'a>(
+    screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
+    scene_id: &screenplay_document::SceneID,
+) -> Option<
+    Vec<(
+        screenplay_document::ScreenplayCoordinate,
+        &'a screenplay_document::Line,
+    )>,
+> {
+    let scene = screenplay_document.scenes.get(scene_id)?;
+    let transitions = get_all_transitions(screenplay_document)?;
+    filter_lines_by_multiple_scenes(screenplay_document, &transitions, vec![(scene_id, scene)])
+}
+
+/// Gets the transition a scene ends on, i.e. `CUT TO:`.
+///
+/// Returns `None` if the last line of content in the scene isn't a transition.
+pub fn get_ending_transition_for_scene<'a>(
+    screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
+    scene_id: &screenplay_document::SceneID,
+) -> Option<(
+    screenplay_document::ScreenplayCoordinate,
+    &'a screenplay_document::Line,
+)> {
+    let scene = screenplay_document.scenes.get(scene_id)?;
+    let mut last_content_line: Option<(
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )> = None;
+
+    'pages: for (p_index, page) in screenplay_document
+        .pages
+        .iter()
+        .enumerate()
+        .skip(scene.start.page)
+    {
+        for (l_index, line) in page.lines.iter().enumerate() {
+            if p_index == scene.start.page && l_index <= scene.start.line {
+                continue;
+            }
+            match line.line_type {
+                Some(SPType::SP_SCENE_HEADING(screenplay_document::SceneHeadingElement::Line)) => {
+                    break 'pages;
+                }
+                None
+                | Some(SPType::NONE)
+                | Some(SPType::SP_PAGE_HEADER)
+                | Some(SPType::SP_MORE_CONTINUED) => {}
+                _ if line.blank => {}
+                _ => {
+                    last_content_line = Some((
+                        screenplay_document::ScreenplayCoordinate {
+                            page: p_index,
+                            line: l_index,
+                            element: None,
+                        },
+                        line,
+                    ));
+                }
+            }
+        }
+    }
+
+    let (coord, line) = last_content_line?;
+    if line.line_type != Some(SPType::SP_TRANSITION) {
+        return None;
+    }
+    Some((coord, line))
+}
+
+/// Gets the ending transition of every scene which ends on one, in document order.
+pub fn get_all_ending_transitions(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<
+    Vec<(
+        &screenplay_document::SceneID,
+        screenplay_document::ScreenplayCoordinate,
+        &screenplay_document::Line,
+    )>,
+> {
+    let ending_transitions: Vec<_> = get_all_scenes_ordered(screenplay_document)?
+        .into_iter()
+        .filter_map(|(scene_id, _)| {
+            let (coord, line) = get_ending_transition_for_scene(screenplay_document, scene_id)?;
+            Some((scene_id, coord, line))
+        })
+        .collect();
+    if ending_transitions.is_empty() {
+        return None;
+    }
+    Some(ending_transitions)
+}
+
 // ------------ Get CHARACTERS...
 // All returns should be Vec<&Character>.
 // TODO: Filter Characters by Scenes they speak in,
@@ -390,12 +651,14 @@ pub fn filter_characters_by_scene<'a>(
                 == Some(SPType::SP_SCENE_HEADING(
                     screenplay_document::SceneHeadingElement::Line,
                 ))
+                && (current_page, l_idx) > (scn.start.page, scn.start.line)
             {
-                if (current_page, l_idx) > (scn.start.page, scn.start.line) {
-                    break 'seeking;
-                }
+                break 'seeking;
             }
-            if line.line_type != Some(SPType::SP_CHARACTER) {
+            if !matches!(
+                line.line_type,
+                Some(SPType::SP_CHARACTER) | Some(SPType::SP_DUAL_CHARACTERS)
+            ) {
                 continue 'lines;
             }
             if characters_in_scene.len() == characters.len() {
@@ -403,7 +666,7 @@ pub fn filter_characters_by_scene<'a>(
             }
             for character in characters {
                 if character.is_line(line) {
-                    characters_in_scene.insert(&character);
+                    characters_in_scene.insert(character);
                 }
             }
         }
@@ -431,9 +694,7 @@ pub fn filter_characters_by_page_index<'a>(
 ) -> Option<Vec<&'a screenplay_document::Character>> {
     let mut characters_on_page: HashSet<&screenplay_document::Character> = HashSet::new();
 
-    let Some(page) = screenplay_document.pages.get(page_index) else {
-        return None;
-    };
+    let page = screenplay_document.pages.get(page_index)?;
     'lines: for line in &page.lines {
         if line.line_type != Some(SPType::SP_CHARACTER) {
             continue 'lines;
@@ -461,7 +722,7 @@ pub fn get_all_characters_on_page_by_index(
 ) -> Option<Vec<&screenplay_document::Character>> {
     let all_characters = screenplay_document.characters.iter().collect();
     //let characters_as_vec = all_characters
-    filter_characters_by_page_index(&screenplay_document, all_characters, page_index)
+    filter_characters_by_page_index(screenplay_document, all_characters, page_index)
 }
 
 // TODO: This function is wrong and bad???
@@ -474,10 +735,7 @@ pub fn filter_characters_by_location<'a>(
     location_id: &'a screenplay_document::LocationID,
 ) -> Option<Vec<&'a screenplay_document::Character>> {
     let mut characters_at_this_location: HashSet<&screenplay_document::Character> = HashSet::new();
-    let Some(scenes_with_location) = get_scenes_with_location(screenplay_document, location_id)
-    else {
-        return None;
-    };
+    let scenes_with_location = get_scenes_with_location(screenplay_document, location_id)?;
     for (scene_id, _) in scenes_with_location {
         let Some(new_character_matches) =
             filter_characters_by_scene(screenplay_document, &characters_to_filter, scene_id)
@@ -501,13 +759,7 @@ pub fn get_characters_for_location<'a>(
     location_id: &'a screenplay_document::LocationID,
 ) -> Option<Vec<&'a screenplay_document::Character>> {
     let all_characters = screenplay_document.characters.iter().collect();
-    let Some(thing) = filter_characters_by_location(
-        screenplay_document,
-        all_characters,
-        location_id,
-    ) else {
-        return None;
-    };
+    let thing = filter_characters_by_location(screenplay_document, all_characters, location_id)?;
     if thing.is_empty() {
         return None;
     };
@@ -539,10 +791,8 @@ pub fn filter_scenes_by_locations<'a>(
 
         for (scene, _) in scenes_for_loc {
             //println!("----- FILTERING BY LOCATION-SCENE....");
-            for (scn_id, scn) in &filtered {
-                if *scn_id == scene {
-                    continue;
-                }
+            if filtered.iter().any(|(scn_id, _)| *scn_id == scene) {
+                continue;
             }
             for (id, scn) in &scenes_to_filter {
                 if *id == scene {
@@ -557,7 +807,6 @@ pub fn filter_scenes_by_locations<'a>(
     Some(filtered)
 }
 
-
 /// Returns a Vec<(&SceneID, &Scene)>, in document order.
 ///
 /// # Example
@@ -568,19 +817,31 @@ pub fn filter_scenes_by_locations<'a>(
 /// let mut doc = ScreenplayDocument::new();
 /// let scene1: Scene = Scene {
 ///     start: ScreenplayCoordinate {page: 0 as usize, line: 10 as usize, element: None},
+///     end: None,
+///     length_eighths: None,
 ///     number: None,
 ///     revised: false,
+///     omitted: false,
 ///     environment: Environment::Int,
 ///     story_locations: vec!(LocationID::default()),
-///     story_time_of_day: None
+///     story_time_of_day: None,
+///     continuity: None,
+///     time_period: None,
+///     story_day: None
 /// };
 /// let scene2: Scene = Scene {
 ///     start: ScreenplayCoordinate {page: 1 as usize, line: 5 as usize, element: None},
+///     end: None,
+///     length_eighths: None,
 ///     number: None,
 ///     revised: false,
+///     omitted: false,
 ///     environment: Environment::Int,
 ///     story_locations: vec!(LocationID::default()),
-///     story_time_of_day: None
+///     story_time_of_day: None,
+///     continuity: None,
+///     time_period: None,
+///     story_day: None
 /// };
 /// let id_1 = SceneID::new();
 /// let id_2 = SceneID::new();
@@ -593,37 +854,22 @@ pub fn filter_scenes_by_locations<'a>(
 /// assert_eq!(*sorted.get(1).unwrap().0, id_2)
 /// ```
 ///
-pub fn get_all_scenes_ordered<'a>(
-    screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
-) -> Option<
-    Vec<(
-        &'a screenplay_document::SceneID,
-        &'a screenplay_document::Scene,
-    )>,
-> {
-    if screenplay_document.scenes.len() == 0 {
+pub fn get_all_scenes_ordered(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(&screenplay_document::SceneID, &screenplay_document::Scene)>> {
+    if screenplay_document.scenes.is_empty() {
         return None;
     }
     if screenplay_document.scenes.len() == 1 {
-        return Some(
-            screenplay_document
-                .scenes
-                .iter()
-                .map(|(id, scn)| (id, scn))
-                .collect(),
-        );
+        return Some(screenplay_document.scenes.iter().collect());
     }
-    let mut all_scenes: Vec<_> = screenplay_document
-        .scenes
-        .iter()
-        .map(|(a, b)| (a, b))
-        .collect();
+    let mut all_scenes: Vec<_> = screenplay_document.scenes.iter().collect();
 
-    all_scenes.sort_by(|(a_id, a_scn), (b_id, b_scn)| {
+    all_scenes.sort_by(|(_a_id, a_scn), (_b_id, b_scn)| {
         (a_scn.start.page, a_scn.start.line).cmp(&(b_scn.start.page, b_scn.start.line))
     });
 
-    return Some(all_scenes);
+    Some(all_scenes)
 }
 
 pub fn get_scene_for_screenplay_coordinate<'a>(
@@ -663,15 +909,9 @@ pub fn get_scene_for_screenplay_coordinate<'a>(
     // couldn't find the scene on this page. try the previous page...
     // recursively check all previous pages
 
-    let Some(previous_page_idx) = checked_coordinate.page.checked_sub(1) else {
-        return None;
-    };
-    let Some(previous_page) = screenplay_document.pages.get(previous_page_idx) else {
-        return None;
-    };
-    let Some(last_line_idx) = previous_page.lines.len().checked_sub(1) else {
-        return None;
-    };
+    let previous_page_idx = checked_coordinate.page.checked_sub(1)?;
+    let previous_page = screenplay_document.pages.get(previous_page_idx)?;
+    let last_line_idx = previous_page.lines.len().checked_sub(1)?;
     let last_page_last_line_coord = screenplay_document::ScreenplayCoordinate {
         page: previous_page_idx,
         line: last_line_idx,
@@ -688,7 +928,7 @@ pub fn get_scene_for_screenplay_coordinate<'a>(
         return None;
     };
     //println!("SUCCESS PART TWO...?");
-    return Some(scene);
+    Some(scene)
 }
 
 // This may be a contender for optimization....
@@ -702,12 +942,8 @@ pub fn get_scenes_from_range<'a>(
         &'a screenplay_document::Scene,
     )>,
 > {
-    if (screenplay_document.pages.get(start.page)).is_none() {
-        return None;
-    }
-    if screenplay_document.pages.get(end.page).is_none() {
-        return None;
-    }
+    (screenplay_document.pages.get(start.page))?;
+    screenplay_document.pages.get(end.page)?;
 
     let mut scenes_in_range: Vec<(&screenplay_document::SceneID, &screenplay_document::Scene)> =
         Vec::new();
@@ -742,7 +978,7 @@ pub fn get_scenes_from_range<'a>(
             ) else {
                 continue;
             };
-            for (scn_id, scn) in &scenes_in_range {
+            for (scn_id, _scn) in &scenes_in_range {
                 if scene_id == *scn_id {
                     continue;
                 }
@@ -775,7 +1011,7 @@ pub fn filter_scenes_by_page_index<'a>(
     if page.lines.is_empty() {
         return None;
     }
-    let last_line_idx = page.lines.len().checked_sub(1).unwrap_or(0);
+    let last_line_idx = page.lines.len().saturating_sub(1);
     let start = screenplay_document::ScreenplayCoordinate {
         page: page_index,
         line: 0,
@@ -786,13 +1022,11 @@ pub fn filter_scenes_by_page_index<'a>(
         line: last_line_idx,
         element: None,
     };
-    let Some(scenes_on_page) = get_scenes_from_range(screenplay_document, &start, &end) else {
-        return None;
-    };
+    let scenes_on_page = get_scenes_from_range(screenplay_document, &start, &end)?;
 
     let filtered: Vec<_> = scenes_on_page
         .iter()
-        .filter(|s| scenes_to_filter.contains(&s))
+        .filter(|s| scenes_to_filter.contains(s))
         .copied()
         .collect();
 
@@ -803,9 +1037,7 @@ pub fn get_all_scenes_on_page_by_index(
     screenplay_document: &crate::screenplay_document::ScreenplayDocument,
     page_index: usize,
 ) -> Option<Vec<(&screenplay_document::SceneID, &screenplay_document::Scene)>> {
-    let Some(all_scenes_ordered) = get_all_scenes_ordered(screenplay_document) else {
-        return None;
-    };
+    let all_scenes_ordered = get_all_scenes_ordered(screenplay_document)?;
     filter_scenes_by_page_index(screenplay_document, all_scenes_ordered, page_index)
 }
 
@@ -822,7 +1054,7 @@ pub fn get_all_scenes_with_character_speaking<'a>(
     )>,
 > {
     let all_scenes_sorted = get_all_scenes_ordered(screenplay_document)?;
-    return filter_scenes_by_character_speaking(screenplay_document, all_scenes_sorted, character);
+    filter_scenes_by_character_speaking(screenplay_document, all_scenes_sorted, character)
 }
 
 pub fn filter_scenes_by_character_speaking<'a>(
@@ -856,7 +1088,7 @@ pub fn filter_scenes_by_character_speaking<'a>(
                 if p_idx < scn.start.page {
                     continue 'pages;
                 }
-                if l_idx < scn.start.line {
+                if p_idx == scn.start.page && l_idx < scn.start.line {
                     continue 'lines;
                 }
                 if line.line_type
@@ -867,7 +1099,7 @@ pub fn filter_scenes_by_character_speaking<'a>(
                 {
                     continue 'scenes;
                 }
-                if character.is_line(&line) {
+                if character.is_line(line) {
                     for (existing_id, _) in &scenes_vec {
                         if *existing_id == scn_id {
                             continue 'lines;
@@ -880,9 +1112,9 @@ pub fn filter_scenes_by_character_speaking<'a>(
         }
     }
     if scenes_vec.is_empty() {
-        return None;
+        None
     } else {
-        return Some(scenes_vec);
+        Some(scenes_vec)
     }
 }
 
@@ -898,19 +1130,56 @@ pub fn get_scenes_with_location<'a>(
     let scenes: Vec<_> = screenplay_document
         .scenes
         .iter()
-        .filter(|(_, scn)| scn.story_locations.contains(&location))
+        .filter(|(_, scn)| scn.story_locations.contains(location))
         .collect();
 
     if scenes.is_empty() {
-        let location_opt = screenplay_document.locations.get(&location);
-        println!("LOCATION: {:?}", location_opt);
-        //panic!("COULDN'T FIND SCENES FOR LOCATION!");
         return None;
     }
 
     Some(scenes)
 }
 
+/// Returns every scene which has this environment on either side of its heading,
+/// i.e. both `INT. CAR / EXT. ROAD` and `EXT./INT. CAR` are `Int` scenes.
+///
+/// Passing a `Combo` only matches scenes with the same combo, in the same order.
+pub fn filter_scenes_by_environment<'a>(
+    scenes_to_filter: Vec<(
+        &'a screenplay_document::SceneID,
+        &'a screenplay_document::Scene,
+    )>,
+    environment: &screenplay_document::Environment,
+) -> Option<
+    Vec<(
+        &'a screenplay_document::SceneID,
+        &'a screenplay_document::Scene,
+    )>,
+> {
+    let filtered: Vec<_> = scenes_to_filter
+        .into_iter()
+        .filter(|(_, scn)| scn.environment.includes(environment))
+        .collect();
+    if filtered.is_empty() {
+        return None;
+    }
+    Some(filtered)
+}
+
+/// Returns every scene with this environment on either side of its heading, in document order.
+pub fn get_all_scenes_with_environment<'a>(
+    screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
+    environment: &screenplay_document::Environment,
+) -> Option<
+    Vec<(
+        &'a screenplay_document::SceneID,
+        &'a screenplay_document::Scene,
+    )>,
+> {
+    let all_scenes_sorted = get_all_scenes_ordered(screenplay_document)?;
+    filter_scenes_by_environment(all_scenes_sorted, environment)
+}
+
 // ------------ Get PAGEs...
 // TODO: Filter pages by location, character
 
@@ -930,15 +1199,14 @@ pub fn get_all_pages_for_scene<'a>(
         {
             continue;
         }
-        let page_indecies: Vec<usize> =
-            (checked_scene.start.page.clone()..=scene_obj.start.page.clone()).collect();
+        let page_indecies: Vec<usize> = (checked_scene.start.page..=scene_obj.start.page).collect();
 
         let mut pages: Vec<(usize, &screenplay_document::Page)> = Vec::new();
         for pi in page_indecies {
             let Some(page) = screenplay_document.pages.get(pi) else {
                 continue;
             };
-            pages.push((pi.clone(), page));
+            pages.push((pi, page));
         }
         if pages.is_empty() {
             return None;
@@ -957,7 +1225,7 @@ pub fn get_all_pages_for_multiple_scenes<'a>(
 
     let mut all_pages: Vec<(usize, &screenplay_document::Page)> = Vec::new();
 
-    for (scene_id, checked_scene) in scene_ids {
+    for (_scene_id, checked_scene) in scene_ids {
         //pages.push(checked_scene.start.page.clone());
 
         for (_this_id, scene_obj) in &scenes_ordered {
@@ -968,14 +1236,14 @@ pub fn get_all_pages_for_multiple_scenes<'a>(
                 continue;
             }
             let page_indecies: Vec<usize> =
-                (checked_scene.start.page.clone()..=scene_obj.start.page.clone()).collect();
+                (checked_scene.start.page..=scene_obj.start.page).collect();
 
             let mut pages: Vec<(usize, &screenplay_document::Page)> = Vec::new();
             for pi in page_indecies {
                 let Some(page) = screenplay_document.pages.get(pi) else {
                     continue;
                 };
-                pages.push((pi.clone(), page));
+                pages.push((pi, page));
             }
             if pages.is_empty() {
                 continue;
@@ -998,13 +1266,11 @@ pub fn get_all_pages_for_location<'a>(
     screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
     location: &'a screenplay_document::LocationID,
 ) -> Option<Vec<(usize, &'a screenplay_document::Page)>> {
-    let Some(scenes_ordered) = get_all_scenes_ordered(screenplay_document) else {
-        return None;
-    };
+    let scenes_ordered = get_all_scenes_ordered(screenplay_document)?;
     let scenes_filtered =
         filter_scenes_by_locations(screenplay_document, scenes_ordered, vec![location])?;
 
-    return get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered);
+    get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered)
 }
 
 pub fn get_all_pages_for_character_speaking<'a>(
@@ -1089,12 +1355,10 @@ pub fn filter_pages_by_location<'a>(
     pages_range: (usize, usize),
     location: &'a screenplay_document::LocationID,
 ) -> Option<Vec<(usize, &'a screenplay_document::Page)>> {
-    let Some(scenes_ordered) = get_all_scenes_ordered(screenplay_document) else {
-        return None;
-    };
-    let scenes_within_page_range: Vec<(_)> = scenes_ordered
+    let scenes_ordered = get_all_scenes_ordered(screenplay_document)?;
+    let scenes_within_page_range: Vec<_> = scenes_ordered
         .iter()
-        .filter(|(scn_id, scn)| pages_range.0 < scn.start.page && scn.start.page < pages_range.1)
+        .filter(|(_scn_id, scn)| pages_range.0 < scn.start.page && scn.start.page < pages_range.1)
         .copied()
         .collect();
     let scenes_filtered = filter_scenes_by_locations(
@@ -1103,23 +1367,359 @@ pub fn filter_pages_by_location<'a>(
         vec![location],
     )?;
 
-    return get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered);
+    get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered)
 }
 pub fn filter_pages_by_multiple_locations<'a>(
     screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
     pages_range: (usize, usize),
     locations: Vec<&'a screenplay_document::LocationID>,
 ) -> Option<Vec<(usize, &'a screenplay_document::Page)>> {
-    let Some(scenes_ordered) = get_all_scenes_ordered(screenplay_document) else {
-        return None;
-    };
-    let scenes_within_page_range: Vec<(_)> = scenes_ordered
+    let scenes_ordered = get_all_scenes_ordered(screenplay_document)?;
+    let scenes_within_page_range: Vec<_> = scenes_ordered
         .iter()
-        .filter(|(scn_id, scn)| pages_range.0 < scn.start.page && scn.start.page < pages_range.1)
+        .filter(|(_scn_id, scn)| pages_range.0 < scn.start.page && scn.start.page < pages_range.1)
         .copied()
         .collect();
     let scenes_filtered =
         filter_scenes_by_locations(screenplay_document, scenes_within_page_range, locations)?;
 
-    return get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered);
+    get_all_pages_for_multiple_scenes(screenplay_document, scenes_filtered)
+}
+
+// ------------ Get EIGHTHS...
+// Scene lengths are measured in eighths of a page, the way production reports count them.
+
+/// Formats a length in eighths of a page, i.e. `11` as `1 3/8`.
+///
+/// ```
+/// use screenplay_doc_parser_rs::reports;
+/// assert_eq!(reports::format_eighths(3), "3/8");
+/// assert_eq!(reports::format_eighths(16), "2");
+/// assert_eq!(reports::format_eighths(11), "1 3/8");
+/// ```
+pub fn format_eighths(eighths: u64) -> String {
+    match (eighths / 8, eighths % 8) {
+        (pages, 0) => pages.to_string(),
+        (0, remainder) => format!("{}/8", remainder),
+        (pages, remainder) => format!("{} {}/8", pages, remainder),
+    }
+}
+
+/// Gets the total length in eighths of every location, sorted by the full location path.
+///
+/// A scene counts towards its location and every location above it,
+/// so `HOUSE` includes the scenes in `HOUSE - KITCHEN`.
+pub fn get_eighths_per_location(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(&screenplay_document::LocationID, u64)>> {
+    let mut eighths_per_location: Vec<(&screenplay_document::LocationID, u64)> = Vec::new();
+    for scene in screenplay_document.scenes.values() {
+        let Some(eighths) = scene.length_eighths else {
+            continue;
+        };
+        let mut scene_locations: HashSet<&screenplay_document::LocationID> = HashSet::new();
+        for location_id in &scene.story_locations {
+            let Some(path) = get_full_location_path_for_leaf_node(screenplay_document, location_id)
+            else {
+                continue;
+            };
+            scene_locations.extend(path);
+        }
+        for location_id in scene_locations {
+            match eighths_per_location
+                .iter_mut()
+                .find(|(id, _)| *id == location_id)
+            {
+                Some((_, total)) => *total += eighths,
+                None => eighths_per_location.push((location_id, eighths)),
+            }
+        }
+    }
+    if eighths_per_location.is_empty() {
+        return None;
+    }
+    eighths_per_location.sort_by_cached_key(|(id, _)| {
+        get_full_string_for_location_path(screenplay_document, id).unwrap_or_default()
+    });
+    Some(eighths_per_location)
+}
+
+/// Gets the total length in eighths of the scenes each character speaks in, sorted by name.
+pub fn get_eighths_per_character_speaking(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(&screenplay_document::Character, u64)>> {
+    let mut eighths_per_character: Vec<(&screenplay_document::Character, u64)> = Vec::new();
+    for (scene_id, scene) in &screenplay_document.scenes {
+        let Some(eighths) = scene.length_eighths else {
+            continue;
+        };
+        let Some(characters) = get_characters_for_scene(screenplay_document, scene_id) else {
+            continue;
+        };
+        for character in characters {
+            match eighths_per_character
+                .iter_mut()
+                .find(|(c, _)| *c == character)
+            {
+                Some((_, total)) => *total += eighths,
+                None => eighths_per_character.push((character, eighths)),
+            }
+        }
+    }
+    if eighths_per_character.is_empty() {
+        return None;
+    }
+    eighths_per_character.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+    Some(eighths_per_character)
+}
+
+// ------------ Get effective TIME OF DAY...
+
+/// Gets the effective time of day of each scene, in the same order.
+///
+/// `CONTINUOUS` and `SAME` scenes without their own time of day take the effective time of day
+/// of the scene before them. Omitted scenes are skipped over.
+pub(crate) fn get_effective_times_of_day<'a>(
+    scenes: &[(
+        &'a screenplay_document::SceneID,
+        &'a screenplay_document::Scene,
+    )],
+) -> Vec<Option<&'a screenplay_document::TimeOfDay>> {
+    let mut times_of_day: Vec<Option<&screenplay_document::TimeOfDay>> = Vec::new();
+    let mut previous: Option<&screenplay_document::TimeOfDay> = None;
+    for (_, scene) in scenes {
+        if scene.omitted {
+            times_of_day.push(None);
+            continue;
+        }
+        let time_of_day = match (&scene.story_time_of_day, &scene.continuity) {
+            (Some(time_of_day), _) => Some(time_of_day),
+            (
+                None,
+                Some(
+                    screenplay_document::Continuity::Continuous(_)
+                    | screenplay_document::Continuity::Same(_),
+                ),
+            ) => previous,
+            (None, _) => None,
+        };
+        times_of_day.push(time_of_day);
+        previous = time_of_day;
+    }
+    times_of_day
+}
+
+/// Gets the time of day a scene is actually set at.
+///
+/// This is the scene's own time of day, or, for a `CONTINUOUS` or `SAME` scene without one,
+/// the effective time of day of the scene before it.
+pub fn get_effective_time_of_day<'a>(
+    screenplay_document: &'a crate::screenplay_document::ScreenplayDocument,
+    scene_id: &screenplay_document::SceneID,
+) -> Option<&'a screenplay_document::TimeOfDay> {
+    let all_scenes = get_all_scenes_ordered(screenplay_document)?;
+    let idx = all_scenes.iter().position(|(id, _)| *id == scene_id)?;
+    get_effective_times_of_day(&all_scenes[..=idx])
+        .pop()
+        .flatten()
+}
+
+/// Gets the total length in eighths of each time of day, in the order they first appear.
+///
+/// `CONTINUOUS` and `SAME` scenes count towards their effective time of day
+/// (see `get_effective_time_of_day`). Scenes without a time of day aren't counted.
+pub fn get_eighths_per_time_of_day(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(&screenplay_document::TimeOfDay, u64)>> {
+    let mut eighths_per_time_of_day: Vec<(&screenplay_document::TimeOfDay, u64)> = Vec::new();
+    let all_scenes = get_all_scenes_ordered(screenplay_document)?;
+    let times_of_day = get_effective_times_of_day(&all_scenes);
+    for ((_, scene), time_of_day) in all_scenes.iter().zip(times_of_day) {
+        let (Some(eighths), Some(time_of_day)) = (scene.length_eighths, time_of_day) else {
+            continue;
+        };
+        match eighths_per_time_of_day
+            .iter_mut()
+            .find(|(t, _)| *t == time_of_day)
+        {
+            Some((_, total)) => *total += eighths,
+            None => eighths_per_time_of_day.push((time_of_day, eighths)),
+        }
+    }
+    if eighths_per_time_of_day.is_empty() {
+        return None;
+    }
+    Some(eighths_per_time_of_day)
+}
+
+/// A time of day, and every scene set at it.
+pub type TimeOfDayGroup<'a> = (
+    &'a screenplay_document::TimeOfDay,
+    Vec<(
+        &'a screenplay_document::SceneID,
+        &'a screenplay_document::Scene,
+    )>,
+);
+
+/// Gets every scene, grouped by its effective time of day (see `get_effective_time_of_day`),
+/// in the order each time of day first appears.
+///
+/// Each custom time of day is its own group, i.e. `DUSK` and `MAGIC HOUR` aren't grouped together.
+/// Scenes without a time of day aren't included.
+pub fn get_all_scenes_grouped_by_time_of_day(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<TimeOfDayGroup<'_>>> {
+    let mut groups: Vec<TimeOfDayGroup> = Vec::new();
+    let all_scenes = get_all_scenes_ordered(screenplay_document)?;
+    let times_of_day = get_effective_times_of_day(&all_scenes);
+    for (scene, time_of_day) in all_scenes.into_iter().zip(times_of_day) {
+        let Some(time_of_day) = time_of_day else {
+            continue;
+        };
+        match groups.iter_mut().find(|(t, _)| *t == time_of_day) {
+            Some((_, scenes)) => scenes.push(scene),
+            None => groups.push((time_of_day, vec![scene])),
+        }
+    }
+    if groups.is_empty() {
+        return None;
+    }
+    Some(groups)
+}
+
+/// Gets the total length in eighths of each `Lighting` (day, night or transitional),
+/// in the order they first appear.
+///
+/// Custom times of day count towards their lighting from `TimeOfDayCollection.extras_lighting`;
+/// scenes without a lighting aren't counted.
+pub fn get_eighths_per_lighting(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(screenplay_document::Lighting, u64)>> {
+    let mut eighths_per_lighting: Vec<(screenplay_document::Lighting, u64)> = Vec::new();
+    let all_scenes = get_all_scenes_ordered(screenplay_document)?;
+    let times_of_day = get_effective_times_of_day(&all_scenes);
+    for ((_, scene), time_of_day) in all_scenes.iter().zip(times_of_day) {
+        let (Some(eighths), Some(lighting)) = (
+            scene.length_eighths,
+            time_of_day.and_then(|t| t.get_lighting()),
+        ) else {
+            continue;
+        };
+        match eighths_per_lighting
+            .iter_mut()
+            .find(|(l, _)| *l == lighting)
+        {
+            Some((_, total)) => *total += eighths,
+            None => eighths_per_lighting.push((lighting, eighths)),
+        }
+    }
+    if eighths_per_lighting.is_empty() {
+        return None;
+    }
+    Some(eighths_per_lighting)
+}
+
+// ------------ Validate SCENE NUMBERS...
+
+/// A problem with a scene's number, from `validate_scene_numbers`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SceneNumberingIssue {
+    /// The scene has no number, but other scenes do
+    Missing,
+    /// An earlier scene has the same number
+    Duplicate,
+    /// One or more numbers are skipped after the previous scene's number, i.e. `12` then `14`
+    Gap { previous: String },
+    /// The number should come before the previous scene's number, i.e. `12B` then `12A`
+    OutOfOrder { previous: String },
+    /// The scene numbers in the left and right margins don't match
+    MarginMismatch { left: String, right: String },
+}
+
+/// Checks the scene numbers of a numbered script, in document order.
+///
+/// Omitted scenes count as numbered scenes, so they don't leave gaps.
+/// Returns `None` if no scene has a number, or if there are no issues.
+pub fn validate_scene_numbers(
+    screenplay_document: &crate::screenplay_document::ScreenplayDocument,
+) -> Option<Vec<(&screenplay_document::SceneID, SceneNumberingIssue)>> {
+    let scenes = get_all_scenes_ordered(screenplay_document)?;
+    if scenes.iter().all(|(_, scene)| scene.number.is_none()) {
+        return None;
+    }
+
+    let mut issues: Vec<(&screenplay_document::SceneID, SceneNumberingIssue)> = Vec::new();
+    let mut seen: Vec<&screenplay_document::SceneNumber> = Vec::new();
+    let mut previous: Option<(
+        &screenplay_document::SceneNumber,
+        screenplay_document::SceneNumberParts,
+    )> = None;
+
+    for (scene_id, scene) in scenes {
+        if let Some(heading) = screenplay_document
+            .pages
+            .get(scene.start.page)
+            .and_then(|page| page.lines.get(scene.start.line))
+        {
+            let margin_numbers: Vec<&str> = heading
+                .text_elements
+                .iter()
+                .filter(|e| e.element_type == Some(SPType::SP_SCENENUM))
+                .map(|e| e.text.trim_matches('*').trim_matches('.'))
+                .filter(|text| !text.is_empty())
+                .collect();
+            if let Some(left) = margin_numbers.first()
+                && let Some(right) = margin_numbers.iter().find(|text| *text != left)
+            {
+                issues.push((
+                    scene_id,
+                    SceneNumberingIssue::MarginMismatch {
+                        left: left.to_string(),
+                        right: right.to_string(),
+                    },
+                ));
+            }
+        }
+
+        let Some(number) = &scene.number else {
+            issues.push((scene_id, SceneNumberingIssue::Missing));
+            continue;
+        };
+        let parts = number.get_parts();
+        if seen.iter().any(|n| match (n.get_parts(), &parts) {
+            (Some(a), Some(b)) => a == *b,
+            _ => n.0 == number.0,
+        }) {
+            issues.push((scene_id, SceneNumberingIssue::Duplicate));
+            continue;
+        }
+        seen.push(number);
+
+        let Some(parts) = parts else {
+            continue;
+        };
+        if let Some((previous_number, previous_parts)) = &previous {
+            if parts < *previous_parts {
+                issues.push((
+                    scene_id,
+                    SceneNumberingIssue::OutOfOrder {
+                        previous: previous_number.0.clone(),
+                    },
+                ));
+            } else if parts.number > previous_parts.number + 1 {
+                issues.push((
+                    scene_id,
+                    SceneNumberingIssue::Gap {
+                        previous: previous_number.0.clone(),
+                    },
+                ));
+            }
+        }
+        previous = Some((number, parts));
+    }
+
+    if issues.is_empty() {
+        return None;
+    }
+    Some(issues)
 }
@@ -1,27 +1,84 @@
-use crate::{pdf_document, };
-use core::panic;
+use crate::pdf_document;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet,},
-    
+    collections::{HashMap, HashSet},
     hash::Hash,
-    ops::{Deref, DerefMut, },
-    time::{Instant, },
+    ops::{Deref, DerefMut},
     vec,
 };
 use uuid::Uuid;
 
-#[derive(PartialEq, Clone, Debug)]
+/// Uppercases a string and replaces its punctuation with spaces, so `int.`, `INT` and `INT.`
+/// (or `INT./EXT.` and `INT. / EXT.`) all match each other.
+pub fn normalize_match_str(text: &str) -> String {
+    text.to_uppercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Checks if two strings are the same, ignoring case and punctuation.
+fn matches_str(a: &str, b: &str) -> bool {
+    let a = normalize_match_str(a);
+    !a.is_empty() && a == normalize_match_str(b)
+}
+
+/// Finds the longest run of words, from the start of `words`, which `get_match` matches.
+///
+/// Returns how many words were matched, and the match.
+fn get_longest_match<T>(
+    words: &[&str],
+    get_match: impl Fn(&str) -> Option<T>,
+) -> Option<(usize, T)> {
+    (1..=words.len())
+        .rev()
+        .find_map(|len| get_match(&words[..len].join(" ")).map(|found| (len, found)))
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum TimeOfDay {
     Day(String),
     Night(String),
     Morning(String),
     Evening(String),
     Afternoon(String),
-    Extras(Option<HashMap<String, String>>),
+    /// A custom time of day from `TimeOfDayCollection.extras`, i.e. `DUSK` or `MAGIC HOUR`
+    Extra {
+        /// The key of the string in `TimeOfDayCollection.extras`
+        key: String,
+        string: String,
+        lighting: Option<Lighting>,
+    },
+}
+impl TimeOfDay {
+    /// Gets the lighting of this time of day. `EVENING` is `Transitional`;
+    /// extras only have a lighting if one was set in `TimeOfDayCollection.extras_lighting`.
+    pub fn get_lighting(&self) -> Option<Lighting> {
+        match self {
+            TimeOfDay::Day(_) | TimeOfDay::Morning(_) | TimeOfDay::Afternoon(_) => {
+                Some(Lighting::Day)
+            }
+            TimeOfDay::Night(_) => Some(Lighting::Night),
+            TimeOfDay::Evening(_) => Some(Lighting::Transitional),
+            TimeOfDay::Extra { lighting, .. } => *lighting,
+        }
+    }
 }
 
+/// Whether a time of day is lit like day or night, for day/night stats and scheduling.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+pub enum Lighting {
+    Day,
+    Night,
+    /// Dawn, dusk, magic hour...
+    Transitional,
+}
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct TimeOfDayCollection {
     pub day: TimeOfDay,
     pub night: TimeOfDay,
@@ -29,20 +86,31 @@ pub struct TimeOfDayCollection {
     pub evening: TimeOfDay,
     pub afternoon: TimeOfDay,
     pub extras: Option<HashMap<String, String>>,
+    /// The lighting of each extra, by its key in `extras`
+    pub extras_lighting: Option<HashMap<String, Lighting>>,
+    /// `CONTINUOUS`, `LATER`... which sit in the time of day's place in a heading
+    pub continuity: ContinuityStrings,
+    /// `FLASHBACK`, `1985`...
+    pub time_periods: TimePeriodStrings,
 }
 impl Default for TimeOfDayCollection {
     fn default() -> Self {
-        return Self {
+        Self {
             day: TimeOfDay::Day("DAY".into()),
             night: TimeOfDay::Night("NIGHT".into()),
             morning: TimeOfDay::Morning("MORNING".into()),
             evening: TimeOfDay::Evening("EVENING".into()),
             afternoon: TimeOfDay::Afternoon("AFTERNOON".into()),
             extras: None,
-        };
+            extras_lighting: None,
+            continuity: ContinuityStrings::default(),
+            time_periods: TimePeriodStrings::default(),
+        }
     }
 }
 impl TimeOfDayCollection {
+    // Takes a `&String` so existing callers keep working
+    #[allow(clippy::ptr_arg)]
     pub fn is_time_of_day(&self, target: &String) -> bool {
         let vars: Vec<&TimeOfDay> = vec![
             &self.day,
@@ -58,10 +126,10 @@ impl TimeOfDayCollection {
                 | TimeOfDay::Night(string)
                 | TimeOfDay::Morning(string)
                 | TimeOfDay::Evening(string)
-                | TimeOfDay::Afternoon(string) => {
-                    if string == target {
-                        return true;
-                    }
+                | TimeOfDay::Afternoon(string)
+                    if matches_str(string, target) =>
+                {
+                    return true;
                 }
                 _ => {}
             }
@@ -72,17 +140,18 @@ impl TimeOfDayCollection {
                 return false;
             }
             Some(e) => {
-                for (_, string) in e {
-                    if target == string {
+                for string in e.values() {
+                    if matches_str(string, target) {
                         return true;
                     }
                 }
             }
         }
 
-        return false;
+        false
     }
 
+    #[allow(clippy::ptr_arg)]
     pub fn get_time_of_day(&self, target: &String) -> Option<TimeOfDay> {
         let vars: Vec<&TimeOfDay> = vec![
             &self.day,
@@ -98,33 +167,207 @@ impl TimeOfDayCollection {
                 | TimeOfDay::Night(string)
                 | TimeOfDay::Morning(string)
                 | TimeOfDay::Evening(string)
-                | TimeOfDay::Afternoon(string) => {
-                    if string == target {
-                        return Some(time.clone());
-                    }
+                | TimeOfDay::Afternoon(string)
+                    if matches_str(string, target) =>
+                {
+                    return Some(time.clone());
                 }
                 _ => {}
             }
         }
 
-        match &self.extras {
-            None => {
-                return None;
-            }
-            Some(e) => {
-                for (_, string) in e {
-                    if target == string {
-                        return Some(TimeOfDay::Extras(None)); // this is FUCKING horrendous what the fuck man
-                    }
-                }
+        // `extras` is a `HashMap`, so when more than one matches, the longest string wins,
+        // then the first key, so it's always the same one
+        if let Some(extras) = &self.extras
+            && let Some((key, string)) = extras
+                .iter()
+                .filter(|(_, string)| matches_str(string, target))
+                .min_by(|(a_key, a), (b_key, b)| {
+                    b.len().cmp(&a.len()).then_with(|| a_key.cmp(b_key))
+                })
+        {
+            return Some(TimeOfDay::Extra {
+                key: key.clone(),
+                string: string.clone(),
+                lighting: self
+                    .extras_lighting
+                    .as_ref()
+                    .and_then(|lighting| lighting.get(key))
+                    .copied(),
+            });
+        }
+
+        None
+    }
+
+    /// Finds the longest time of day at the start of `words`, i.e. `HIGH NOON` in `["HIGH", "NOON", "-"]`.
+    ///
+    /// Returns how many words it took up, and the time of day.
+    pub fn get_longest_match(&self, words: &[&str]) -> Option<(usize, TimeOfDay)> {
+        get_longest_match(words, |phrase| self.get_time_of_day(&phrase.to_string()))
+    }
+
+    /// Gets every time of day string in this collection, including the extras.
+    pub fn get_all_strs(&self) -> Vec<&String> {
+        let mut all_strs: Vec<&String> = Vec::new();
+        for time in [
+            &self.day,
+            &self.night,
+            &self.morning,
+            &self.evening,
+            &self.afternoon,
+        ] {
+            match time {
+                TimeOfDay::Day(string)
+                | TimeOfDay::Night(string)
+                | TimeOfDay::Morning(string)
+                | TimeOfDay::Evening(string)
+                | TimeOfDay::Afternoon(string)
+                | TimeOfDay::Extra { string, .. } => all_strs.push(string),
             }
         }
+        if let Some(extras) = &self.extras {
+            let mut extra_strs: Vec<&String> = extras.values().collect();
+            extra_strs.sort();
+            all_strs.extend(extra_strs);
+        }
+        all_strs
+    }
+}
 
-        return None;
+/// How a scene follows on from the scene before it, i.e. the `CONTINUOUS` in `INT. HALL - CONTINUOUS`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum Continuity {
+    Continuous(String),
+    Same(String),
+    Later(String),
+    Earlier(String),
+}
+
+/// Strings which are matched against a whole part of a scene heading (between separators),
+/// to find its `Continuity`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ContinuityStrings {
+    pub continuous: Vec<String>,
+    pub same: Vec<String>,
+    pub later: Vec<String>,
+    pub earlier: Vec<String>,
+}
+impl Default for ContinuityStrings {
+    fn default() -> Self {
+        Self {
+            continuous: vec!["CONTINUOUS".into(), "CONT.".into()],
+            same: vec!["SAME".into(), "SAME TIME".into()],
+            later: vec![
+                "LATER".into(),
+                "MOMENTS LATER".into(),
+                "A MOMENT LATER".into(),
+                "MINUTES LATER".into(),
+                "HOURS LATER".into(),
+            ],
+            earlier: vec!["EARLIER".into(), "MOMENTS EARLIER".into()],
+        }
+    }
+}
+impl ContinuityStrings {
+    /// Matches a string against the continuity strings, ignoring case and punctuation.
+    ///
+    /// The `Continuity` has the matching string from this collection.
+    pub fn get_continuity(&self, target: &str) -> Option<Continuity> {
+        let find = |strs: &Vec<String>| strs.iter().find(|s| matches_str(s, target)).cloned();
+        if let Some(string) = find(&self.continuous) {
+            return Some(Continuity::Continuous(string));
+        }
+        if let Some(string) = find(&self.same) {
+            return Some(Continuity::Same(string));
+        }
+        if let Some(string) = find(&self.later) {
+            return Some(Continuity::Later(string));
+        }
+        if let Some(string) = find(&self.earlier) {
+            return Some(Continuity::Earlier(string));
+        }
+        None
+    }
+
+    pub fn is_continuity(&self, target: &str) -> bool {
+        self.get_continuity(target).is_some()
+    }
+}
+
+/// When a scene is set, if it isn't the story's present, i.e. `FLASHBACK` or `1985`.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum TimePeriod {
+    Flashback(String),
+    FlashForward(String),
+    /// A return to the story's present, i.e. `PRESENT DAY`
+    Present(String),
+    /// A year or decade, i.e. `1985` or `1950S`
+    Year(String),
+    Extra(String),
+}
+
+/// Strings which are matched against a whole part of a scene heading (between separators),
+/// to find its `TimePeriod`. Years and decades are matched without any strings.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TimePeriodStrings {
+    pub flashback: Vec<String>,
+    pub flash_forward: Vec<String>,
+    pub present: Vec<String>,
+    pub extras: Vec<String>,
+}
+impl Default for TimePeriodStrings {
+    fn default() -> Self {
+        Self {
+            flashback: vec!["FLASHBACK".into(), "FLASH BACK".into()],
+            flash_forward: vec!["FLASH FORWARD".into(), "FLASHFORWARD".into()],
+            present: vec![
+                "PRESENT".into(),
+                "PRESENT DAY".into(),
+                "BACK TO PRESENT".into(),
+                "END FLASHBACK".into(),
+            ],
+            extras: Vec::new(),
+        }
+    }
+}
+impl TimePeriodStrings {
+    /// Matches a string against the time period strings, ignoring case and punctuation.
+    ///
+    /// The `TimePeriod` has the matching string from this collection,
+    /// or the year as it's written (without any parentheses).
+    pub fn get_time_period(&self, target: &str) -> Option<TimePeriod> {
+        let find = |strs: &Vec<String>| strs.iter().find(|s| matches_str(s, target)).cloned();
+        if let Some(string) = find(&self.flashback) {
+            return Some(TimePeriod::Flashback(string));
+        }
+        if let Some(string) = find(&self.flash_forward) {
+            return Some(TimePeriod::FlashForward(string));
+        }
+        if let Some(string) = find(&self.present) {
+            return Some(TimePeriod::Present(string));
+        }
+        if let Some(string) = find(&self.extras) {
+            return Some(TimePeriod::Extra(string));
+        }
+
+        // 1985, 1950S, 1950'S
+        let year = normalize_match_str(target).replace(' ', "");
+        let digits = year.strip_suffix('S').unwrap_or(&year);
+        if digits.len() == 4 && digits.chars().all(|c| c.is_ascii_digit()) {
+            return Some(TimePeriod::Year(
+                target.trim_matches(|c| c == '(' || c == ')').to_string(),
+            ));
+        }
+        None
+    }
+
+    pub fn is_time_period(&self, target: &str) -> bool {
+        self.get_time_period(target).is_some()
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PageFormat {
     US,
     A4,
@@ -162,7 +405,7 @@ pub enum PageFormat {
 /// `EXT. BASEBALL FIELD - PITCHER'S MOUND - DAY`
 ///
 /// Scene headings can contain more element types, such as a Time Period, or multiple Sublocations.
-#[derive(Default, Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[derive(Default, Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 #[allow(non_camel_case_types)]
 pub enum SPType {
@@ -219,6 +462,7 @@ pub enum SPType {
     TP_TITLE,
     TP_BYLINE,
     TP_AUTHOR,
+    TP_SOURCE, // "Based on..."
     TP_DRAFT_DATE,
     TP_CONTACT,
     // -------------
@@ -239,7 +483,7 @@ pub enum SPType {
 // -------- SCREENPLAY TYPED STRUCTS / ENUMS
 
 // -------------------- CHARACTER
-#[derive(Default, PartialEq, Clone, Debug, Eq, Hash)]
+#[derive(Default, PartialEq, Clone, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct CharacterID(Uuid);
 impl Deref for CharacterID {
     type Target = Uuid;
@@ -258,7 +502,7 @@ impl CharacterID {
     }
 }
 
-#[derive(Default, PartialEq, Clone, Debug, Eq, Hash)]
+#[derive(Default, PartialEq, Clone, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct Character {
     pub name: String,
     pub id: CharacterID,
@@ -270,7 +514,7 @@ impl Character {
         for text_element in &line.text_elements {
             if previous_type != text_element.element_type {
                 if maybe_character_name == self.name {
-                    println!("'howdy y'all");
+                    //println!("'howdy y'all");
                     return true;
                 }
                 maybe_character_name = String::new();
@@ -286,7 +530,7 @@ impl Character {
                 }
                 _ => {}
             }
-            previous_type = text_element.element_type.clone();
+            previous_type = text_element.element_type;
         }
         if maybe_character_name == self.name {
             //println!("HOO WEE!");
@@ -298,7 +542,7 @@ impl Character {
 }
 
 // -------------------- PAGE
-#[derive(Default, PartialEq, Clone, Debug, Eq, Hash)]
+#[derive(Default, PartialEq, Clone, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct PageID(pub Uuid);
 impl Deref for PageID {
     type Target = Uuid;
@@ -313,7 +557,7 @@ impl DerefMut for PageID {
     }
 }
 
-#[derive(Default, PartialEq, Clone, Debug)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct PageNumber(pub String);
 impl Deref for PageNumber {
     type Target = String;
@@ -328,10 +572,94 @@ impl DerefMut for PageNumber {
 }
 
 // -------------------- SCENE
-#[derive(Default, PartialEq, Clone, Debug)]
+/// A scene number, exactly as it's written in the script, i.e. `12`, `12A` or `A12`.
+///
+/// Scene numbers are ordered the way a locked shooting script inserts scenes:
+/// `A12` comes before `12`, and `12A` comes after it. See `SceneNumber::get_parts`.
+#[derive(Default, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct SceneNumber(pub String);
+impl SceneNumber {
+    /// Parses the scene number into its prefix, number and suffix, i.e. `A12B` is `A`, `12` and `B`.
+    ///
+    /// Revision markers and trailing periods are ignored. Returns `None` if there's no number,
+    /// or if the number isn't in one piece (i.e. `12-14`).
+    pub fn get_parts(&self) -> Option<SceneNumberParts> {
+        let text = self.0.trim().trim_matches(|c: char| c == '*' || c == '.');
+        let number_start = text.find(|c: char| c.is_ascii_digit())?;
+        let number_end = text[number_start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map(|idx| number_start + idx)
+            .unwrap_or(text.len());
+        let prefix = &text[..number_start];
+        let suffix = &text[number_end..];
+        if !prefix
+            .chars()
+            .chain(suffix.chars())
+            .all(|c| c.is_alphabetic())
+        {
+            return None;
+        }
+        Some(SceneNumberParts {
+            prefix: prefix.to_uppercase(),
+            number: text[number_start..number_end].parse().ok()?,
+            suffix: suffix.to_uppercase(),
+        })
+    }
+}
+impl PartialOrd for SceneNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for SceneNumber {
+    /// Scene numbers which can't be parsed come after every one which can.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.get_parts(), other.get_parts()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then_with(|| self.0.cmp(&other.0))
+    }
+}
 
-#[derive(Default, PartialEq, Clone, Copy, Debug, Hash, Eq)]
+/// The parts of a `SceneNumber`, i.e. `A12B` is `A`, `12` and `B`. Letters are uppercase.
+#[derive(Default, PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub struct SceneNumberParts {
+    pub prefix: String,
+    pub number: u64,
+    pub suffix: String,
+}
+impl PartialOrd for SceneNumberParts {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for SceneNumberParts {
+    /// Prefixed numbers are inserted before the plain number, and suffixed numbers after it:
+    /// `A12`, `B12`, `12`, `12A`, `12B`.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (
+            self.number,
+            self.prefix.is_empty(),
+            self.prefix.len(),
+            &self.prefix,
+            self.suffix.len(),
+            &self.suffix,
+        )
+            .cmp(&(
+                other.number,
+                other.prefix.is_empty(),
+                other.prefix.len(),
+                &other.prefix,
+                other.suffix.len(),
+                &other.suffix,
+            ))
+    }
+}
+
+#[derive(Default, PartialEq, Clone, Copy, Debug, Hash, Eq, Serialize, Deserialize)]
 pub struct SceneID(pub Uuid);
 impl Deref for SceneID {
     type Target = Uuid;
@@ -355,7 +683,7 @@ impl SceneID {
 // instead of having the scene elements flattened out among the SP_TYPEs
 // maybe also do this technique with CHARACTER, DIALOGUE, etc. ,
 // basically make each element have the LINE TYPE, which contains the ELEMENT TYPE as data...
-#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SceneHeadingElement {
     Line, // The Line Itself
     Environment,
@@ -370,18 +698,46 @@ pub enum SceneHeadingElement {
 }
 
 //TODO: add get_scene_from_id func to ScreenplayDocument struct
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Scene {
     pub start: ScreenplayCoordinate,
+    /// The last content line of the scene. Set once the whole document has been parsed.
+    pub end: Option<ScreenplayCoordinate>,
+    /// The length of the scene in eighths of a page, with a minimum of one eighth (omitted scenes are `0`).
+    /// Set once the whole document has been parsed.
+    pub length_eighths: Option<u64>,
 
     pub environment: Environment,
     pub number: Option<SceneNumber>,
     pub revised: bool,
+    /// Omitted scenes keep their scene number in a locked script, but have no content.
+    pub omitted: bool,
 
     pub story_locations: Vec<LocationID>,
     pub story_time_of_day: Option<TimeOfDay>, // DAY, NIGHT, etc.
+    pub continuity: Option<Continuity>,       // CONTINUOUS, LATER, etc.
+    pub time_period: Option<TimePeriod>,      // FLASHBACK, 1985, etc.
+    /// Set once the whole document has been parsed. `None` for flashbacks and omitted scenes.
+    pub story_day: Option<StoryDay>,
+}
+
+/// A day in the story's timeline, i.e. `Day 1` or `Night 1`. Night 1 follows Day 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StoryDay {
+    pub number: u32,
+    pub night: bool,
+}
+impl StoryDay {
+    pub fn get_label(&self) -> String {
+        if self.night {
+            format!("Night {}", self.number)
+        } else {
+            format!("Day {}", self.number)
+        }
+    }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct EnvironmentStrings {
     pub int: Vec<String>,
     pub ext: Vec<String>,
@@ -402,29 +758,167 @@ impl Default for EnvironmentStrings {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl EnvironmentStrings {
+    /// Finds the longest environment at the start of `words`, i.e. `INT. / EXT.` in
+    /// `["INT.", "/", "EXT.", "HOUSE"]`.
+    ///
+    /// Returns how many words it took up, and the environment.
+    pub fn get_longest_match(&self, words: &[&str]) -> Option<(usize, Environment)> {
+        get_longest_match(words, |phrase| {
+            Environment::from_str(&phrase.to_string(), self)
+        })
+    }
+
+    /// Splits a combo string on `/`, and matches each part against the `int` and `ext` strings.
+    ///
+    /// Abbreviated parts (`I.`, `E.`) match the `int` or `ext` string they're the start of,
+    /// as long as they don't also start the other.
+    /// Returns `None` if any part can't be matched.
+    pub fn get_combo_environments(&self, combo: &str) -> Option<Vec<Environment>> {
+        let strip = |s: &str| s.trim().trim_end_matches('.').to_uppercase();
+        let starts_any = |strs: &Vec<String>, part: &str| {
+            !part.is_empty() && strs.iter().any(|s| strip(s).starts_with(part))
+        };
+        let mut sides: Vec<Environment> = Vec::new();
+        for part in combo.split('/').map(strip) {
+            let is_int = self.int.iter().any(|s| strip(s) == part);
+            let is_ext = self.ext.iter().any(|s| strip(s) == part);
+            let side = match (is_int, is_ext) {
+                (true, false) => Environment::Int,
+                (false, true) => Environment::Ext,
+                (false, false) => {
+                    match (starts_any(&self.int, &part), starts_any(&self.ext, &part)) {
+                        (true, false) => Environment::Int,
+                        (false, true) => Environment::Ext,
+                        _ => return None,
+                    }
+                }
+                (true, true) => return None,
+            };
+            sides.push(side);
+        }
+        if sides.len() < 2 {
+            return None;
+        }
+        Some(sides)
+    }
+}
+
+/// Strings which are matched against whole lines, to find transitions.
+///
+/// Most transitions are right-aligned, but the opening `FADE IN:` is usually
+/// left-aligned, at the Action indentation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransitionStrings {
+    pub right_aligned: Vec<String>,
+    pub left_aligned: Vec<String>,
+}
+impl Default for TransitionStrings {
+    fn default() -> Self {
+        TransitionStrings {
+            right_aligned: vec![
+                "CUT TO:".into(),
+                "SMASH CUT TO:".into(),
+                "MATCH CUT TO:".into(),
+                "JUMP CUT TO:".into(),
+                "DISSOLVE TO:".into(),
+                "WIPE TO:".into(),
+                "FADE TO:".into(),
+                "FADE OUT.".into(),
+                "FADE OUT:".into(),
+                "FADE TO BLACK.".into(),
+                "CUT TO BLACK.".into(),
+                "TIME CUT:".into(),
+                "BACK TO:".into(),
+                "INTERCUT WITH:".into(),
+                "FADE IN:".into(),
+            ],
+            left_aligned: vec!["FADE IN:".into()],
+        }
+    }
+}
+impl TransitionStrings {
+    /// Matching ignores case and punctuation, so `Cut to` matches `CUT TO:`.
+    pub fn is_right_aligned_transition(&self, target: &str) -> bool {
+        self.right_aligned.iter().any(|t| matches_str(t, target))
+    }
+
+    /// Matching ignores case and punctuation, so `Fade in` matches `FADE IN:`.
+    pub fn is_left_aligned_transition(&self, target: &str) -> bool {
+        self.left_aligned.iter().any(|t| matches_str(t, target))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Environment {
     Int,
     Ext,
+    /// Every environment in the heading, in order, i.e. `EXT./INT.` is `[Ext, Int]`.
+    /// `None` if the combo couldn't be split into `Int`s and `Ext`s.
     Combo(Option<Vec<Environment>>),
 }
 impl Environment {
+    /// Matches a string against the environment strings, ignoring case and punctuation.
+    #[allow(clippy::ptr_arg)]
     pub fn from_str(string: &String, current_env_strs: &EnvironmentStrings) -> Option<Self> {
-        if current_env_strs.int.contains(&string) {
+        let find = |strs: &Vec<String>| strs.iter().find(|s| matches_str(s, string)).cloned();
+        if find(&current_env_strs.int).is_some() {
             return Some(Environment::Int);
         }
-        if current_env_strs.ext.contains(&string) {
+        if find(&current_env_strs.ext).is_some() {
             return Some(Environment::Ext);
         }
-        if current_env_strs.combo.contains(&string) {
-            // TODO: actually hanndle combos (4 total possibilities, int/int, int/ext, ext/int, and ext/ext)
-            return Some(Environment::Combo(None));
+        if let Some(combo) = find(&current_env_strs.combo) {
+            return Some(Environment::Combo(
+                current_env_strs.get_combo_environments(&combo),
+            ));
         }
         None
     }
+
+    /// Joins the environments of each side of a heading into one,
+    /// i.e. `INT. CAR / EXT. ROAD` is `Combo(Some([Int, Ext]))`.
+    ///
+    /// Nested combos are flattened; if any side is an unresolved combo, so is the result.
+    pub fn from_sides(sides: Vec<Environment>) -> Option<Self> {
+        let mut flattened: Vec<Environment> = Vec::new();
+        for side in sides {
+            match side {
+                Environment::Combo(Some(inner)) => flattened.extend(inner),
+                Environment::Combo(None) => return Some(Environment::Combo(None)),
+                _ => flattened.push(side),
+            }
+        }
+        match flattened.len() {
+            0 => None,
+            1 => flattened.pop(),
+            _ => Some(Environment::Combo(Some(flattened))),
+        }
+    }
+
+    /// Gets every side of this environment, in order. `Int` and `Ext` only have themselves.
+    ///
+    /// An unresolved combo is both `Int` and `Ext`.
+    pub fn get_sides(&self) -> Vec<Environment> {
+        match self {
+            Environment::Combo(Some(sides)) => sides.iter().flat_map(|s| s.get_sides()).collect(),
+            Environment::Combo(None) => vec![Environment::Int, Environment::Ext],
+            side => vec![side.clone()],
+        }
+    }
+
+    /// Checks if either side of this environment is `environment`.
+    ///
+    /// A combo `environment` only matches the same combo.
+    pub fn includes(&self, environment: &Environment) -> bool {
+        match environment {
+            Environment::Combo(_) => self == environment,
+            _ => self.get_sides().contains(environment),
+        }
+    }
 }
 
-#[derive(Default, PartialEq, Clone, Debug, Eq, Hash)]
+#[derive(Default, PartialEq, Clone, Debug, Eq, Hash, Serialize, Deserialize)]
 pub struct LocationID(Uuid);
 
 impl Deref for LocationID {
@@ -444,7 +938,7 @@ impl LocationID {
     }
 }
 
-#[derive(Default, PartialEq, Clone, Debug)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct LocationNode {
     pub string: String,
     pub sublocations: HashSet<LocationID>, // list of IDs for other locations
@@ -459,7 +953,8 @@ impl LocationNode {
     /// Determines if a path exists under this LocationNode.
     ///
     /// ```
-    /// let mut screenplay_doc = screenplay_document::ScreenplayDocument::new();
+    /// use screenplay_doc_parser_rs::screenplay_document;
+    /// let screenplay_doc = screenplay_document::ScreenplayDocument::new();
     /// ```
     ///
     pub fn subpath_exists<'a>(
@@ -495,7 +990,7 @@ impl LocationNode {
 
 // --------------- BASIC DOCUMENT COMPONENTS ---------------
 
-#[derive(Default, PartialEq, Clone, Debug)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct TextElement {
     pub text: String,
     pub element_type: Option<SPType>,
@@ -503,7 +998,7 @@ pub struct TextElement {
     pub element_position: Option<pdf_document::TextPosition>,
 }
 
-#[derive(Default, PartialEq, Clone, Debug)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Line {
     pub text_elements: Vec<TextElement>,
     pub scene_number: Option<String>,
@@ -512,9 +1007,11 @@ pub struct Line {
     pub preceding_empty_lines: u64,
     pub revised: bool,
     pub blank: bool,
+    /// Centered text, like `>THE END<` in Fountain.
+    pub centered: bool,
 }
 
-#[derive(Default, PartialEq, Clone, Debug)]
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Page {
     pub lines: Vec<Line>,
     pub page_number: Option<PageNumber>,
@@ -524,17 +1021,143 @@ pub struct Page {
     pub page_format: Option<PageFormat>,
 }
 
-#[derive(Default, PartialEq, Clone, Debug, Hash, Eq, PartialOrd)]
+#[derive(Default, PartialEq, Clone, Debug, Hash, Eq, PartialOrd, Serialize, Deserialize)]
 pub struct ScreenplayCoordinate {
     pub page: usize,
     pub line: usize,
     pub element: Option<u64>,
 }
 
-#[derive(Default, PartialEq, Clone, Debug)]
+/// A draft date from the title page, i.e. `Revised Draft - 10/18/2026`.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct DraftDate {
+    pub text: String,
+    /// The date found within the text, if any
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// A revision page color. Anything outside the standard WGA colors is `Other`.
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum RevisionColor {
+    White,
+    Blue,
+    Pink,
+    Yellow,
+    Green,
+    Goldenrod,
+    Buff,
+    Salmon,
+    Cherry,
+    Other(String),
+}
+impl RevisionColor {
+    pub fn name(&self) -> &str {
+        match self {
+            RevisionColor::White => "White",
+            RevisionColor::Blue => "Blue",
+            RevisionColor::Pink => "Pink",
+            RevisionColor::Yellow => "Yellow",
+            RevisionColor::Green => "Green",
+            RevisionColor::Goldenrod => "Goldenrod",
+            RevisionColor::Buff => "Buff",
+            RevisionColor::Salmon => "Salmon",
+            RevisionColor::Cherry => "Cherry",
+            RevisionColor::Other(name) => name,
+        }
+    }
+}
+
+/// The order revision colors are used in, from the original draft onwards.
+///
+/// The default is the WGA order. Productions which use a different order,
+/// or other colors (i.e. `Other("Double White")`), can replace it.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RevisionColorSequence {
+    pub colors: Vec<RevisionColor>,
+}
+impl Default for RevisionColorSequence {
+    fn default() -> Self {
+        Self {
+            colors: vec![
+                RevisionColor::White,
+                RevisionColor::Blue,
+                RevisionColor::Pink,
+                RevisionColor::Yellow,
+                RevisionColor::Green,
+                RevisionColor::Goldenrod,
+                RevisionColor::Buff,
+                RevisionColor::Salmon,
+                RevisionColor::Cherry,
+            ],
+        }
+    }
+}
+impl RevisionColorSequence {
+    /// Finds a color from this sequence within some text, i.e. `Blue Rev. 03/14/26`.
+    ///
+    /// Case-insensitive. Longer color names are tried first, so `Double Blue` isn't `Blue`.
+    pub fn get_color_for_str(&self, text: &str) -> Option<RevisionColor> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_uppercase())
+            .collect();
+        let mut colors: Vec<&RevisionColor> = self.colors.iter().collect();
+        colors.sort_by_key(|c| std::cmp::Reverse(c.name().split_whitespace().count()));
+        colors
+            .into_iter()
+            .find(|color| {
+                let name: Vec<String> = color
+                    .name()
+                    .split_whitespace()
+                    .map(|w| w.to_uppercase())
+                    .collect();
+                !name.is_empty() && words.windows(name.len()).any(|window| window == name)
+            })
+            .cloned()
+    }
+
+    /// Gets where a color is in the sequence, starting from `0` for the original draft.
+    pub fn get_position(&self, color: &RevisionColor) -> Option<usize> {
+        self.colors.iter().position(|c| c == color)
+    }
+
+    /// Gets the color which comes after this one, if there is one.
+    pub fn get_next_color(&self, color: &RevisionColor) -> Option<&RevisionColor> {
+        self.colors.get(self.get_position(color)? + 1)
+    }
+}
+
+/// A set of revised pages, i.e. `Blue Rev. 03/14/26`, or a draft date from the title page.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Revision {
+    /// Revisions without a color in their label are `White`, the color of the original draft.
+    pub color: RevisionColor,
+    pub date: Option<DateTime<Utc>>,
+    /// The full text of the revision label
+    pub label: String,
+}
+
+/// The contents of the title page.
+///
+/// The title page itself is still the first `Page` of the document, but it has
+/// no page number and doesn't count towards scene or page numbering.
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct TitlePage {
+    pub title: Option<String>,
+    pub credit: Option<String>, // "Written by"
+    pub authors: Vec<String>,
+    pub source: Option<String>, // "Based on the novel by..."
+    pub draft_dates: Vec<DraftDate>,
+    pub contact: Vec<String>,
+}
+
+#[derive(Default, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct ScreenplayDocument {
     pub pages: Vec<Page>,
-    pub revisions: Option<Vec<String>>, // current (and possible previous) revision date(s) from the title page
+    pub title_page: Option<TitlePage>,
+    /// Every revision in the document, in color order, or the title page draft dates if no pages are revised
+    pub revisions: Option<Vec<Revision>>,
     pub scenes: HashMap<SceneID, Scene>,
     pub locations: HashMap<LocationID, LocationNode>,
     pub characters: HashSet<Character>,
@@ -544,6 +1167,7 @@ impl ScreenplayDocument {
     pub fn new() -> Self {
         ScreenplayDocument {
             pages: Vec::new(),
+            title_page: None,
             revisions: None,
             scenes: HashMap::new(),
             locations: HashMap::new(),
@@ -552,5 +1176,84 @@ impl ScreenplayDocument {
         }
     }
 
-    
+    /// Sets `revisions` from the revision labels of every page, in the order of the color sequence.
+    ///
+    /// Documents without any revised pages use their title page draft dates instead.
+    /// Parsers call this with the default WGA sequence; call it again to use a different sequence.
+    pub fn set_revisions(&mut self, color_sequence: &RevisionColorSequence) {
+        let mut labels: Vec<String> = Vec::new();
+        for page in &self.pages {
+            if let Some(label) = &page.revision_label
+                && !labels.contains(label)
+            {
+                labels.push(label.clone());
+            }
+        }
+        if labels.is_empty()
+            && let Some(title_page) = &self.title_page
+        {
+            labels = title_page
+                .draft_dates
+                .iter()
+                .map(|d| d.text.clone())
+                .collect();
+        }
+
+        let mut revisions: Vec<Revision> = labels
+            .into_iter()
+            .map(|label| crate::parser_common::get_revision(&label, color_sequence))
+            .collect();
+        // Colors outside of the sequence go last; sorting is stable, so they keep their order
+        revisions.sort_by_key(|r| {
+            (
+                color_sequence.get_position(&r.color).unwrap_or(usize::MAX),
+                r.date,
+            )
+        });
+
+        if revisions.is_empty() {
+            self.revisions = None;
+        } else {
+            self.revisions = Some(revisions);
+        }
+    }
+
+    /// Sets the `story_day` of every scene, with `story_days::get_story_days`.
+    ///
+    /// Parsers call this with the default markers and no overrides;
+    /// call it again to use your own markers, or to fix the story day of particular scenes.
+    pub fn set_story_days(
+        &mut self,
+        markers: &crate::story_days::StoryDayMarkers,
+        overrides: &HashMap<SceneID, StoryDay>,
+    ) {
+        let story_days: Vec<(SceneID, Option<StoryDay>)> =
+            crate::story_days::get_story_days(self, markers, overrides)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(id, day)| (*id, day))
+                .collect();
+        for (id, story_day) in story_days {
+            if let Some(scene) = self.scenes.get_mut(&id) {
+                scene.story_day = story_day;
+            }
+        }
+    }
+
+    /// Serializes the document to JSON. See the README for the JSON schema.
+    ///
+    /// ```
+    /// use screenplay_doc_parser_rs::screenplay_document::ScreenplayDocument;
+    /// let screenplay_doc = ScreenplayDocument::new();
+    /// let json = screenplay_doc.to_json().unwrap();
+    /// assert_eq!(ScreenplayDocument::from_json(&json).unwrap(), screenplay_doc);
+    /// ```
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserializes a document from JSON, as written by `ScreenplayDocument::to_json`.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
 }
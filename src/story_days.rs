@@ -0,0 +1,306 @@
+//! Story days, which track the days of the story's timeline across scenes: `Day 1`,
+//! `Night 1`, `Day 2`... Continuity and wardrobe use these to keep each day consistent.
+//!
+//! Scenes are walked in script order, starting on `Day 1` (or `Night 1`). The story day changes:
+//!
+//! - On an override for the scene, which every following scene continues from
+//! - On a marker in the scene's heading or action, i.e. `THE NEXT MORNING`, `THAT NIGHT` or `DAY 3`
+//! - When the time of day goes back, i.e. `DAY` after `NIGHT`, which starts the next day
+//!
+//! Flashbacks and flash-forwards are outside the story's timeline, so they don't get a story day.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::reports;
+use crate::screenplay_document::{
+    Character, Lighting, LocationID, SPType, SceneID, ScreenplayDocument, StoryDay, TimeOfDay,
+    TimePeriod,
+};
+use crate::writer_common::get_csv_record;
+
+/// Strings which are matched against the words of a scene's heading and action lines,
+/// to move to a different story day.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoryDayMarkers {
+    /// Starts the next story day, i.e. `THE NEXT MORNING`
+    pub next_day: Vec<String>,
+    /// Moves to the night of the current story day, i.e. `THAT NIGHT`
+    pub same_night: Vec<String>,
+    /// Followed by a number, sets the story day, i.e. `DAY 3`
+    pub numbered_day: Vec<String>,
+    /// Followed by a number, sets the story night, i.e. `NIGHT 3`
+    pub numbered_night: Vec<String>,
+}
+impl Default for StoryDayMarkers {
+    fn default() -> Self {
+        Self {
+            next_day: vec![
+                "NEXT DAY".into(),
+                "NEXT MORNING".into(),
+                "FOLLOWING DAY".into(),
+                "FOLLOWING MORNING".into(),
+            ],
+            same_night: vec!["THAT NIGHT".into(), "THAT EVENING".into()],
+            numbered_day: vec!["DAY".into()],
+            numbered_night: vec!["NIGHT".into()],
+        }
+    }
+}
+
+/// The highest story day a marker can set. Larger numbers are years, i.e. `NIGHT 1985`.
+const MAX_MARKED_STORY_DAY: u32 = 999;
+
+/// Where a time of day falls within a story day. Going back to an earlier one starts the next day.
+fn get_time_of_day_rank(time_of_day: &TimeOfDay) -> Option<u8> {
+    match time_of_day {
+        TimeOfDay::Morning(_) => Some(0),
+        TimeOfDay::Day(_) | TimeOfDay::Afternoon(_) => Some(1),
+        TimeOfDay::Evening(_) => Some(2),
+        TimeOfDay::Night(_) => Some(3),
+        // Without knowing if it's dawn or dusk, a transitional extra could be either end of the day
+        TimeOfDay::Extra { lighting, .. } => match lighting {
+            Some(Lighting::Day) => Some(1),
+            Some(Lighting::Night) => Some(3),
+            Some(Lighting::Transitional) | None => None,
+        },
+    }
+}
+
+fn is_night_rank(rank: u8) -> bool {
+    rank >= 2
+}
+
+fn contains_phrase(words: &[String], phrase: &str) -> bool {
+    let phrase: Vec<&str> = phrase.split_whitespace().collect();
+    !phrase.is_empty()
+        && words
+            .windows(phrase.len())
+            .any(|window| window == phrase.as_slice())
+}
+
+/// Gets the first marker in a scene's heading and action lines, as the story day it moves to.
+fn get_marked_story_day(
+    screenplay_doc: &ScreenplayDocument,
+    scene_id: &SceneID,
+    current: StoryDay,
+    markers: &StoryDayMarkers,
+) -> Option<StoryDay> {
+    for (_, line) in reports::get_all_lines_for_scene(screenplay_doc, scene_id)? {
+        // Separators, like the `-` in `NIGHT - 1985`, are kept as empty words,
+        // so a marker can't run across them
+        let words: Vec<String> = line
+            .text_elements
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e.element_type,
+                    Some(SPType::SP_ACTION | SPType::SP_SCENE_HEADING(_))
+                )
+            })
+            .flat_map(|e| e.text.split_whitespace())
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+            .collect();
+
+        if markers.next_day.iter().any(|m| contains_phrase(&words, m)) {
+            return Some(StoryDay {
+                number: current.number + 1,
+                night: false,
+            });
+        }
+        if markers
+            .same_night
+            .iter()
+            .any(|m| contains_phrase(&words, m))
+        {
+            return Some(StoryDay {
+                number: current.number,
+                night: true,
+            });
+        }
+        for pair in words.windows(2) {
+            let Ok(number) = pair[1].parse::<u32>() else {
+                continue;
+            };
+            if number > MAX_MARKED_STORY_DAY {
+                continue;
+            }
+            if markers.numbered_day.contains(&pair[0]) {
+                return Some(StoryDay {
+                    number,
+                    night: false,
+                });
+            }
+            if markers.numbered_night.contains(&pair[0]) {
+                return Some(StoryDay {
+                    number,
+                    night: true,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Gets the story day of every scene, in document order.
+///
+/// Overrides win over markers, and markers win over the time of day.
+/// Omitted scenes, flashbacks and flash-forwards are `None`.
+pub fn get_story_days<'a>(
+    screenplay_doc: &'a ScreenplayDocument,
+    markers: &StoryDayMarkers,
+    overrides: &HashMap<SceneID, StoryDay>,
+) -> Option<Vec<(&'a SceneID, Option<StoryDay>)>> {
+    let scenes = reports::get_all_scenes_ordered(screenplay_doc)?;
+    let times_of_day = reports::get_effective_times_of_day(&scenes);
+
+    let mut story_days: Vec<(&SceneID, Option<StoryDay>)> = Vec::new();
+    let mut current: Option<StoryDay> = None;
+    let mut previous_rank: Option<u8> = None;
+    for ((scene_id, scene), time_of_day) in scenes.into_iter().zip(times_of_day) {
+        if scene.omitted
+            || matches!(
+                scene.time_period,
+                Some(TimePeriod::Flashback(_) | TimePeriod::FlashForward(_))
+            )
+        {
+            story_days.push((scene_id, None));
+            continue;
+        }
+        let rank = time_of_day.and_then(get_time_of_day_rank);
+
+        let story_day = if let Some(story_day) = overrides.get(scene_id) {
+            *story_day
+        } else {
+            let from_time_of_day = match (current, rank) {
+                (None, rank) => StoryDay {
+                    number: 1,
+                    night: rank.is_some_and(is_night_rank),
+                },
+                (Some(current), Some(rank)) => StoryDay {
+                    number: match previous_rank {
+                        Some(previous) if rank < previous => current.number + 1,
+                        _ => current.number,
+                    },
+                    night: is_night_rank(rank),
+                },
+                (Some(current), None) => current,
+            };
+            let baseline = current.unwrap_or(from_time_of_day);
+            get_marked_story_day(screenplay_doc, scene_id, baseline, markers)
+                .unwrap_or(from_time_of_day)
+        };
+
+        current = Some(story_day);
+        previous_rank = match rank {
+            Some(rank) => Some(rank),
+            // Without a time of day, the marker tells us where in the day we are
+            None if story_day.night => Some(3),
+            None => Some(1),
+        };
+        story_days.push((scene_id, Some(story_day)));
+    }
+    Some(story_days)
+}
+
+/// Everything which happens on one story day.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoryDayReport {
+    pub story_day: StoryDay,
+    /// Every scene on this story day, in document order
+    pub scenes: Vec<SceneID>,
+    /// Every location of those scenes, in the order they first appear
+    pub locations: Vec<LocationID>,
+    /// Every character who speaks in those scenes, by name
+    pub characters: Vec<Character>,
+    /// The total length of those scenes
+    pub length_eighths: u64,
+}
+
+/// Gets a report for each story day in the document, in story order (`Day 1`, `Night 1`, `Day 2`...).
+///
+/// Uses each scene's `story_day`; call `ScreenplayDocument::set_story_days` first
+/// to use your own markers or overrides.
+pub fn get_story_day_report(screenplay_doc: &ScreenplayDocument) -> Option<Vec<StoryDayReport>> {
+    let mut reports: Vec<StoryDayReport> = Vec::new();
+    for (scene_id, scene) in reports::get_all_scenes_ordered(screenplay_doc)? {
+        let Some(story_day) = scene.story_day else {
+            continue;
+        };
+        let idx = match reports.iter().position(|r| r.story_day == story_day) {
+            Some(idx) => idx,
+            None => {
+                reports.push(StoryDayReport {
+                    story_day,
+                    scenes: Vec::new(),
+                    locations: Vec::new(),
+                    characters: Vec::new(),
+                    length_eighths: 0,
+                });
+                reports.len() - 1
+            }
+        };
+        let report = &mut reports[idx];
+        report.scenes.push(*scene_id);
+        for location in &scene.story_locations {
+            if !report.locations.contains(location) {
+                report.locations.push(location.clone());
+            }
+        }
+        for character in
+            reports::get_characters_for_scene(screenplay_doc, scene_id).unwrap_or_default()
+        {
+            if !report.characters.iter().any(|c| c.id == character.id) {
+                report.characters.push(character.clone());
+            }
+        }
+        report.length_eighths += scene.length_eighths.unwrap_or_default();
+    }
+    if reports.is_empty() {
+        return None;
+    }
+    for report in &mut reports {
+        report.characters.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    reports.sort_by_key(|r| r.story_day);
+    Some(reports)
+}
+
+/// Exports a story day report as CSV, with one row per story day.
+///
+/// The `Scenes` column lists scene numbers, so unnumbered scenes are left out of it.
+pub fn get_story_day_csv(
+    screenplay_doc: &ScreenplayDocument,
+    story_day_reports: &[StoryDayReport],
+) -> String {
+    let mut csv = get_csv_record(&[
+        "Story Day".into(),
+        "Scenes".into(),
+        "Pages".into(),
+        "Locations".into(),
+        "Characters".into(),
+    ]);
+    for report in story_day_reports {
+        let scenes: Vec<String> = report
+            .scenes
+            .iter()
+            .filter_map(|id| screenplay_doc.scenes.get(id))
+            .filter_map(|scene| scene.number.as_ref().map(|n| n.0.clone()))
+            .collect();
+        let locations: Vec<String> = report
+            .locations
+            .iter()
+            .filter_map(|id| reports::get_full_string_for_location_path(screenplay_doc, id))
+            .collect();
+        let characters: Vec<String> = report.characters.iter().map(|c| c.name.clone()).collect();
+        csv.push_str(&get_csv_record(&[
+            report.story_day.get_label(),
+            scenes.join(", "),
+            reports::format_eighths(report.length_eighths),
+            locations.join(", "),
+            characters.join(", "),
+        ]));
+    }
+    csv
+}
@@ -0,0 +1,350 @@
+//! Stripboards (one-liner schedules), which show every scene as a single strip:
+//! its number, INT/EXT, location, time of day, length in eighths, and cast IDs.
+//!
+//! Cast IDs are numbered from 1 in order of how many scenes each character speaks in,
+//! so the lead is usually `1`. Strips can be grouped, reordered, and split into shoot days
+//! with day breaks. `Stripboard::get_shoot_days` feeds `day_out_of_days`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::reports;
+use crate::screenplay_document::{
+    Character, Environment, LocationID, Scene, SceneID, ScreenplayDocument, TimeOfDay,
+};
+use crate::writer_common::{get_csv_record, get_environment_text, get_time_of_day_text};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Strip {
+    pub scene_id: SceneID,
+    pub number: Option<String>,
+    pub environment: Environment,
+    /// The full location path, i.e. `INT. HOUSE - KITCHEN`, or every side of a split heading
+    pub location: Option<String>,
+    /// The time of day the scene is set at, so `CONTINUOUS` scenes group with the scene before them
+    pub time_of_day: Option<TimeOfDay>,
+    pub length_eighths: Option<u64>,
+    /// The IDs of every character who speaks in the scene, from `Stripboard::cast`
+    pub cast_ids: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StripboardEntry {
+    Strip(Strip),
+    /// Ends a shoot day
+    DayBreak,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stripboard {
+    pub entries: Vec<StripboardEntry>,
+    /// Every character with a cast ID. A character's ID is their index in here, plus 1.
+    pub cast: Vec<Character>,
+}
+impl Stripboard {
+    /// Gets every strip, in their current order, without the day breaks.
+    pub fn get_strips(&self) -> Vec<&Strip> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                StripboardEntry::Strip(strip) => Some(strip),
+                StripboardEntry::DayBreak => None,
+            })
+            .collect()
+    }
+
+    /// Gets the character for a cast ID.
+    pub fn get_cast_member(&self, cast_id: usize) -> Option<&Character> {
+        self.cast.get(cast_id.checked_sub(1)?)
+    }
+
+    /// Moves the entry at `from` to `to`, shifting the entries in between.
+    ///
+    /// Returns `false` if either index is out of bounds.
+    pub fn move_entry(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.entries.len() || to >= self.entries.len() {
+            return false;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        true
+    }
+
+    /// Inserts a day break before the entry at `index`. An `index` past the end appends it.
+    pub fn insert_day_break(&mut self, index: usize) {
+        self.entries
+            .insert(index.min(self.entries.len()), StripboardEntry::DayBreak);
+    }
+
+    /// Removes every day break.
+    pub fn clear_day_breaks(&mut self) {
+        self.entries
+            .retain(|entry| *entry != StripboardEntry::DayBreak);
+    }
+
+    /// Groups strips with the same location together, then by time of day within each location.
+    ///
+    /// Groups are kept in order of their first strip, and strips keep their order within a group.
+    /// Day breaks are removed, since the days they ended no longer make sense.
+    pub fn group_by_location_and_time_of_day(&mut self) {
+        self.clear_day_breaks();
+
+        let mut locations: Vec<Option<String>> = Vec::new();
+        let mut times_of_day: Vec<(Option<String>, Option<String>)> = Vec::new();
+        for strip in self.get_strips() {
+            let time_of_day = strip.time_of_day.as_ref().map(get_time_of_day_text);
+            if !locations.contains(&strip.location) {
+                locations.push(strip.location.clone());
+            }
+            if !times_of_day.contains(&(strip.location.clone(), time_of_day.clone())) {
+                times_of_day.push((strip.location.clone(), time_of_day));
+            }
+        }
+
+        let group_index = |entry: &StripboardEntry| {
+            let StripboardEntry::Strip(strip) = entry else {
+                return (0, 0);
+            };
+            let time_of_day = strip.time_of_day.as_ref().map(get_time_of_day_text);
+            (
+                locations
+                    .iter()
+                    .position(|l| *l == strip.location)
+                    .unwrap_or_default(),
+                times_of_day
+                    .iter()
+                    .position(|(l, t)| *l == strip.location && *t == time_of_day)
+                    .unwrap_or_default(),
+            )
+        };
+        // Sorting is stable, so strips keep their order within a group
+        self.entries.sort_by_key(group_index);
+    }
+
+    /// Gets the scenes shot on each day, split by the day breaks.
+    ///
+    /// Strips after the last day break are the last day. Empty days are skipped.
+    pub fn get_shoot_days(&self) -> Vec<Vec<SceneID>> {
+        let mut days: Vec<Vec<SceneID>> = Vec::new();
+        let mut current_day: Vec<SceneID> = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                StripboardEntry::Strip(strip) => current_day.push(strip.scene_id),
+                StripboardEntry::DayBreak => {
+                    if !current_day.is_empty() {
+                        days.push(std::mem::take(&mut current_day));
+                    }
+                }
+            }
+        }
+        if !current_day.is_empty() {
+            days.push(current_day);
+        }
+        days
+    }
+}
+
+fn get_strips(
+    screenplay_doc: &ScreenplayDocument,
+    scenes: &[(&SceneID, &Scene)],
+) -> Option<Stripboard> {
+    if scenes.is_empty() {
+        return None;
+    }
+    let scene_casts: Vec<Vec<&Character>> = scenes
+        .iter()
+        .map(|(id, _)| reports::get_characters_for_scene(screenplay_doc, id).unwrap_or_default())
+        .collect();
+
+    // The character in the most scenes is cast member 1
+    let mut cast: Vec<(&Character, usize)> = Vec::new();
+    for character in scene_casts.iter().flatten() {
+        match cast.iter_mut().find(|(c, _)| c.id == character.id) {
+            Some((_, count)) => *count += 1,
+            None => cast.push((character, 1)),
+        }
+    }
+    cast.sort_by(|(a, a_count), (b, b_count)| {
+        b_count.cmp(a_count).then_with(|| a.name.cmp(&b.name))
+    });
+
+    // Scenes take their time of day from the script, even if the scene before them is filtered out
+    let all_scenes = reports::get_all_scenes_ordered(screenplay_doc)?;
+    let times_of_day: HashMap<&SceneID, Option<&TimeOfDay>> = all_scenes
+        .iter()
+        .map(|(id, _)| *id)
+        .zip(reports::get_effective_times_of_day(&all_scenes))
+        .collect();
+
+    let entries = scenes
+        .iter()
+        .zip(&scene_casts)
+        .map(|((scene_id, scene), scene_cast)| {
+            let mut cast_ids: Vec<usize> = scene_cast
+                .iter()
+                .filter_map(|character| cast.iter().position(|(c, _)| c.id == character.id))
+                .map(|idx| idx + 1)
+                .collect();
+            cast_ids.sort();
+            StripboardEntry::Strip(Strip {
+                scene_id: **scene_id,
+                number: scene.number.as_ref().map(|n| n.0.clone()),
+                environment: scene.environment.clone(),
+                location: reports::get_full_string_for_scene_locations(screenplay_doc, scene),
+                time_of_day: times_of_day.get(scene_id).copied().flatten().cloned(),
+                length_eighths: scene.length_eighths,
+                cast_ids,
+            })
+        })
+        .collect();
+
+    Some(Stripboard {
+        entries,
+        cast: cast.into_iter().map(|(c, _)| c.clone()).collect(),
+    })
+}
+
+/// Gets a stripboard with a strip for every scene, in script order, without any day breaks.
+///
+/// Omitted scenes aren't shot, so they don't get strips.
+pub fn get_stripboard(screenplay_doc: &ScreenplayDocument) -> Option<Stripboard> {
+    let scenes: Vec<(&SceneID, &Scene)> = reports::get_all_scenes_ordered(screenplay_doc)?
+        .into_iter()
+        .filter(|(_, scene)| !scene.omitted)
+        .collect();
+    get_strips(screenplay_doc, &scenes)
+}
+
+/// Gets a stripboard with only the scenes at any of these locations, in script order.
+///
+/// Locations must be exact; scenes at a sub-location of one of these locations aren't included.
+pub fn get_stripboard_for_locations<'a>(
+    screenplay_doc: &'a ScreenplayDocument,
+    locations: Vec<&'a LocationID>,
+) -> Option<Stripboard> {
+    let all_scenes = reports::get_all_scenes_ordered(screenplay_doc)?;
+    let filtered =
+        reports::filter_scenes_by_locations(screenplay_doc, all_scenes.clone(), locations)?;
+    let scenes: Vec<(&SceneID, &Scene)> = all_scenes
+        .into_iter()
+        .filter(|(id, scene)| !scene.omitted && filtered.iter().any(|(f_id, _)| f_id == id))
+        .collect();
+    get_strips(screenplay_doc, &scenes)
+}
+
+fn get_strip_fields(strip: &Strip) -> Vec<String> {
+    vec![
+        strip.number.clone().unwrap_or_default(),
+        get_environment_text(&strip.environment),
+        strip.location.clone().unwrap_or_default(),
+        strip
+            .time_of_day
+            .as_ref()
+            .map(get_time_of_day_text)
+            .unwrap_or_default(),
+        strip
+            .length_eighths
+            .map(reports::format_eighths)
+            .unwrap_or_default(),
+        strip
+            .cast_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+    ]
+}
+
+/// Exports a stripboard as CSV, with one row per strip.
+///
+/// Day breaks aren't rows; the `Day` column has each strip's shoot day, numbered from 1.
+pub fn get_stripboard_csv(stripboard: &Stripboard) -> String {
+    let mut csv = get_csv_record(&[
+        "Day".into(),
+        "Scene".into(),
+        "INT/EXT".into(),
+        "Location".into(),
+        "Time of Day".into(),
+        "Pages".into(),
+        "Cast".into(),
+    ]);
+    let mut day = 1;
+    let mut day_has_strips = false;
+    for entry in &stripboard.entries {
+        match entry {
+            StripboardEntry::Strip(strip) => {
+                let mut fields = vec![day.to_string()];
+                fields.extend(get_strip_fields(strip));
+                csv.push_str(&get_csv_record(&fields));
+                day_has_strips = true;
+            }
+            StripboardEntry::DayBreak if day_has_strips => {
+                day += 1;
+                day_has_strips = false;
+            }
+            StripboardEntry::DayBreak => {}
+        }
+    }
+    csv
+}
+
+/// Exports a stripboard as a plain text one-liner, with a line for each strip
+/// and each day break, followed by the cast list.
+///
+/// Day breaks show the total length of their day.
+pub fn get_stripboard_text(stripboard: &Stripboard) -> String {
+    let rows: Vec<Option<Vec<String>>> = stripboard
+        .entries
+        .iter()
+        .map(|entry| match entry {
+            StripboardEntry::Strip(strip) => Some(get_strip_fields(strip)),
+            StripboardEntry::DayBreak => None,
+        })
+        .collect();
+    let mut widths: Vec<usize> = vec![0; 6];
+    for row in rows.iter().flatten() {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let mut text = String::new();
+    let mut day = 1;
+    let mut day_eighths: u64 = 0;
+    let mut day_has_strips = false;
+    for (entry, row) in stripboard.entries.iter().zip(&rows) {
+        match (entry, row) {
+            (StripboardEntry::Strip(strip), Some(row)) => {
+                let padded: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(field, width)| format!("{:<width$}", field, width = width))
+                    .collect();
+                text.push_str(padded.join("  ").trim_end());
+                text.push('\n');
+                day_eighths += strip.length_eighths.unwrap_or_default();
+                day_has_strips = true;
+            }
+            _ if day_has_strips => {
+                text.push_str(&format!(
+                    "--- END OF DAY {} -- {} pages ---\n",
+                    day,
+                    reports::format_eighths(day_eighths)
+                ));
+                day += 1;
+                day_eighths = 0;
+                day_has_strips = false;
+            }
+            _ => {}
+        }
+    }
+
+    if !stripboard.cast.is_empty() {
+        text.push_str("\nCAST\n");
+        for (idx, character) in stripboard.cast.iter().enumerate() {
+            text.push_str(&format!("{}. {}\n", idx + 1, character.name));
+        }
+    }
+    text
+}
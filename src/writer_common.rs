@@ -0,0 +1,113 @@
+//! Logic shared between the Fountain and FDX writers, and the CSV exports.
+
+use crate::screenplay_document::{
+    Environment, Line, Page, SPType, ScreenplayDocument, TextElement, TimeOfDay,
+};
+
+pub(crate) fn is_title_page_type(element_type: &Option<SPType>) -> bool {
+    matches!(
+        element_type,
+        Some(SPType::TP_TITLE)
+            | Some(SPType::TP_BYLINE)
+            | Some(SPType::TP_AUTHOR)
+            | Some(SPType::TP_SOURCE)
+            | Some(SPType::TP_DRAFT_DATE)
+            | Some(SPType::TP_CONTACT)
+    )
+}
+
+/// A title page is any page with title page elements on it.
+pub(crate) fn is_title_page(page: &Page) -> bool {
+    page.lines.iter().any(|l| is_title_page_type(&l.line_type))
+}
+
+/// Joins all elements of a line which match the filter, with a single space between each.
+pub(crate) fn get_text_for_elements(
+    text_elements: &[TextElement],
+    filter: impl Fn(&Option<SPType>) -> bool,
+) -> String {
+    text_elements
+        .iter()
+        .filter(|e| filter(&e.element_type))
+        .map(|e| e.text.as_str())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Checks if an element is part of the script's text, as opposed to scene numbers,
+/// revision markers or other non-content text.
+pub(crate) fn is_content_element_type(element_type: &Option<SPType>) -> bool {
+    !matches!(
+        element_type,
+        Some(SPType::SP_SCENENUM)
+            | Some(SPType::SP_PAGENUM)
+            | Some(SPType::SP_LINE_REVISION_MARKER)
+            | Some(SPType::SP_PAGE_REVISION_LABEL)
+            | Some(SPType::SP_MORE_CONTINUED)
+            | Some(SPType::SP_DG_MORE_CONTINUED)
+            | Some(SPType::SP_DD_L_MORE_CONTINUED)
+            | Some(SPType::SP_DD_R_MORE_CONTINUED)
+            | Some(SPType::NON_CONTENT_TOP)
+            | Some(SPType::NON_CONTENT_BOTTOM)
+            | Some(SPType::NON_CONTENT_LEFT)
+            | Some(SPType::NON_CONTENT_RIGHT)
+    )
+}
+
+/// Gets the text of a line, without any scene numbers, revision markers or other non-content text.
+pub(crate) fn get_content_text(line: &Line) -> String {
+    get_text_for_elements(&line.text_elements, is_content_element_type)
+}
+
+/// Gets the scene number for a Scene Heading line, from the line itself or from its `Scene`.
+pub(crate) fn get_scene_number(line: &Line, screenplay_doc: &ScreenplayDocument) -> Option<String> {
+    line.scene_number.clone().or_else(|| {
+        line.scene_id
+            .and_then(|id| screenplay_doc.scenes.get(&id))
+            .and_then(|scene| scene.number.clone())
+            .map(|number| number.0)
+    })
+}
+
+/// Gets a CSV record for a row of fields, quoting any field which needs it. Ends with a newline.
+pub(crate) fn get_csv_record(fields: &[String]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    let mut record = quoted.join(",");
+    record.push('\n');
+    record
+}
+
+/// Gets the short form of an environment for reports, i.e. `INT/EXT`.
+pub(crate) fn get_environment_text(environment: &Environment) -> String {
+    match environment {
+        Environment::Int => "INT".into(),
+        Environment::Ext => "EXT".into(),
+        Environment::Combo(Some(environments)) => environments
+            .iter()
+            .map(get_environment_text)
+            .collect::<Vec<String>>()
+            .join("/"),
+        Environment::Combo(None) => "INT/EXT".into(),
+    }
+}
+
+/// Gets the text of a time of day, as it's written in the scene heading.
+pub(crate) fn get_time_of_day_text(time_of_day: &TimeOfDay) -> String {
+    match time_of_day {
+        TimeOfDay::Day(text)
+        | TimeOfDay::Night(text)
+        | TimeOfDay::Morning(text)
+        | TimeOfDay::Evening(text)
+        | TimeOfDay::Afternoon(text)
+        | TimeOfDay::Extra { string: text, .. } => text.clone(),
+    }
+}